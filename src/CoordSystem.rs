@@ -1,246 +1,824 @@
-use crate::scan_parser::{ScanResponse, GetScanReturn};
-
-pub struct board {
-    board: Vec<Vec<Vec<Point>>>,
-    teammates: Vec<Point>,
-    sidelen: i32,
-}
-
-#[derive(Debug, Clone, Copy)]
-pub struct Point {
-    q: i32,
-    r: i32,
-    s: i32,
-    elevation: i32,
-    occupied_by: char
-}
-
-pub trait board_operations {
-    fn initialize(side_len: i32) -> Self;
-    fn find_path(source: Point,destination: Point) -> String;
-    fn dist(p1: Point, p2: Point) -> u32;
-
-    fn update_board(&mut self, scan:&mut ScanResponse);
-}
-
-impl board_operations for board {
-
-    /**
-     * Initialize a board with a colection of empty points.
-     *   - Empty points has q, r, s = -1.
-     * 
-     * It will add the point that the player has calibrated by adding the points 'q', 'r', 's'
-     * 
-     *  - q: the q coordinate.
-     *  - r: the r coordinate.
-     *  - s: the s coordinate.
-     *  - side_len: The lenght of one side of the board.
-     * 
-     *  - Returns: An initialized version of the board.
-     * 
-     * //////////////////////////////////////////////////////////////////////////////////////
-     * // For better comprehension of the board visit this website:
-     * // https://www.redblobgames.com/grids/hexagons/#line-drawing
-     * /////////////////////////////////////////////////////////////////////////////////////
-     */
-    fn initialize(side_len: i32) -> Self {
-
-        let empty_point: Point = Point { 
-            q: -1, 
-            r: -1, 
-            s: -1, 
-            elevation: -1, 
-            occupied_by: ' '
-        };
-
-        // let new_point: Point = Point { 
-        //     q: q, 
-        //     r: r, 
-        //     s: s, 
-        //     elevation: -1, 
-        //     occupied_by: ' '
-        // };
-
-        let array_len: usize = ((side_len * 2) - 1) as usize;
-
-        
-        let mut board: Vec<Vec<Vec<Point>>> = vec![vec![vec![empty_point; array_len]; array_len]; array_len];
-        //board[q as usize][r as usize][s as usize] = new_point;
-
-        
-        Self { board: board, 
-            teammates: Vec::new(), 
-            sidelen:  side_len}
-    }
-
-    fn find_path(source: Point,destination: Point) -> String {
-        todo!();
-    }
-
-    fn dist(p1: Point, p2: Point) -> u32 {
-        todo!()
-    }
-
-    /**
-     * Add the scan response to the board. The board may have negative coordinates but in the array representation it can't. 
-     * ////////////////////////////////////////////////////////////////////////////////////////////
-     * // Imagene a board with side len = 6.
-     * //   - The coordinated will range from -5 to +5 => -(side len -1) to (side len -1)
-     * //
-     * //  => When q = -5 in the array its position will be on index 0 => [-5 + (side len -1)].
-     * //  => When q = 5 in the array its position will be on index 10 => [5 + (side len -1)].
-     * // 
-     * // This hold true for the oder coordinates 'r' and 's'.
-     * //
-     * /////////////////////////////////////////////////////////////////////////////////////////////
-     * // For better comprehension of the board visit this website:
-     * // https://www.redblobgames.com/grids/hexagons/#line-drawing
-     * /////////////////////////////////////////////////////////////////////////////////////////////
-     * 
-     * The board will add the points returned from a scan and the indexing order will be [q][r][s].
-     */
-    fn update_board(&mut self, scan: &mut ScanResponse) {
-        for points in scan.get_scanned_positions().iter(){
-
-            let q: usize = (points.q + (self.sidelen-1)) as usize;
-            let r: usize = (points.r + (self.sidelen-1)) as usize;
-            let s: usize = (points.s + (self.sidelen-1)) as usize;
-
-            self.board[q][r][s] = points.clone();
-            
-        }
-    }
-}
-pub trait Point_operations {
-    fn new(p: usize, occupied_by: char) -> Point;
-}
-
-impl Point {
-
-    /**
-     * Initialize a point based on the position of the character in the vector returned by scan response.
-     * 
-     *  - p: The position of the entry in the scan respoonse.
-     *  - occupied_by: Check what that tile contains, it could be:
-     *          - terrain type.
-     *          - player.
-     *  - is_scout: a flag to determine if the player is of scout type, since its scan is bigger than the others.
-     */
-    pub fn new(p: usize, occupied_by: char, is_scout: bool) -> Point {
-        let mut new_tile = Point {
-            q: 0,
-            r: 0,
-            s: 0,
-            elevation: -1, // -1 Indicates unset elevation
-            occupied_by
-        };
-
-        let mut q:i32 = 0;
-        let mut r:i32 = 0;
-        let pos:i32 = p as i32;
-
-        // Really bad way to do this.  Is there a formula to map index position to a relative position?
-        if pos < 3 { // abc
-            q = (pos - 1) as i32;
-            if pos == 0 {
-                r = 0;
-            } else {
-                r = -1;
-            }
-        } else if pos >= 3 && pos < 8 { //efg
-            q = (pos - 6) as i32;
-            if pos == 3 {
-                r = 0;
-            } else if pos == 4 {
-                r = -1;
-            } else {
-                r = -2;
-            }
-        }
-        // abc efghi jkl : standard scan
-        // abc efghi jklmn opq
-
-        // Handle if scout or not
-        if is_scout {
-            q = (pos - 12);
-            if pos > 7 {
-                if pos == 8 {
-                    r = -1;
-                } else if pos == 9 {
-                    r = -2;
-                } else {
-                    r = -3;
-                }
-            } else if pos > 12 {
-                q = pos - 14;
-                if pos == 13 {
-                    r = -3;
-                } else {
-                    r = -4;
-                }
-            }
-        } else { // Is a standard scan
-            if pos > 8 {
-                q = pos - 9;
-                if pos == 9 {
-                    r = -2;
-                } else {
-                    r = -3;
-                }
-            }
-        }
-
-        new_tile.q = q;
-        new_tile.r = r;
-        new_tile.s = -q - r;
-
-        new_tile.occupied_by = occupied_by;
-
-        return new_tile;
-    }
-
-    /// Translates a given point by displacement values
-    /// dq: Displacement in the q direction
-    /// dr: Displacement in the r direction
-    /// ds: Displacement in the s direction
-    pub fn translate(&mut self, dq: i32, dr: i32, ds: i32)  {
-        self.q = self.q + dq;
-        self.r = self.r + dr;
-        self.s = self.s + ds;
-    }
-
-    /// Rotates a point/vector where a rotation is changing the axis.  e.g. one rotation of a north-facing vector CW results in a NE-facing vector
-    /// Implemented rotation as defined here:
-    /// https://www.redblobgames.com/grids/hexagons/#rotation
-    pub fn rotate(&mut self, rotations: i32) {
-        if rotations > 0  {
-            for _i in 0..rotations {
-                let tmp = self.s;
-                self.s = self.q;
-                self.q = self.r;
-                self.r = tmp;
-            }
-        } else {
-            for _i in 0..-rotations {
-                let tmp = self.r;
-                self.r = self.q;
-                self.q = self.s;
-                self.s = tmp;
-            }
-        }
-
-        let mut sign = -1 * rotations.abs() % 2;
-        if sign == 0 { sign = 1; }
-
-        self.r = self.r * sign;
-        self.q = self.q * sign;
-        self.s = self.s * sign;
-    }
-
-    fn find_path(&mut self, destination: Point) {
-        todo!()
-    }
-}
-
-
-// Is there a rule way to deserialize
+use crate::move_manager::{NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST, SOUTH_WEST};
+use crate::scan_parser::{ScanResponse, GetScanReturn};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use serde::{Deserialize, Serialize};
+
+/// Every round a tank goes unseen, the chance it is still on the tile where it was spotted is multiplied by this factor.
+pub const TANK_CONFIDENCE_DECAY: f32 = 0.5;
+
+/// Confidence given to tiles we observed with our own scan.
+pub const OWN_SCAN_CONFIDENCE: f32 = 1.0;
+
+/// Confidence given to tiles reported by a teammate, their pose may be slightly off so we trust our own scans more.
+pub const TEAMMATE_SCAN_CONFIDENCE: f32 = 0.8;
+
+/// Letters the server uses to represent tanks on a scan.
+pub const TANK_LETTERS: [char; 6] = ['R', 'O', 'Y', 'G', 'B', 'V'];
+
+/// Relative (q, r) offsets of the standard scan entries 'a' to 'k' for a tank facing north.
+///   - a, b, c: The three hexes in front of the tank at distance 1 (NW, N, NE).
+///   - d to h: The five hexes in front of the tank at distance 2 (NW-NW, NW-N, N-N, N-NE, NE-NE).
+///   - i, j, k: The three hexes straight ahead at distance 3.
+const STANDARD_SCAN_OFFSETS: [(i32, i32); 11] = [
+    (-1, 0), (0, -1), (1, -1),                      // abc
+    (-2, 0), (-1, -1), (0, -2), (1, -2), (2, -2),   // defgh
+    (-1, -2), (0, -3), (1, -3),                     // ijk
+];
+
+/// Number of entries of a standard scan.
+pub const STANDARD_SCAN_LEN: usize = STANDARD_SCAN_OFFSETS.len();
+
+/// Relative (q, r) offsets of the scout scan entries 'a' to 'p' for a tank facing north.
+///   - a to h: Same as the standard scan.
+///   - i to m: The five hexes in front of the tank at distance 3.
+///   - n, o, p: The three hexes straight ahead at distance 4.
+const SCOUT_SCAN_OFFSETS: [(i32, i32); 16] = [
+    (-1, 0), (0, -1), (1, -1),                                  // abc
+    (-2, 0), (-1, -1), (0, -2), (1, -2), (2, -2),               // defgh
+    (-2, -1), (-1, -2), (0, -3), (1, -3), (2, -3),              // ijklm
+    (-1, -3), (0, -4), (1, -4),                                 // nop
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "BoardSnapshot", from = "BoardSnapshot")]
+pub struct board {
+    board: Vec<Vec<Vec<Point>>>,
+    teammates: Vec<Point>,
+    sidelen: i32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Point {
+    q: i32,
+    r: i32,
+    s: i32,
+    #[serde(default = "unset_elevation")]
+    elevation: i32,
+    occupied_by: char,
+    seen_round: u32,    // 0 means the tile was never observed, rounds start at 1.
+    #[serde(default)]
+    seen_step: u32,
+    #[serde(default = "own_scan_confidence")]
+    confidence: f32,    // Confidence of the observation at the moment it was made.
+    #[serde(default)]
+    reported_by: Option<char>,  // Colour of the teammate that reported the tile, None for our own scans.
+}
+
+/**
+ * Serialized form of the board. Only the observed tiles are kept so snapshots stay small and can be written by hand.
+ */
+#[derive(Serialize, Deserialize)]
+struct BoardSnapshot {
+    side_len: i32,
+    teammates: Vec<Point>,
+    tiles: Vec<Point>,
+}
+
+impl From<board> for BoardSnapshot {
+    fn from(game_board: board) -> Self {
+        let tiles: Vec<Point> = game_board.board.iter().flatten().flatten().filter(|tile| tile.is_known()).cloned().collect();
+
+        Self {
+            side_len: game_board.sidelen,
+            teammates: game_board.teammates,
+            tiles: tiles,
+        }
+    }
+}
+
+impl From<BoardSnapshot> for board {
+    fn from(snapshot: BoardSnapshot) -> Self {
+        let mut game_board: board = board::initialize(snapshot.side_len);
+        game_board.teammates = snapshot.teammates;
+
+        for tile in snapshot.tiles {
+            if let Some((q, r, s)) = game_board.to_index(tile.q, tile.r, tile.s) {
+                game_board.board[q][r][s] = tile;
+            }
+        }
+        return game_board;
+    }
+}
+
+fn unset_elevation() -> i32 {
+    -1
+}
+
+fn own_scan_confidence() -> f32 {
+    OWN_SCAN_CONFIDENCE
+}
+
+pub trait board_operations {
+    fn initialize(side_len: i32) -> Self;
+    fn find_path(&self, source: (i32, i32, i32), destination: (i32, i32, i32)) -> Option<Vec<(i32, i32, i32)>>;
+    fn find_weighted_path(&self, source: (i32, i32, i32), destination: (i32, i32, i32), extra_cost: &dyn Fn((i32, i32, i32)) -> u32) -> Option<Vec<(i32, i32, i32)>>;
+    fn frontier(&self) -> Vec<(i32, i32, i32)>;
+    fn dist(p1: Point, p2: Point) -> u32;
+
+    fn update_board(&mut self, scan:&mut ScanResponse, position: (i32, i32, i32), facing: &str, round: u32, step: u32);
+    fn merge_scan(&mut self, scan: &mut ScanResponse, position: (i32, i32, i32), facing: &str, round: u32, step: u32, reporter: Option<char>, confidence: f32);
+    fn merge_tile(&mut self, position: (i32, i32, i32), occupied_by: char, round: u32, step: u32, reporter: Option<char>, confidence: f32);
+    fn set_teammate(&mut self, colour: char, position: (i32, i32, i32), round: u32);
+    fn get_tile(&self, q: i32, r: i32, s: i32) -> Option<Point>;
+    fn get_side_len(&self) -> i32;
+    fn get_teammates(&self) -> Vec<Point>;
+    fn last_seen(&self, q: i32, r: i32, s: i32) -> Option<(u32, u32)>;
+    fn stale_tiles(&self, current_round: u32, max_age: u32) -> Vec<Point>;
+    fn recent_enemies(&self, own_colour: &str, current_round: u32, max_age: u32) -> Vec<Point>;
+    fn tile_confidence(&self, q: i32, r: i32, s: i32, current_round: u32) -> f32;
+    fn tank_probability(&self, q: i32, r: i32, s: i32, current_round: u32) -> f32;
+}
+
+impl board_operations for board {
+
+    /**
+     * Initialize a board with a colection of empty points.
+     *   - Empty points has q, r, s = -1.
+     * 
+     * It will add the point that the player has calibrated by adding the points 'q', 'r', 's'
+     * 
+     *  - q: the q coordinate.
+     *  - r: the r coordinate.
+     *  - s: the s coordinate.
+     *  - side_len: The lenght of one side of the board.
+     * 
+     *  - Returns: An initialized version of the board.
+     * 
+     * //////////////////////////////////////////////////////////////////////////////////////
+     * // For better comprehension of the board visit this website:
+     * // https://www.redblobgames.com/grids/hexagons/#line-drawing
+     * /////////////////////////////////////////////////////////////////////////////////////
+     */
+    fn initialize(side_len: i32) -> Self {
+
+        let empty_point: Point = Point { 
+            q: -1, 
+            r: -1, 
+            s: -1, 
+            elevation: -1, 
+            occupied_by: ' ',
+            seen_round: 0,
+            seen_step: 0,
+            confidence: 0.0,
+            reported_by: None
+        };
+
+        // let new_point: Point = Point { 
+        //     q: q, 
+        //     r: r, 
+        //     s: s, 
+        //     elevation: -1, 
+        //     occupied_by: ' '
+        // };
+
+        let array_len: usize = ((side_len * 2) - 1) as usize;
+
+        
+        let mut board: Vec<Vec<Vec<Point>>> = vec![vec![vec![empty_point; array_len]; array_len]; array_len];
+        //board[q as usize][r as usize][s as usize] = new_point;
+
+        
+        Self { board: board, 
+            teammates: Vec::new(), 
+            sidelen:  side_len}
+    }
+
+    /**
+     * Shortest path between two tiles of the board, going around the walls we know of. Tiles never observed are assumed
+     * to be free, the path is checked again as the tank scans on its way.
+     *
+     *  - Returns: The tiles to drive through, source excluded and destination included, or None if the destination
+     *    can't be reached.
+     */
+    fn find_path(&self, source: (i32, i32, i32), destination: (i32, i32, i32)) -> Option<Vec<(i32, i32, i32)>> {
+        return self.find_weighted_path(source, destination, &|_| 0);
+    }
+
+    /**
+     * Cheapest path between two tiles of the board, like find_path, where driving onto a tile costs 1 plus its extra
+     * cost. For example the threat map gives a cost to the tiles the enemies can shoot at, see ThreatMap::path_cost.
+     */
+    fn find_weighted_path(&self, source: (i32, i32, i32), destination: (i32, i32, i32), extra_cost: &dyn Fn((i32, i32, i32)) -> u32) -> Option<Vec<(i32, i32, i32)>> {
+        let passable = |position: (i32, i32, i32)| -> bool {
+            match self.get_tile(position.0, position.1, position.2) {
+                Some(tile) => tile.occupied_by != 'W',
+                None => false
+            }
+        };
+        if !passable(destination) {
+            return None;
+        }
+
+        // Dijkstra, the cheapest tile is expanded first. Ties keep the order the tiles were reached in.
+        let mut came_from: HashMap<(i32, i32, i32), (i32, i32, i32)> = HashMap::new();
+        let mut costs: HashMap<(i32, i32, i32), u32> = HashMap::new();
+        let mut queue: BinaryHeap<(Reverse<u32>, Reverse<u32>, (i32, i32, i32))> = BinaryHeap::new();
+        let mut order: u32 = 0;
+        costs.insert(source, 0);
+        queue.push((Reverse(0), Reverse(order), source));
+
+        while let Some((Reverse(cost), _, current)) = queue.pop() {
+            if current == destination {
+                let mut path: Vec<(i32, i32, i32)> = Vec::new();
+                let mut step = current;
+                while step != source {
+                    path.push(step);
+                    step = came_from[&step];
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if cost > costs[&current] {
+                continue;
+            }
+
+            for next in neighbours(current) {
+                let next_cost = cost + 1 + extra_cost(next);
+                if passable(next) && costs.get(&next).map_or(true, |known| next_cost < *known) {
+                    costs.insert(next, next_cost);
+                    came_from.insert(next, current);
+                    order += 1;
+                    queue.push((Reverse(next_cost), Reverse(order), next));
+                }
+            }
+        }
+        return None;
+    }
+
+    /**
+     * Return the frontier of the map: the observed tiles, walls excluded, that have a neighbour inside the board that
+     * was never observed.
+     */
+    fn frontier(&self) -> Vec<(i32, i32, i32)> {
+        let max_coord = self.sidelen - 1;
+        let mut frontier: Vec<(i32, i32, i32)> = Vec::new();
+
+        for q in -max_coord..=max_coord {
+            for r in -max_coord..=max_coord {
+                let position = (q, r, -q - r);
+                let known_free = match self.get_tile(q, r, -q - r) {
+                    Some(tile) => tile.is_known() && tile.occupied_by != 'W',
+                    None => false
+                };
+                let borders_unknown = neighbours(position).into_iter().any(|(nq, nr, ns)| {
+                    self.get_tile(nq, nr, ns).map_or(false, |tile| !tile.is_known())
+                });
+
+                if known_free && borders_unknown {
+                    frontier.push(position);
+                }
+            }
+        }
+        return frontier;
+    }
+
+    fn dist(p1: Point, p2: Point) -> u32 {
+        return hex_distance(p1.get_coords(), p2.get_coords());
+    }
+
+    /**
+     * Add the scan response to the board. The board may have negative coordinates but in the array representation it can't. 
+     * ////////////////////////////////////////////////////////////////////////////////////////////
+     * // Imagene a board with side len = 6.
+     * //   - The coordinated will range from -5 to +5 => -(side len -1) to (side len -1)
+     * //
+     * //  => When q = -5 in the array its position will be on index 0 => [-5 + (side len -1)].
+     * //  => When q = 5 in the array its position will be on index 10 => [5 + (side len -1)].
+     * // 
+     * // This hold true for the oder coordinates 'r' and 's'.
+     * //
+     * /////////////////////////////////////////////////////////////////////////////////////////////
+     * // For better comprehension of the board visit this website:
+     * // https://www.redblobgames.com/grids/hexagons/#line-drawing
+     * /////////////////////////////////////////////////////////////////////////////////////////////
+     * 
+     * The scanned points are relative to the tank, so they are rotated by the facing direction and translated by the tank position
+     * before being stored. Each stored tile remembers the round and step it was observed on.
+     *
+     *  - position: The absolute (q, r, s) position of the tank when it scanned.
+     *  - facing: The direction the tank was facing when it scanned.
+     *  - round: The round number the scan was made on.
+     *  - step: The player step the scan was made on.
+     * 
+     * The board will add the points returned from a scan and the indexing order will be [q][r][s].
+     */
+    fn update_board(&mut self, scan: &mut ScanResponse, position: (i32, i32, i32), facing: &str, round: u32, step: u32) {
+        self.merge_scan(scan, position, facing, round, step, None, OWN_SCAN_CONFIDENCE);
+    }
+
+    /**
+     * Merge a scan into the board, it can be our own scan or a scan reported by a teammate.
+     *
+     *  - position, facing: The pose of the tank that scanned, the scanned points are relative to it.
+     *  - round, step: When the scan was made.
+     *  - reporter: The colour of the teammate that reported the scan, None for our own scans.
+     *  - confidence: How much the source of the scan is trusted, see OWN_SCAN_CONFIDENCE and TEAMMATE_SCAN_CONFIDENCE.
+     *
+     * When a tile was already observed, the conflict is resolved as follows:
+     *   1. The most recent round wins.
+     *   2. On the same round, the most trusted source wins.
+     *   3. On the same round and source, the latest step wins. Steps of different tanks can't be compared.
+     */
+    fn merge_scan(&mut self, scan: &mut ScanResponse, position: (i32, i32, i32), facing: &str, round: u32, step: u32, reporter: Option<char>, confidence: f32) {
+        for points in scan.get_scanned_positions().iter(){
+
+            let mut point = points.clone();
+            point.to_absolute(position, facing);
+
+            self.merge_tile(point.get_coords(), point.occupied_by, round, step, reporter, confidence);
+        }
+    }
+
+    /**
+     * Merge a single observed tile into the board, for example an enemy sighting reported by a teammate.
+     * Conflicts are resolved the same way as in merge_scan.
+     */
+    fn merge_tile(&mut self, position: (i32, i32, i32), occupied_by: char, round: u32, step: u32, reporter: Option<char>, confidence: f32) {
+        if let Some((q, r, s)) = self.to_index(position.0, position.1, position.2) {
+            let mut point = self.board[q][r][s];
+            point.q = position.0;
+            point.r = position.1;
+            point.s = position.2;
+            point.occupied_by = occupied_by;
+            point.seen_round = round;
+            point.seen_step = step;
+            point.confidence = confidence;
+            point.reported_by = reporter;
+
+            if point.replaces(&self.board[q][r][s]) {
+                self.board[q][r][s] = point;
+            }
+        }
+    }
+
+    /**
+     * Record the position of a teammate, the previous position of the same teammate is replaced.
+     * The round the teammate was seen on is kept in the point, so the recency of the position can be checked.
+     */
+    fn set_teammate(&mut self, colour: char, position: (i32, i32, i32), round: u32) {
+        let mut teammate = Point::new(0, colour, false);
+        teammate.q = position.0;
+        teammate.r = position.1;
+        teammate.s = position.2;
+        teammate.seen_round = round;
+        teammate.confidence = TEAMMATE_SCAN_CONFIDENCE;
+        teammate.reported_by = Some(colour);
+
+        self.teammates.retain(|known| known.occupied_by != colour);
+        self.teammates.push(teammate);
+    }
+
+    /**
+     * Return the tile at the given coordinates if it is inside the board, observed or not.
+     */
+    fn get_tile(&self, q: i32, r: i32, s: i32) -> Option<Point> {
+        return self.to_index(q, r, s).map(|(q_index, r_index, s_index)| self.board[q_index][r_index][s_index]);
+    }
+
+    /**
+     * Return the number of tiles on one side of the board.
+     */
+    fn get_side_len(&self) -> i32 {
+        return self.sidelen;
+    }
+
+    /**
+     * Return the last known position of our teammates.
+     */
+    fn get_teammates(&self) -> Vec<Point> {
+        return self.teammates.clone();
+    }
+
+    /**
+     * When did we last see this hex.
+     *
+     *  - Returns: The (round, step) of the last observation, or None if the tile was never observed.
+     */
+    fn last_seen(&self, q: i32, r: i32, s: i32) -> Option<(u32, u32)> {
+        return match self.get_tile(q, r, s) {
+            Some(tile) if tile.is_known() => Some((tile.seen_round, tile.seen_step)),
+            _ => None
+        };
+    }
+
+    /**
+     * Return every observed tile that was last seen more than 'max_age' rounds before 'current_round'.
+     * Tiles that were never observed are unknown, not stale, so they are not returned.
+     */
+    fn stale_tiles(&self, current_round: u32, max_age: u32) -> Vec<Point> {
+        let mut stale: Vec<Point> = Vec::new();
+
+        for q_slice in self.board.iter() {
+            for r_slice in q_slice.iter() {
+                for tile in r_slice.iter() {
+                    if tile.is_known() && current_round.saturating_sub(tile.seen_round) > max_age {
+                        stale.push(*tile);
+                    }
+                }
+            }
+        }
+        return stale;
+    }
+
+    /**
+     * Return the tanks on the board seen at most 'max_age' rounds before 'current_round' that are not ours: our own tank
+     * ('own_colour') and our teammates are tanks on the board too.
+     */
+    fn recent_enemies(&self, own_colour: &str, current_round: u32, max_age: u32) -> Vec<Point> {
+        let mut friends: Vec<char> = self.teammates.iter().map(|teammate| teammate.occupied_by).collect();
+        friends.extend(own_colour.chars());
+
+        let max_coord = self.sidelen - 1;
+        let mut enemies: Vec<Point> = Vec::new();
+        for q in -max_coord..=max_coord {
+            for r in -max_coord..=max_coord {
+                if let Some(tile) = self.get_tile(q, r, -q - r) {
+                    if tile.is_tank() && !friends.contains(&tile.occupied_by) && current_round.saturating_sub(tile.seen_round) <= max_age {
+                        enemies.push(tile);
+                    }
+                }
+            }
+        }
+        return enemies;
+    }
+
+    /**
+     * Confidence that the tile still holds what we last saw on it.
+     *   - Static contents (terrain, walls) keep the confidence they were observed with.
+     *   - Tanks move, so their confidence decays by TANK_CONFIDENCE_DECAY for every round they go unseen.
+     *   - Tiles never observed have 0 confidence.
+     */
+    fn tile_confidence(&self, q: i32, r: i32, s: i32, current_round: u32) -> f32 {
+        return match self.get_tile(q, r, s) {
+            Some(tile) if tile.is_known() => {
+                if tile.is_tank() {
+                    let age = current_round.saturating_sub(tile.seen_round) as i32;
+                    tile.confidence * TANK_CONFIDENCE_DECAY.powi(age)
+                } else {
+                    tile.confidence
+                }
+            },
+            _ => 0.0
+        };
+    }
+
+    /**
+     * Probability that the tank we last saw on this tile is still there. It is 0 if no tank was seen on the tile.
+     */
+    fn tank_probability(&self, q: i32, r: i32, s: i32, current_round: u32) -> f32 {
+        return match self.get_tile(q, r, s) {
+            Some(tile) if tile.is_tank() => self.tile_confidence(q, r, s, current_round),
+            _ => 0.0
+        };
+    }
+}
+
+impl board {
+
+    /**
+     * Convert board coordinates to the array indexes, returns None if the coordinates are outside of the board.
+     */
+    fn to_index(&self, q: i32, r: i32, s: i32) -> Option<(usize, usize, usize)> {
+        let max_coord = self.sidelen - 1;
+
+        if q + r + s != 0 || q.abs() > max_coord || r.abs() > max_coord || s.abs() > max_coord {
+            return None;
+        }
+        return Some(((q + max_coord) as usize, (r + max_coord) as usize, (s + max_coord) as usize));
+    }
+}
+
+/**
+ * Return the number of tiles between two (q, r, s) positions.
+ * https://www.redblobgames.com/grids/hexagons/#distances
+ */
+pub fn hex_distance(a: (i32, i32, i32), b: (i32, i32, i32)) -> u32 {
+    return ((a.0 - b.0).abs().max((a.1 - b.1).abs()).max((a.2 - b.2).abs())) as u32;
+}
+
+/**
+ * Return the (q, r, s) displacement of moving one tile towards a direction.
+ */
+pub fn direction_vector(direction: &str) -> (i32, i32, i32) {
+    return match direction {
+        NORTH => (0, -1, 1),
+        NORTH_EAST => (1, -1, 0),
+        SOUTH_EAST => (1, 0, -1),
+        SOUTH => (0, 1, -1),
+        SOUTH_WEST => (-1, 1, 0),
+        NORTH_WEST => (-1, 0, 1),
+        _ => (0, 0, 0)
+    };
+}
+
+/**
+ * Return the six positions around a position, clockwise from north. Some of them may be outside of the board.
+ */
+pub fn neighbours(position: (i32, i32, i32)) -> Vec<(i32, i32, i32)> {
+    return [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST].iter().map(|direction| {
+        let (dq, dr, ds) = direction_vector(direction);
+        (position.0 + dq, position.1 + dr, position.2 + ds)
+    }).collect();
+}
+
+/**
+ * Return the absolute positions covered by a scan made from the given pose, in scan entry order.
+ *  - is_scout: The scan is the bigger scout scan.
+ */
+pub fn scan_area(position: (i32, i32, i32), facing: &str, is_scout: bool) -> Vec<(i32, i32, i32)> {
+    let entries = if is_scout { SCOUT_SCAN_OFFSETS.len() } else { STANDARD_SCAN_OFFSETS.len() };
+    return (0..entries).map(|entry| {
+        let mut point = Point::new(entry, ' ', is_scout);
+        point.to_absolute(position, facing);
+        point.get_coords()
+    }).collect();
+}
+
+pub trait Point_operations {
+    fn new(p: usize, occupied_by: char) -> Point;
+}
+
+impl Point {
+
+    /**
+     * Initialize a point based on the position of the character in the vector returned by scan response.
+     * 
+     *  - p: The position of the entry in the scan respoonse.
+     *  - occupied_by: Check what that tile contains, it could be:
+     *          - terrain type.
+     *          - player.
+     *  - is_scout: a flag to determine if the player is of scout type, since its scan is bigger than the others.
+     */
+    pub fn new(p: usize, occupied_by: char, is_scout: bool) -> Point {
+        let mut new_tile = Point {
+            q: 0,
+            r: 0,
+            s: 0,
+            elevation: -1, // -1 Indicates unset elevation
+            occupied_by,
+            seen_round: 0,
+            seen_step: 0,
+            confidence: 0.0,
+            reported_by: None
+        };
+
+        // abc defgh ijk : standard scan
+        // abc defgh ijklm nop : scout scan
+        let offsets: &[(i32, i32)] = if is_scout { &SCOUT_SCAN_OFFSETS } else { &STANDARD_SCAN_OFFSETS };
+        let (q, r) = match offsets.get(p) {
+            Some(offset) => *offset,
+            None => (0, 0)
+        };
+
+        new_tile.q = q;
+        new_tile.r = r;
+        new_tile.s = -q - r;
+
+        new_tile.occupied_by = occupied_by;
+
+        return new_tile;
+    }
+
+    /**
+     * Convert a point relative to a tank facing north to absolute board coordinates.
+     *
+     *  - origin: The absolute (q, r, s) position of the tank.
+     *  - facing: The direction the tank is facing, the relative point is rotated clockwise until north matches it.
+     */
+    pub fn to_absolute(&mut self, origin: (i32, i32, i32), facing: &str) {
+        let rotations = match facing {
+            NORTH => 0,
+            NORTH_EAST => 1,
+            SOUTH_EAST => 2,
+            SOUTH => 3,
+            SOUTH_WEST => 4,
+            NORTH_WEST => 5,
+            _ => 0
+        };
+
+        if rotations != 0 {
+            self.rotate(rotations);
+        }
+        self.translate(origin.0, origin.1, origin.2);
+    }
+
+    /// Returns the (q, r, s) coordinates of the point.
+    pub fn get_coords(&self) -> (i32, i32, i32) {
+        return (self.q, self.r, self.s);
+    }
+
+    /// Returns what was seen on the tile, terrain type or a player.
+    pub fn get_occupied_by(&self) -> char {
+        return self.occupied_by;
+    }
+
+    /// Returns the round the tile was last observed on, 0 if it was never observed.
+    pub fn get_seen_round(&self) -> u32 {
+        return self.seen_round;
+    }
+
+    /// Returns the player step the tile was last observed on.
+    pub fn get_seen_step(&self) -> u32 {
+        return self.seen_step;
+    }
+
+    /// True if the tile has been observed at least once.
+    pub fn is_known(&self) -> bool {
+        return self.seen_round > 0;
+    }
+
+    /// True if a tank was seen on the tile.
+    pub fn is_tank(&self) -> bool {
+        return TANK_LETTERS.contains(&self.occupied_by);
+    }
+
+    /// Returns the colour of the teammate that reported the tile, None if we observed it ourselves.
+    pub fn get_reported_by(&self) -> Option<char> {
+        return self.reported_by;
+    }
+
+    /**
+     * Check if this observation should replace an older observation of the same tile, see board::merge_scan.
+     */
+    fn replaces(&self, current: &Point) -> bool {
+        if !current.is_known() || self.seen_round > current.seen_round {
+            return true;
+        }
+        if self.seen_round < current.seen_round {
+            return false;
+        }
+        if self.confidence != current.confidence {
+            return self.confidence > current.confidence;
+        }
+        return self.reported_by == current.reported_by && self.seen_step >= current.seen_step;
+    }
+
+    /// Translates a given point by displacement values
+    /// dq: Displacement in the q direction
+    /// dr: Displacement in the r direction
+    /// ds: Displacement in the s direction
+    pub fn translate(&mut self, dq: i32, dr: i32, ds: i32)  {
+        self.q = self.q + dq;
+        self.r = self.r + dr;
+        self.s = self.s + ds;
+    }
+
+    /// Rotates a point/vector where a rotation is changing the axis.  e.g. one rotation of a north-facing vector CW results in a NE-facing vector
+    /// Implemented rotation as defined here:
+    /// https://www.redblobgames.com/grids/hexagons/#rotation
+    pub fn rotate(&mut self, rotations: i32) {
+        if rotations > 0  {
+            for _i in 0..rotations {
+                let tmp = self.s;
+                self.s = self.q;
+                self.q = self.r;
+                self.r = tmp;
+            }
+        } else {
+            for _i in 0..-rotations {
+                let tmp = self.r;
+                self.r = self.q;
+                self.q = self.s;
+                self.s = tmp;
+            }
+        }
+
+        let mut sign = -1 * rotations.abs() % 2;
+        if sign == 0 { sign = 1; }
+
+        self.r = self.r * sign;
+        self.q = self.q * sign;
+        self.s = self.s * sign;
+    }
+
+    fn find_path(&mut self, destination: Point) {
+        todo!()
+    }
+}
+
+
+// Is there a rule way to deserialize
+
+#[cfg(test)]
+mod unit_test {
+    use crate::CoordSystem::*;
+    use crate::parser;
+
+    fn scanned_board(scan_line: &str, round: u32) -> board {
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(parser::get_args(scan_line.to_string()));
+        game_board.update_board(&mut scan, (0, 0, 0), "N", round, 1);
+        return game_board;
+    }
+
+    #[test]
+    fn test_last_seen_after_scan() {
+        let game_board: board = scanned_board("abcdefghijk", 3);
+
+        assert_eq!(Some((3, 1)), game_board.last_seen(0, -1, 1), "The tile in front of the tank was scanned on round 3 step 1.");
+        assert_eq!(None, game_board.last_seen(0, 1, -1), "The tile behind the tank was never scanned.");
+    }
+
+    #[test]
+    fn test_scan_is_rotated_by_facing() {
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(parser::get_args("aWcdefghijk".to_string()));
+        game_board.update_board(&mut scan, (1, 0, -1), "S", 1, 1);
+
+        assert_eq!('W', game_board.get_tile(1, 1, -2).unwrap().get_occupied_by(), "Facing S from (1, 0, -1), the 'b' entry is on (1, 1, -2).");
+    }
+
+    #[test]
+    fn test_merge_teammate_scan() {
+        let mut game_board: board = scanned_board("abcdefghijk", 2);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(parser::get_args("abcdeWghijk".to_string()));
+
+        // The teammate is right behind us, its 'f' entry is our 'b' entry.
+        game_board.merge_scan(&mut scan, (0, 1, -1), "N", 2, 3, Some('G'), TEAMMATE_SCAN_CONFIDENCE);
+        let tile = game_board.get_tile(0, -1, 1).unwrap();
+        assert_eq!('b', tile.get_occupied_by(), "Our own scan of the same round is trusted more than the teammate's.");
+        assert_eq!(None, tile.get_reported_by(), "The tile should still be reported by us.");
+
+        game_board.merge_scan(&mut scan, (0, 1, -1), "N", 3, 1, Some('G'), TEAMMATE_SCAN_CONFIDENCE);
+        let tile = game_board.get_tile(0, -1, 1).unwrap();
+        assert_eq!('W', tile.get_occupied_by(), "The teammate's scan is more recent and should win.");
+        assert_eq!(Some('G'), tile.get_reported_by(), "The tile should now be reported by G.");
+        assert_eq!(Some((3, 1)), game_board.last_seen(0, 0, 0), "Our own tile was scanned by the teammate and should be known.");
+    }
+
+    #[test]
+    fn test_stale_tiles() {
+        let game_board: board = scanned_board("abcdefghijk", 2);
+
+        assert_eq!(0, game_board.stale_tiles(4, 2).len(), "Tiles seen 2 rounds ago are not older than 2 rounds.");
+        assert_eq!(11, game_board.stale_tiles(5, 2).len(), "All the 11 scanned tiles are older than 2 rounds on round 5.");
+    }
+
+    #[test]
+    fn test_recent_enemies() {
+        let mut game_board: board = scanned_board("abcYeRghGjk", 1);
+        game_board.set_teammate('G', (1, -1, 0), 1);
+
+        let enemies: Vec<(i32, i32, i32)> = game_board.recent_enemies("Y", 2, 1).iter().map(|tile| tile.get_coords()).collect();
+        assert_eq!(vec![(0, -2, 2)], enemies, "Only R is an enemy, Y is our tank and G a teammate.");
+        assert!(game_board.recent_enemies("Y", 3, 1).is_empty(), "R was seen 2 rounds ago, that is too old.");
+    }
+
+    #[test]
+    fn test_tank_probability_decays() {
+        let game_board: board = scanned_board("abcdeRghijk", 1);
+
+        assert_eq!(1.0, game_board.tank_probability(0, -2, 2, 1), "A tank seen this round should have full probability.");
+        assert_eq!(0.25, game_board.tank_probability(0, -2, 2, 3), "A tank unseen for 2 rounds should have decayed twice.");
+        assert_eq!(1.0, game_board.tile_confidence(0, -1, 1, 3), "Terrain doesn't move, its confidence shouldn't decay.");
+    }
+
+    #[test]
+    fn test_find_path_goes_around_walls() {
+        let game_board: board = scanned_board("aWcdefghijk", 1);
+
+        let path = game_board.find_path((0, 0, 0), (0, -2, 2)).unwrap();
+        assert_eq!(3, path.len(), "The wall in front of the tank should cost one extra drive.");
+        assert!(!path.contains(&(0, -1, 1)), "The path should not go through the wall.");
+        assert_eq!(None, game_board.find_path((0, 0, 0), (0, -1, 1)), "A wall can't be reached.");
+        assert_eq!(None, game_board.find_path((0, 0, 0), (0, -5, 5)), "A tile outside of the board can't be reached.");
+    }
+
+    #[test]
+    fn test_frontier() {
+        let game_board: board = scanned_board("aWcdefghijk", 1);
+        let frontier = game_board.frontier();
+
+        assert!(frontier.contains(&(-1, 0, 1)), "The 'a' tile borders the unknown tiles behind the tank.");
+        assert!(!frontier.contains(&(0, -1, 1)), "Walls are not part of the frontier.");
+        assert!(frontier.iter().all(|(q, r, s)| game_board.get_tile(*q, *r, *s).unwrap().is_known()), "The frontier is made of observed tiles.");
+    }
+
+    #[test]
+    fn test_scout_scan_shape() {
+        let area = scan_area((0, 0, 0), "N", true);
+
+        assert_eq!(16, area.len(), "The scout scan has 16 entries.");
+        assert_eq!(scan_area((0, 0, 0), "N", false)[..8], area[..8], "The first two rows are the same as the standard scan.");
+        assert_eq!((0, -4, 4), area[14], "The 'o' entry is four hexes straight ahead.");
+        assert!(area[8..13].iter().all(|hex| hex_distance(*hex, (0, 0, 0)) == 3), "The third row is three hexes away.");
+
+        let scan = scanned_board("abcdefghijklmnoR", 1);
+        assert!(scan.get_tile(1, -4, 3).unwrap().is_tank(), "A tank on the last entry of a scout scan should be mapped four hexes away.");
+    }
+
+    #[test]
+    fn test_weighted_path_avoids_costly_tiles() {
+        let game_board: board = board::initialize(5);
+        let costly = |position: (i32, i32, i32)| -> u32 { if position == (0, -1, 1) { 5 } else { 0 } };
+
+        let path = game_board.find_weighted_path((0, 0, 0), (0, -2, 2), &costly).unwrap();
+        assert_eq!(3, path.len(), "Going around the costly tile takes one more drive but is cheaper.");
+        assert!(!path.contains(&(0, -1, 1)), "The path should avoid the costly tile.");
+        assert_eq!(2, game_board.find_path((0, 0, 0), (0, -2, 2)).unwrap().len(), "Without extra cost the straight path is the shortest.");
+    }
+}
@@ -1,114 +1,181 @@
-use crate::player::{ Player, PlayerState};
-
-#[derive(Debug, Clone, Copy)]
-pub enum playerOutput{
-    SCAN,
-    TURN,
-    END,
-    SHOOT,
-    SKIP,
-    DRIVE,
-    NONE
-}
-
-pub struct action_manager {
-    last_action: playerOutput,
-    round_action_counter: u32
-}
-
-pub trait manage_player_action{
-    fn initialize() -> Self;
-    fn get_last_action(&mut self) -> playerOutput;
-    fn get_action_counter(&mut self) -> u32;
-    fn reset_counter(&mut self);
-    fn shoot(&mut self, direction: &str, player: &mut Player); // ======> should't be done at player's model, state machine should do the action and the update the model accordingly.
-    fn turn(&mut self, direction: &str, player: &mut Player);  // ======> same
-    fn drive(&mut self, player: &mut Player);                  // ======> same
-    fn scan(&mut self, player: &mut Player);   // ======> same
-    fn skip(&mut self, player: &mut Player);                   // ======> same
-    fn end(&mut self);  
-}
-
-impl manage_player_action for action_manager{
-
-    fn initialize() -> Self {
-        Self { 
-            last_action: playerOutput::NONE,
-            round_action_counter: 0
-        }
-    }
-    
-    fn get_last_action(&mut self) -> playerOutput {
-        return self.last_action;
-    }
-
-    fn reset_counter(&mut self) {
-        self.round_action_counter = 0;
-    }
-
-    fn get_action_counter(&mut self) -> u32 {
-        return self.round_action_counter;
-    }
-
-     /**
-     * Send a shoot message to server.
-     * direction: for example SHOOT N or SHOOT N-E
-     */
-    fn shoot(&mut self, direction: &str,  player: &mut Player) {
-        player.add_shoot_action();
-        player.add_step();
-        println!("{}", direction);
-        self.last_action = playerOutput::SHOOT;
-        self.round_action_counter += 1;
-    }
-
-    /**
-     * Send a turn message to the server
-     * direction: for example N or NE
-     */
-    fn turn(&mut self, direction: &str,  player: &mut Player) {
-        println!("TURN {} ", direction);
-        player.update_facing_direction(direction);
-        self.last_action = playerOutput::TURN;
-        self.round_action_counter += 1;
-    }
-
-    /**
-     * Send a drive action to the server
-     */
-    fn drive(&mut self,  player: &mut Player) {
-        player.add_drive_action();
-        player.add_step();
-        println!("DRIVE");
-        self.last_action = playerOutput::DRIVE;
-        self.round_action_counter += 1;
-    }
-
-    /**
-     * Send and receive a scan message to the server
-     */
-    fn scan(&mut self,  player: &mut Player) {
-        player.add_scan_action();
-        player.add_step();
-        println!("SCAN");
-        self.last_action = playerOutput::SCAN;
-        self.round_action_counter += 1;
-    }
-
-    /**
-     * Send a scan message to the server
-     */
-    fn skip(&mut self,  player: &mut Player) {
-       player.add_skip_action();
-       player.add_step();
-        println!("SKIP");
-        self.last_action = playerOutput::SKIP;
-        self.round_action_counter += 1;
-    }
-
-    fn end(&mut self) {
-        println!("END");
-        self.last_action = playerOutput::END;
-        self.round_action_counter += 1;
-    }
-}
+use crate::player::{ Player, PlayerState};
+use crate::CoordSystem::board;
+use crate::fire_guard::{FireGuard, friendly_fire_guard};
+use crate::strategy::Command;
+
+#[derive(Debug, Clone, Copy)]
+pub enum playerOutput{
+    SCAN,
+    TURN,
+    END,
+    SHOOT,
+    SKIP,
+    DRIVE,
+    NONE
+}
+
+pub struct action_manager {
+    last_action: playerOutput,
+    round_action_counter: u32,
+    round_shots: Vec<(String, (i32, i32, i32))>,
+    fire_guard: FireGuard
+}
+
+pub trait manage_player_action{
+    fn initialize() -> Self;
+    fn get_last_action(&mut self) -> playerOutput;
+    fn get_action_counter(&mut self) -> u32;
+    fn reset_counter(&mut self);
+    fn get_round_shots(&self) -> Vec<(String, (i32, i32, i32))>;
+    fn update_fire_guard(&mut self, game_board: &board, round: u32);
+    #[must_use = "nothing is sent when a shot is vetoed, the server still waits for a command"]
+    fn execute(&mut self, command: &Command, player: &mut Player) -> bool;
+    #[must_use = "nothing is sent when a shot is vetoed, the server still waits for a command"]
+    fn shoot(&mut self, direction: &str, player: &mut Player) -> bool; // ======> should't be done at player's model, state machine should do the action and the update the model accordingly.
+    fn turn(&mut self, direction: &str, player: &mut Player);  // ======> same
+    fn drive(&mut self, player: &mut Player);                  // ======> same
+    fn scan(&mut self, player: &mut Player);   // ======> same
+    fn skip(&mut self, player: &mut Player);                   // ======> same
+    fn end(&mut self);  
+}
+
+impl manage_player_action for action_manager{
+
+    fn initialize() -> Self {
+        Self { 
+            last_action: playerOutput::NONE,
+            round_action_counter: 0,
+            round_shots: Vec::new(),
+            fire_guard: FireGuard::initialize()
+        }
+    }
+    
+    fn get_last_action(&mut self) -> playerOutput {
+        return self.last_action;
+    }
+
+    fn reset_counter(&mut self) {
+        self.round_action_counter = 0;
+        self.round_shots.clear();
+    }
+
+    /**
+     * Return the shoot commands sent since the counter was last reset with the player position they were fired from,
+     * for example: [("SHOOT N-NE", (2, -1, -1))]
+     */
+    fn get_round_shots(&self) -> Vec<(String, (i32, i32, i32))> {
+        return self.round_shots.clone();
+    }
+
+    fn get_action_counter(&mut self) -> u32 {
+        return self.round_action_counter;
+    }
+
+    /**
+     * Give the friendly fire guard the teammate positions known at the start of the round.
+     */
+    fn update_fire_guard(&mut self, game_board: &board, round: u32) {
+        self.fire_guard.update_teammates(game_board, round);
+    }
+
+    /**
+     * Send the command chosen by a strategy to the server.
+     *
+     * Returns: false if the command was not sent, see shoot. The server still waits for a command then, the caller has
+     * to send another one.
+     */
+    fn execute(&mut self, command: &Command, player: &mut Player) -> bool {
+        match command {
+            Command::Scan => self.scan(player),
+            Command::Drive => self.drive(player),
+            Command::Turn(direction) => self.turn(direction, player),
+            Command::Shoot(shot) => return self.shoot(shot, player),
+            Command::Skip => self.skip(player),
+            Command::End => self.end(),
+        }
+        return true;
+    }
+
+     /**
+     * Send a shoot message to server.
+     * direction: for example SHOOT N or SHOOT N-E
+     *
+     * The shot is checked by the friendly fire guard first. A shot going through a teammate is sent in another order
+     * that avoids it, or not sent at all when there is none. Before the tank is calibrated its position is unknown and
+     * the shot can't be checked.
+     *
+     * Returns: true if a shot was sent. On a veto nothing is printed and the action counters don't change, so the caller
+     * can send another command instead.
+     */
+    fn shoot(&mut self, direction: &str,  player: &mut Player) -> bool {
+        let command = if player.is_calibrated() {
+            match self.fire_guard.safe_shot(player.get_position(), direction) {
+                Some(command) => command,
+                None => {
+                    eprintln!("Friendly fire: '{}' goes through a teammate, not shooting.", direction);
+                    return false;
+                }
+            }
+        } else {
+            direction.to_string()
+        };
+
+        player.add_shoot_action();
+        player.add_step();
+        println!("{}", command);
+        self.round_shots.push((command, player.get_position()));
+        self.last_action = playerOutput::SHOOT;
+        self.round_action_counter += 1;
+        return true;
+    }
+
+    /**
+     * Send a turn message to the server
+     * direction: for example N or NE
+     */
+    fn turn(&mut self, direction: &str,  player: &mut Player) {
+        println!("TURN {} ", direction);
+        player.update_facing_direction(direction);
+        self.last_action = playerOutput::TURN;
+        self.round_action_counter += 1;
+    }
+
+    /**
+     * Send a drive action to the server
+     */
+    fn drive(&mut self,  player: &mut Player) {
+        player.add_drive_action();
+        player.add_step();
+        println!("DRIVE");
+        self.last_action = playerOutput::DRIVE;
+        self.round_action_counter += 1;
+    }
+
+    /**
+     * Send and receive a scan message to the server
+     */
+    fn scan(&mut self,  player: &mut Player) {
+        player.add_scan_action();
+        player.add_step();
+        println!("SCAN");
+        self.last_action = playerOutput::SCAN;
+        self.round_action_counter += 1;
+    }
+
+    /**
+     * Send a scan message to the server
+     */
+    fn skip(&mut self,  player: &mut Player) {
+       player.add_skip_action();
+       player.add_step();
+        println!("SKIP");
+        self.last_action = playerOutput::SKIP;
+        self.round_action_counter += 1;
+    }
+
+    fn end(&mut self) {
+        println!("END");
+        self.last_action = playerOutput::END;
+        self.round_action_counter += 1;
+    }
+}
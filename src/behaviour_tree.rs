@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::action_manager::playerOutput;
+use crate::move_manager::construct_shoot_move;
+use crate::move_manager::{NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST, SOUTH_WEST};
+use crate::player::PlayerState;
+use crate::scan_parser::GetScanReturn;
+use crate::strategy::{Command, GameView};
+
+/// Directions in clockwise order, used by the turn actions.
+const CLOCKWISE: [&str; 6] = [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST];
+
+/// Conditions a leaf can check, see check_condition.
+pub const CONDITIONS: [&str; 10] = [
+    "can_scan", "can_drive", "can_shoot", "scanned_this_round", "last_action_scan", "enemy_in_scan", "path_clear",
+    "has_target", "calibrated", "low_health",
+];
+
+/// Actions a leaf can take, see take_action.
+pub const ACTIONS: [&str; 7] = ["scan", "drive", "turn_left", "turn_right", "shoot_enemy", "skip", "end"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Success,
+    Failure,
+}
+
+/**
+ * Node of a behaviour tree.
+ *  - Sequence: Runs its children in order until one fails.
+ *  - Selector: Runs its children in order until one succeeds.
+ *  - Parallel: Runs all its children, succeeds if at least 'threshold' of them succeed.
+ *  - Not: Inverts the result of its child.
+ *  - Succeed: Always succeeds, whatever its child returns.
+ *  - Limit: Lets its child choose at most 'times' commands per round, fails after that. A tick of the child that
+ *    chooses no command is not counted. The count is kept in the blackboard.
+ *  - Condition: Query on the player, the board or the last scan.
+ *  - Action: Chooses the command to send. Only the first action reached in a tick is taken, the tick then stops.
+ */
+#[derive(Debug, Clone)]
+pub enum Node {
+    Sequence(Vec<Node>),
+    Selector(Vec<Node>),
+    Parallel { threshold: usize, children: Vec<Node> },
+    Not(Box<Node>),
+    Succeed(Box<Node>),
+    Limit { id: usize, times: i64, child: Box<Node> },
+    Condition(String),
+    Action(String),
+}
+
+/**
+ * Memory of a tree between ticks. Entries starting with "round:" are cleared at the start of each round.
+ */
+#[derive(Debug, Default)]
+pub struct Blackboard {
+    values: HashMap<String, i64>,
+}
+
+impl Blackboard {
+    pub fn get(&self, key: &str) -> i64 {
+        return *self.values.get(key).unwrap_or(&0);
+    }
+
+    pub fn set(&mut self, key: &str, value: i64) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    /// Forget the entries that only last one round.
+    pub fn start_round(&mut self) {
+        self.values.retain(|key, _| !key.starts_with("round:"));
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TreeParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TreeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/**
+ * Behaviour tree with its blackboard. Trees are written as text, one node per line and children indented below their
+ * parent. Lines starting with '#' are comments. For example:
+ *
+ * ```text
+ *     selector
+ *       sequence
+ *         enemy_in_scan
+ *         can_shoot
+ *         shoot_enemy
+ *       sequence
+ *         not
+ *           scanned_this_round
+ *         scan
+ *       end
+ * ```
+ *
+ * 'parallel' takes the number of children that must succeed (all of them by default), 'limit' the number of commands
+ * its child can choose per round.
+ */
+pub struct BehaviourTree {
+    root: Node,
+    blackboard: Blackboard,
+}
+
+impl BehaviourTree {
+
+    /**
+     * Parse a tree from its text.
+     */
+    pub fn parse(text: &str) -> Result<Self, TreeParseError> {
+        let mut lines: Vec<(usize, usize, Vec<&str>)> = Vec::new(); // (line number, indentation, words)
+        for (index, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indentation = line.len() - line.trim_start().len();
+            lines.push((index + 1, indentation, trimmed.split_whitespace().collect()));
+        }
+
+        if lines.is_empty() {
+            return Err(TreeParseError { line: 0, message: "the tree is empty".to_string() });
+        }
+
+        let mut position = 0;
+        let mut next_id = 0;
+        let root = parse_node(&lines, &mut position, &mut next_id)?;
+        if position < lines.len() {
+            return Err(TreeParseError { line: lines[position].0, message: "a tree has a single root".to_string() });
+        }
+
+        return Ok(Self { root: root, blackboard: Blackboard::default() });
+    }
+
+    /**
+     * Forget what only lasts one round, to be called at the start of each round.
+     */
+    pub fn start_round(&mut self) {
+        self.blackboard.start_round();
+    }
+
+    /**
+     * Run the tree once and return the command chosen by the first action reached, if any.
+     */
+    pub fn tick(&mut self, view: &GameView) -> Option<Command> {
+        let mut command: Option<Command> = None;
+        tick_node(&self.root, view, &mut self.blackboard, &mut command);
+        return command;
+    }
+
+    pub fn get_blackboard(&self) -> &Blackboard {
+        return &self.blackboard;
+    }
+}
+
+/**
+ * Parse the node on lines[position] and its children, leaving position on the line after them.
+ */
+fn parse_node(lines: &[(usize, usize, Vec<&str>)], position: &mut usize, next_id: &mut usize) -> Result<Node, TreeParseError> {
+    let (line, indentation, words) = &lines[*position];
+    *position += 1;
+
+    let mut children: Vec<Node> = Vec::new();
+    while *position < lines.len() && lines[*position].1 > *indentation {
+        children.push(parse_node(lines, position, next_id)?);
+    }
+
+    let error = |message: &str| TreeParseError { line: *line, message: format!("{}: {}", words[0], message) };
+    let number = |default: i64| -> Result<i64, TreeParseError> {
+        match words.get(1) {
+            Some(word) => word.parse::<i64>().map_err(|_| error("the argument should be a number")),
+            None => Ok(default),
+        }
+    };
+    let single_child = |children: &mut Vec<Node>| -> Result<Box<Node>, TreeParseError> {
+        if children.len() != 1 {
+            return Err(error("a decorator should have exactly one child"));
+        }
+        return Ok(Box::new(children.remove(0)));
+    };
+
+    let node = match words[0] {
+        "sequence" | "selector" | "parallel" if children.is_empty() => return Err(error("a composite should have children")),
+        "sequence" => Node::Sequence(children),
+        "selector" => Node::Selector(children),
+        "parallel" => Node::Parallel { threshold: number(children.len() as i64)? as usize, children: children },
+        "not" => Node::Not(single_child(&mut children)?),
+        "succeed" => Node::Succeed(single_child(&mut children)?),
+        "limit" => {
+            *next_id += 1;
+            Node::Limit { id: *next_id, times: number(1)?, child: single_child(&mut children)? }
+        },
+        name if CONDITIONS.contains(&name) || ACTIONS.contains(&name) => {
+            if !children.is_empty() {
+                return Err(error("a leaf can't have children"));
+            }
+            if CONDITIONS.contains(&name) { Node::Condition(name.to_string()) } else { Node::Action(name.to_string()) }
+        },
+        _ => return Err(error("unknown node")),
+    };
+    return Ok(node);
+}
+
+fn tick_node(node: &Node, view: &GameView, blackboard: &mut Blackboard, command: &mut Option<Command>) -> Status {
+    // Once an action was chosen nothing else runs, the status only matters to unwind the tree.
+    if command.is_some() {
+        return Status::Failure;
+    }
+
+    match node {
+        Node::Sequence(children) => {
+            for child in children {
+                if tick_node(child, view, blackboard, command) == Status::Failure || command.is_some() {
+                    return if command.is_some() { Status::Success } else { Status::Failure };
+                }
+            }
+            Status::Success
+        },
+        Node::Selector(children) => {
+            for child in children {
+                if tick_node(child, view, blackboard, command) == Status::Success || command.is_some() {
+                    return Status::Success;
+                }
+            }
+            Status::Failure
+        },
+        Node::Parallel { threshold, children } => {
+            let successes = children.iter().filter(|child| tick_node(child, view, blackboard, command) == Status::Success).count();
+            if successes >= *threshold || command.is_some() { Status::Success } else { Status::Failure }
+        },
+        Node::Not(child) => match tick_node(child, view, blackboard, command) {
+            Status::Success => Status::Failure,
+            Status::Failure => Status::Success,
+        },
+        Node::Succeed(child) => {
+            tick_node(child, view, blackboard, command);
+            Status::Success
+        },
+        Node::Limit { id, times, child } => {
+            let key = format!("round:limit:{}", id);
+            let count = blackboard.get(&key);
+            if count >= *times {
+                return Status::Failure;
+            }
+            let status = tick_node(child, view, blackboard, command);
+            if command.is_some() {
+                blackboard.set(&key, count + 1);
+            }
+            status
+        },
+        Node::Condition(name) => if check_condition(name, view) { Status::Success } else { Status::Failure },
+        Node::Action(name) => match take_action(name, view) {
+            Some(chosen) => {
+                *command = Some(chosen);
+                Status::Success
+            },
+            None => Status::Failure,
+        },
+    }
+}
+
+/**
+ * Check a condition leaf.
+ *  - can_scan, can_drive, can_shoot: The player has that action left this round.
+ *  - scanned_this_round: The player scanned this round, so the last scan is fresh.
+ *  - last_action_scan: The last action was a scan, the scan still describes what is around the tank.
+ *  - enemy_in_scan: The last scan has an enemy the tank can shoot at.
+ *  - path_clear: Right after a scan, the tile in front of the tank is neither a wall nor an enemy.
+ *  - has_target: The team chose an enemy to focus fire on.
+ *  - calibrated: The tank knows its position.
+ *  - low_health: The tank has one health point left.
+ */
+fn check_condition(name: &str, view: &GameView) -> bool {
+    let player = view.player;
+    return match name {
+        "can_scan" => player.get_scan_actions_check(),
+        "can_drive" => player.get_drive_actions_check(),
+        "can_shoot" => player.get_shoot_action_check(),
+        "scanned_this_round" => player.get_scan_action_count() > 0,
+        "last_action_scan" => matches!(view.last_action, playerOutput::SCAN),
+        "enemy_in_scan" => shootable_enemy(view).is_some(),
+        "path_clear" => matches!(view.last_action, playerOutput::SCAN) && !view.scan.get_walls().contains(&1) && !view.scan.get_enemies().contains(&1),
+        "has_target" => view.target.is_some(),
+        "calibrated" => player.is_calibrated(),
+        "low_health" => player.get_health() <= 1,
+        _ => false,
+    };
+}
+
+/**
+ * Return the command of an action leaf, or None if the action can't be taken.
+ */
+fn take_action(name: &str, view: &GameView) -> Option<Command> {
+    let facing = view.player.get_facing_direction();
+    let index = CLOCKWISE.iter().position(|direction| *direction == facing.as_str()).unwrap_or(0);
+
+    return match name {
+        "scan" => Some(Command::Scan),
+        "drive" => Some(Command::Drive),
+        "turn_left" => Some(Command::Turn(CLOCKWISE[(index + 5) % 6].to_string())),
+        "turn_right" => Some(Command::Turn(CLOCKWISE[(index + 1) % 6].to_string())),
+        "shoot_enemy" => shootable_enemy(view).map(|enemy| Command::Shoot(construct_shoot_move(facing.clone(), enemy))),
+        "skip" => Some(Command::Skip),
+        "end" => Some(Command::End),
+        _ => None,
+    };
+}
+
+/**
+ * Index in the last scan of the first enemy a shot can reach.
+ */
+fn shootable_enemy(view: &GameView) -> Option<usize> {
+    return view.scan.get_enemies().into_iter().find(|enemy| *enemy <= 7);
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::behaviour_tree::*;
+    use crate::game::EnemyTracker;
+    use crate::CoordSystem::{board, board_operations};
+    use crate::parser;
+    use crate::player::{Player, tank_type};
+    use crate::scan_parser::ScanResponse;
+
+    const TREE: &str = "
+# shoot what is in front of us, otherwise look around once
+selector
+  sequence
+    scanned_this_round
+    enemy_in_scan
+    shoot_enemy
+  sequence
+    not
+      scanned_this_round
+    scan
+  limit 1
+    turn_right
+  end
+";
+
+    #[test]
+    fn test_tick_follows_the_tree() {
+        let mut tree = BehaviourTree::parse(TREE).unwrap();
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        let game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(Some(Command::Scan), tree.tick(&view), "Nothing was scanned this round, the tank should scan.");
+
+        player.add_scan_action();
+        scan.scan_entry(parser::get_args("abcdeRghijk".to_string()));
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        assert_eq!(Some(Command::Shoot("SHOOT N-N".to_string())), tree.tick(&view), "The enemy on 'f' should be shot.");
+
+        scan.scan_entry(parser::get_args("abcdefghijk".to_string()));
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        assert_eq!(Some(Command::Turn("NE".to_string())), tree.tick(&view), "Without an enemy the tank should turn once.");
+        assert_eq!(Some(Command::End), tree.tick(&view), "The turn is limited to once per round.");
+
+        tree.start_round();
+        assert_eq!(Some(Command::Turn("NE".to_string())), tree.tick(&view), "The limit should be reset on a new round.");
+    }
+
+    #[test]
+    fn test_limit_counts_chosen_commands() {
+        let mut tree = BehaviourTree::parse("selector\n  limit 1\n    sequence\n      last_action_scan\n      turn_right\n  end\n").unwrap();
+        let player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(Some(Command::End), tree.tick(&view), "Without a scan the child chooses nothing.");
+        assert_eq!(Some(Command::End), tree.tick(&view), "Without a scan the child still chooses nothing.");
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        assert_eq!(Some(Command::Turn("NE".to_string())), tree.tick(&view), "Ticks that chose no command should not use up the limit.");
+        assert_eq!(Some(Command::End), tree.tick(&view), "The child already chose its one command of the round.");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(2, BehaviourTree::parse("selector\n  fly\n").err().unwrap().line, "An unknown node should be reported with its line.");
+        assert!(BehaviourTree::parse("not\n  scan\n  drive\n").is_err(), "A decorator has a single child.");
+        assert!(BehaviourTree::parse("scan\ndrive\n").is_err(), "A tree has a single root.");
+        assert!(BehaviourTree::parse("sequence\n").is_err(), "A composite needs children.");
+    }
+}
@@ -0,0 +1,217 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+
+use crate::CoordSystem::{board, board_operations, Point};
+use crate::player::{Player, PlayerState};
+use crate::threat_map::ThreatMap;
+
+/**
+ * Text renderer for what the robot believes about the arena. Used for debugging only, the output should go to stderr
+ * because stdout is reserved to talk with the server.
+ *
+ * The board is drawn flat-top with north pointing up. Each hex is 3 characters wide and the columns are staggered,
+ * so (q, r) is drawn on text row 2r + q and text column 3q.
+ *
+ *  Legend:
+ *   - ` ? `: Tile never observed.
+ *   - ` . `: Tile observed and not blocked.
+ *   - `###`: Wall.
+ *   - `@NE`: Our tank and its facing direction.
+ *   - `[G]`: Teammate.
+ *   - `R2 `: Enemy R, last seen 2 rounds ago (capped at 9).
+ *   - ` * `: Planned path.
+ */
+const CELL_WIDTH: usize = 3;
+
+pub const LEGEND: &str = "' ? ' unknown  ' . ' open  '###' wall  '@NE' us  '[G]' teammate  'R2 ' enemy seen 2 rounds ago  ' * ' path";
+
+pub const THREAT_LEGEND: &str = "' 2 ' enemies that could shoot the hex next round  ' . ' no threat  '###' wall  '@NE' us";
+
+/**
+ * Render the board as text.
+ *
+ *  - game_board: The board to draw.
+ *  - player: Our player, its position is only drawn once it is calibrated.
+ *  - path: Tiles of the planned path to overlay on top of the board.
+ *
+ *  - Returns: The multi-line text of the board, starting with a header that has the round number.
+ */
+pub fn render_board(game_board: &board, player: &Player, path: &[(i32, i32, i32)]) -> String {
+    return render_cells(game_board, player, |tile, coords| render_cell(game_board, player, path, tile, coords));
+}
+
+/**
+ * Render the threat map as text, each hex shows how many enemies could shoot it next round (capped at 9).
+ */
+pub fn render_threat_map(game_board: &board, player: &Player, threat: &ThreatMap) -> String {
+    return render_cells(game_board, player, |tile, coords| {
+        if player.is_calibrated() && player.get_position() == coords {
+            return format!("@{:<2}", player.get_facing_direction());
+        }
+        if tile.get_occupied_by() == 'W' {
+            return "###".to_string();
+        }
+        return match threat.get_threat(coords) {
+            0 => " . ".to_string(),
+            count => format!(" {} ", count.min(9)),
+        };
+    })
+}
+
+/**
+ * Print the threat map to stderr, with its legend.
+ */
+pub fn print_threat_map(game_board: &board, player: &Player, threat: &ThreatMap) {
+    eprint!("{}", render_threat_map(game_board, player, threat));
+    eprintln!("{}", THREAT_LEGEND);
+}
+
+/**
+ * Draw every tile of the board with the given cell renderer, under a header with the round number.
+ */
+fn render_cells<F: Fn(&Point, (i32, i32, i32)) -> String>(game_board: &board, player: &Player, render: F) -> String {
+    let max_coord = game_board.get_side_len() - 1;
+    let rows = (4 * max_coord + 1) as usize;
+    let columns = ((2 * max_coord + 1) as usize) * CELL_WIDTH;
+
+    let mut canvas: Vec<Vec<char>> = vec![vec![' '; columns]; rows];
+
+    for q in -max_coord..=max_coord {
+        for r in -max_coord..=max_coord {
+            let s = -q - r;
+
+            if let Some(tile) = game_board.get_tile(q, r, s) {
+                let cell = render(&tile, (q, r, s));
+
+                let row = (2 * r + q + 2 * max_coord) as usize;
+                let column = ((q + max_coord) as usize) * CELL_WIDTH;
+                for (offset, character) in cell.chars().take(CELL_WIDTH).enumerate() {
+                    canvas[row][column + offset] = character;
+                }
+            }
+        }
+    }
+
+    let mut output = format!("==== ROUND {} ====\n", player.get_rounds());
+    for line in canvas.iter() {
+        let text: String = line.iter().collect();
+        output.push_str(text.trim_end());
+        output.push('\n');
+    }
+    return output;
+}
+
+/**
+ * Print the board to stderr, with the legend.
+ */
+pub fn print_board(game_board: &board, player: &Player, path: &[(i32, i32, i32)]) {
+    eprint!("{}", render_board(game_board, player, path));
+    eprintln!("{}", LEGEND);
+}
+
+/**
+ * Append the board to the given file, with the legend, so the rounds of a game can be read back after it.
+ */
+pub fn append_board(file_name: &str, game_board: &board, player: &Player, path: &[(i32, i32, i32)]) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(file_name)?;
+    write!(file, "{}", render_board(game_board, player, path))?;
+    return writeln!(file, "{}", LEGEND);
+}
+
+/**
+ * Return the 3 characters used to draw one tile. Our tank is drawn over teammates, which are drawn over the path,
+ * which is drawn over what the tile contains.
+ */
+fn render_cell(game_board: &board, player: &Player, path: &[(i32, i32, i32)], tile: &Point, coords: (i32, i32, i32)) -> String {
+    if player.is_calibrated() && player.get_position() == coords {
+        let mut facing_player = player.get_facing_direction();
+        facing_player.insert(0, '@');
+        return format!("{:<3}", facing_player);
+    }
+
+    if let Some(teammate) = game_board.get_teammates().iter().find(|teammate| teammate.get_coords() == coords) {
+        return format!("[{}]", teammate.get_occupied_by());
+    }
+
+    if path.contains(&coords) {
+        return " * ".to_string();
+    }
+
+    if !tile.is_known() {
+        return " ? ".to_string();
+    }
+
+    if tile.is_tank() {
+        let age = player.get_rounds().saturating_sub(tile.get_seen_round()).min(9);
+        return format!("{}{} ", tile.get_occupied_by(), age);
+    }
+
+    return match tile.get_occupied_by() {
+        'W' => "###".to_string(),
+        _ => " . ".to_string(),
+    };
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::board_renderer::*;
+    use crate::parser;
+    use crate::player::tank_type;
+    use crate::scan_parser::{GetScanReturn, ScanResponse};
+    use crate::game::{EnemiesPos, EnemyTracker, TeamColor};
+
+    #[test]
+    fn test_render_scanned_board() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 100 as u32, 3, tank_type::tank);
+        player.calibrate(0, 0, 0);
+
+        let mut game_board: board = board::initialize(3);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(parser::get_args("aWcdeRghijk".to_string()));
+        game_board.update_board(&mut scan, (0, 0, 0), "N", 1, 1);
+
+        let rendered = render_board(&game_board, &player, &[(0, 1, -1)]);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!("==== ROUND 1 ====", lines[0], "The first line should be the round header.");
+        assert_eq!("      R0", lines[1], "The enemy seen this round on (0, -2, 2) should be on the top row.");
+        assert_eq!(" .    ###    .", lines[3], "The wall on (0, -1, 1) should be between 'd' and 'h'.");
+        assert_eq!(" ?    @N     ?", lines[5], "The tank should be at the centre facing N, with unknown tiles on its sides.");
+        assert_eq!(" ?     *     ?", lines[7], "The planned path tile should be right below the tank.");
+    }
+
+    #[test]
+    fn test_append_board_to_file() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 100 as u32, 3, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(3);
+        let file_name = std::env::temp_dir().join(format!("board_renderer_{}.txt", std::process::id()));
+        let file_name = file_name.to_str().unwrap();
+        let _ = std::fs::remove_file(file_name);
+
+        append_board(file_name, &game_board, &player, &[]).unwrap();
+        append_board(file_name, &game_board, &player, &[]).unwrap();
+        let written = std::fs::read_to_string(file_name).unwrap();
+        let _ = std::fs::remove_file(file_name);
+
+        assert_eq!(2, written.matches("==== ROUND 1 ====").count(), "Every call should append one picture of the board.");
+        assert!(written.ends_with(&format!("{}\n", LEGEND)), "Every picture should end with the legend.");
+    }
+
+    #[test]
+    fn test_render_threat_map() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 100 as u32, 3, tank_type::tank);
+        player.calibrate(0, 2, -2);
+        let game_board: board = board::initialize(3);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -2, 2, TeamColor::B, 1, None));
+        tracker.set_max_moves(TeamColor::B, 0);
+
+        let rendered = render_threat_map(&game_board, &player, &ThreatMap::build(&tracker, &game_board, 1));
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!("       .", lines[1], "B can't shoot its own hex.");
+        assert_eq!(" 1     1     1", lines[3], "The hexes around B are threatened by one enemy.");
+        assert_eq!("      @N", lines[9], "Our tank is drawn over the threat.");
+    }
+}
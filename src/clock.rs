@@ -0,0 +1,48 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/**
+ * Source of time for the code that waits on the team, like the role negotiation.
+ * The robot uses SystemClock, the simulated team channel gives a virtual clock so tests don't depend on the wall clock
+ * (see TeamBus::clock).
+ */
+pub trait Clock {
+
+    /**
+     * Milliseconds elapsed since the clock was created.
+     */
+    fn now_ms(&self) -> u64;
+
+    /**
+     * Wait for 'ms' milliseconds.
+     */
+    fn sleep_ms(&self, ms: u64);
+}
+
+/**
+ * Clock of the machine the robot runs on.
+ */
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+
+    /**
+     * Create a clock starting at 0 now.
+     */
+    pub fn new() -> Self {
+        return Self { start: Instant::now() };
+    }
+}
+
+impl Clock for SystemClock {
+
+    fn now_ms(&self) -> u64 {
+        return self.start.elapsed().as_millis() as u64;
+    }
+
+    fn sleep_ms(&self, ms: u64) {
+        thread::sleep(Duration::from_millis(ms));
+    }
+}
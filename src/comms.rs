@@ -1,151 +1,555 @@
-use std::{fs::File, os::unix::prelude::FromRawFd, io::{Read, Write}, sync::mpsc::{Receiver, Sender, self}};
-use crate::{parser, player::Player};
-
-// /// comm scan struct
-// /// Used to send and receive scan information between robots
-// /// to sychronize internal maps
-// #[derive(serde::Serialize, serde::Deserialize)]
-// pub struct CommScan
-// {
-//     scan: Vec<String>
-// }
-
-/// internal function, called by the listener thread
-/// Consumes scanned information from other robots
-fn put_scan(s:String, player: &mut Player)
-{
-    let scan: CommScan = serde_json::from_str(&s).unwrap();
-    // sca.scan is the vector of a scan from another player
-    eprintln!("{:?}", scan.scan);
-    // TODO: use received scan: put the information into player
-}
-
-pub fn parse_thread_comms(in_string: String, player: &mut Player)
-{
-    let vec_in = parser::get_args(in_string);
-    match vec_in[0].as_str()
-    {
-        "SCAN" => put_scan(vec_in[1].clone(), player),
-        _ => () // bad input
-    }
-}
-
-/// Starts a listener thread. Returns the thread so that it can
-/// be joined later before robot stops executing.
-/// How to use: Detailed instructions in the test called: start_listener_thread_test
-pub fn start_listener_thread(tx: Sender<String>, end_rx: Receiver<String>) -> std::thread::JoinHandle<()>
-{
-    // team listens to channel 3
-    let input_fd = 3;
-    let comm_thread = std::thread::spawn(move || {
-        let mut fd_in = unsafe { File::from_raw_fd(input_fd) };
-        loop
-        {
-            let mut input = String::new();
-            fd_in.read_to_string(&mut input).unwrap();
-            let in_vec: Vec<&str> = input.split('\n').collect();
-
-            for str in in_vec 
-            {
-                if str != ""
-                {
-                    tx.send(str.to_string()).unwrap();
-                }
-            }
-
-            match end_rx.try_recv()
-            {
-                Ok(_) | Err(mpsc::TryRecvError::Disconnected) => {
-                    break;
-                }
-                Err(mpsc::TryRecvError::Empty) => {}
-            }
-        }
-    });
-
-//     comm_thread
-// }
-
-// /// Internal function, used to write some string to some file descriptor
-// fn write_to_fd(output_string: String, output_fd: i32)
-// {
-//     let mut out_fd = unsafe { File::from_raw_fd(output_fd) };
-//     write!(&mut out_fd, "{}", output_string).unwrap();
-// }
-
-// /// Used to send scan information to other robots.
-// pub fn comm_send_scan(scan_vector: Vec<String>)
-// {
-//     // team outputs to channel 4
-//     let out_fd_num = 4;
-
-//     let out_struct = CommScan{ scan:scan_vector };
-//     let serialized = serde_json::to_string(&out_struct).unwrap();
-
-    let out = "SCAN ".to_string() + &serialized + "\n";
-    write_to_fd(out, out_fd_num);
-}
-
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-
-//     #[test]
-//     fn comm_send_scan_tests()
-//     {
-//         // TEST FAILS UNLESS YOU USE COMMANDLINE: cargo test 4>{anything you want}
-//         let scan_vector = vec!["a".to_string(), "b".to_string(), "c".to_string()];
-//         comm_send_scan(scan_vector);
-//     }
-
-    #[test]
-    fn listener_decisions_test()
-    {
-        let example_str = "SCAN {\"scan\":[\"a\",\"b\",\"c\"]}".to_string();
-        let mut player = <Player as crate::player::PlayerState>::initialize_player("V".to_string(), 20 as u32, 9, crate::player::tank_type::tank);
-        parse_thread_comms(example_str, &mut player);
-    }
-
-    #[test]
-    fn start_listener_thread_test()
-    {
-        // TEST FAILS UNLESS YOU USE COMMANDLINE: cargo test 3<{something that has expected input}
-
-        // start by making 2 sets of transmitter/receiver pairs. One communicates the information from other tanks
-        // other one is for turning off the thread (end_tx/end_rx).
-        // do this when initializing the tank in the very beginning one time.
-        let (tx, rx) = mpsc::channel();
-        let (end_tx, end_rx) = mpsc::channel();
-        // create the thread with the transmitter/receiver pairs passed in
-        let x = start_listener_thread(tx, end_rx);
-        // create a try_iter(has to be try_iter). It receives info from thread
-        let mut iter = rx.try_iter();
-        // done with tank initialization stuff
-
-        // this line is to simulate server lag. Ignore for implementations
-        std::thread::sleep(std::time::Duration::from_millis(100));
-
-        // creating a dummy player (needs to be passed into function: parse_thread_comms)
-        let mut player = <Player as crate::player::PlayerState>::initialize_player("V".to_string(), 20 as u32, 9, crate::player::tank_type::tank);        
-
-        // this loop goes through all the messages in transmission line.
-        // this loop (or a similar loop) should be called beginning of every round.
-        // do this beginning of each turn
-        loop {
-            match iter.next() {
-                Some(s) => {
-                    parse_thread_comms(s, &mut player);
-                },
-                None => {
-                    break;
-                }
-            }   
-        }
-        // stop here and do the rest of the turn then go back to start of loop on line 131 beginning of next turn
-
-        // do this at the very end when tank is dead or game is over
-        let _ = end_tx.send("Done!".to_string());
-        x.join().unwrap();
-    }
-
+use std::{collections::HashMap, fs::File, mem::ManuallyDrop, os::unix::prelude::FromRawFd, io::{BufRead, BufReader, Read, Write}, sync::mpsc::{Receiver, Sender, self}, thread::JoinHandle, time::{Duration, Instant}};
+use crate::{parser::RunOptions, player::{Player, PlayerState}, scan_parser::{ScanResponse, GetScanReturn}};
+use crate::CoordSystem::{board, board_operations, TEAMMATE_SCAN_CONFIDENCE};
+use crate::team_protocol::{decode_message, encode_message, CommsError, TeamEnvelope, TeamMessage};
+
+/// Maximum number of routine messages (pose, scans, health...) sent to the team in a round, the rest are dropped so
+/// teammates aren't flooded.
+pub const MAX_MESSAGES_PER_ROUND: u32 = 8;
+
+/// Maximum number of messages about enemies (sightings, target claims and shots) sent to the team in a round. They have
+/// their own budget, so the routine messages of the round never crowd them out.
+pub const MAX_ENEMY_MESSAGES_PER_ROUND: u32 = 8;
+
+/// A message identical to the last one of the same kind is only sent again after this many rounds,
+/// so teammates know it is still true without receiving it every round.
+pub const REPEAT_AFTER_ROUNDS: u32 = 3;
+
+/// How long TeamListener::shutdown waits for the listener thread to stop before leaving it behind.
+pub const LISTENER_SHUTDOWN_TIMEOUT_MS: u64 = 100;
+
+/// internal function, called by the listener thread
+/// Consumes scanned information from other robots and merges it into the board
+fn put_scan(sender: char, round: u32, step: u32, position: (i32, i32, i32), facing: &str, scan_vector: Vec<String>, board: &mut board)
+{
+    let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+    scan.scan_entry(scan_vector);
+
+    board.merge_scan(&mut scan, position, facing, round, step, Some(sender), TEAMMATE_SCAN_CONFIDENCE);
+    board.set_teammate(sender, position, round);
+}
+
+/// Decodes a message from another robot and puts what it tells about the board into the board.
+/// Returns the decoded message, so that the caller can use the messages that are not about the board (claims, intentions, health).
+/// Messages that we sent ourselves are decoded but not applied.
+pub fn parse_thread_comms(in_string: String, player: &mut Player, board: &mut board) -> Result<TeamEnvelope, CommsError>
+{
+    let envelope = decode_message(&in_string)?;
+
+    if player.get_colour().starts_with(envelope.sender) {
+        return Ok(envelope);
+    }
+
+    match &envelope.message
+    {
+        TeamMessage::Scan { step, position, facing, scan } => put_scan(envelope.sender, envelope.round, *step, *position, facing, scan.clone(), board),
+        TeamMessage::Pose { position, .. } => board.set_teammate(envelope.sender, *position, envelope.round),
+        TeamMessage::EnemySighting { enemy, position, seen_round } => board.merge_tile(*position, *enemy, *seen_round, 0, Some(envelope.sender), TEAMMATE_SCAN_CONFIDENCE),
+        _ => ()
+    }
+    return Ok(envelope);
+}
+
+/// Starts a listener thread. Returns the thread so that it can
+/// be joined later before robot stops executing.
+/// The input is read line by line, each line is one message and is sent to 'tx' as soon as it is read.
+/// A line that is not valid UTF-8 is logged and skipped, the next lines are still read.
+/// The thread stops when the input is closed, when 'tx' is dropped or when something is sent on 'end_rx'.
+/// The stop request is checked after each line, because reading a line blocks until the line arrives.
+/// How to use: Detailed instructions in the test called: start_listener_thread_test
+pub fn start_listener_thread<R: Read + Send + 'static>(input: R, tx: Sender<String>, end_rx: Receiver<()>) -> JoinHandle<()>
+{
+    let comm_thread = std::thread::spawn(move || {
+        let mut reader = BufReader::new(input);
+        loop
+        {
+            let mut bytes: Vec<u8> = Vec::new();
+            match reader.read_until(b'\n', &mut bytes)
+            {
+                Ok(0) => break, // input closed
+                Ok(_) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    eprintln!("Team channel can't be read anymore: {}", error);
+                    break;
+                }
+            }
+
+            let line = match String::from_utf8(bytes)
+            {
+                Ok(line) => line,
+                Err(error) => {
+                    eprintln!("Ignoring team message that is not valid UTF-8: {}", error);
+                    continue;
+                }
+            };
+            let message = line.trim();
+            if !message.is_empty() && tx.send(message.to_string()).is_err()
+            {
+                break;
+            }
+
+            match end_rx.try_recv()
+            {
+                Ok(_) | Err(mpsc::TryRecvError::Disconnected) => {
+                    break;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+    });
+
+    comm_thread
+}
+
+/// Opens a file descriptor inherited from the process that started the robot.
+/// Returns None if the descriptor is not open. The descriptor is checked with fstat, which works on every unix.
+/// Only descriptors the robot was told to use on the command line should be opened (see RunOptions), an inherited
+/// descriptor that happens to be open may belong to something else, like the jobserver of cargo in tests.
+pub fn open_fd(fd: i32) -> Option<File>
+{
+    if fd < 0
+    {
+        return None;
+    }
+    // The descriptor was handed over to us by the process that started the robot, nobody else in the robot uses it.
+    // It is not closed when the check fails, it was not open in the first place.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    if file.metadata().is_err()
+    {
+        return None;
+    }
+    Some(ManuallyDrop::into_inner(file))
+}
+
+/// Opens the team channel given on the command line: the teammates are read from 'team_in_fd' and written to on
+/// 'team_out_fd' (see RunOptions). A side of the channel whose descriptor is not given or not open stays closed.
+pub fn open_team_channel(sender: char, options: &RunOptions) -> (Option<TeamListener>, TeamBroadcaster)
+{
+    let listener = options.team_in_fd.and_then(TeamListener::from_fd);
+    let broadcaster = match options.team_out_fd
+    {
+        Some(fd) => TeamBroadcaster::from_fd(sender, fd),
+        None => TeamBroadcaster::new(sender, None),
+    };
+    (listener, broadcaster)
+}
+
+/// Listener for the messages of the other robots of the team.
+/// The messages are kept in an inbox until they are drained, which should be done at the start of every round.
+pub struct TeamListener
+{
+    inbox: Receiver<String>,
+    end_tx: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TeamListener
+{
+    /// Starts listening to the given input.
+    pub fn start<R: Read + Send + 'static>(input: R) -> Self
+    {
+        let (tx, rx) = mpsc::channel();
+        let (end_tx, end_rx) = mpsc::channel();
+        let thread = start_listener_thread(input, tx, end_rx);
+
+        Self { inbox: rx, end_tx: end_tx, thread: Some(thread) }
+    }
+
+    /// Starts listening to a file descriptor, returns None if the descriptor is not open.
+    pub fn from_fd(fd: i32) -> Option<Self>
+    {
+        open_fd(fd).map(TeamListener::start)
+    }
+
+    /// Returns all the messages received since the last call, without waiting for new ones.
+    pub fn drain(&self) -> Vec<String>
+    {
+        self.inbox.try_iter().collect()
+    }
+
+    /// Waits up to 'timeout' for the next message. Returns None if nothing arrived in time or the input is closed.
+    pub fn next_message(&self, timeout: Duration) -> Option<String>
+    {
+        self.inbox.recv_timeout(timeout).ok()
+    }
+
+    /// Asks the listener thread to stop and joins it. The thread can only see the request once its current read
+    /// returns, so it is given LISTENER_SHUTDOWN_TIMEOUT_MS to stop: the inbox is disconnected once it did. A thread
+    /// still blocked reading after that is left to finish on its own when the input is closed, so the robot never
+    /// hangs here. Messages that arrive during the shutdown are dropped.
+    pub fn shutdown(mut self)
+    {
+        let _ = self.end_tx.send(());
+        let deadline = Instant::now() + Duration::from_millis(LISTENER_SHUTDOWN_TIMEOUT_MS);
+        let stopped = loop
+        {
+            match self.inbox.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(_) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break true,
+                Err(mpsc::RecvTimeoutError::Timeout) => break false,
+            }
+        };
+
+        if let Some(thread) = self.thread.take()
+        {
+            if stopped
+            {
+                let _ = thread.join();
+            }
+            else
+            {
+                eprintln!("Team listener still waiting for its input, leaving it behind.");
+            }
+        }
+    }
+
+    /// True once the listener thread stopped, checked by the tests.
+    #[cfg(test)]
+    fn is_stopped(&self) -> bool
+    {
+        self.thread.as_ref().map_or(true, |thread| thread.is_finished())
+    }
+}
+
+/// Publishes what the robot learned to the other robots of the team.
+/// Messages are rate limited per round and a message identical to the last one of the same kind is not repeated,
+/// see MAX_MESSAGES_PER_ROUND, MAX_ENEMY_MESSAGES_PER_ROUND and REPEAT_AFTER_ROUNDS.
+/// When there is no output, or writing to it fails (the channel is closed when running solo or in tests), the messages are dropped.
+pub struct TeamBroadcaster
+{
+    output: Option<Box<dyn Write + Send>>,
+    sender: char,
+    seq: u64,
+    round: u32,
+    sent_this_round: u32,
+    enemy_sent_this_round: u32,
+    last_sent: HashMap<String, (String, u32)>, // message kind -> (content, round it was sent on)
+}
+
+impl TeamBroadcaster
+{
+    /// Creates a broadcaster writing to the given output, or dropping everything if there is none.
+    pub fn new(sender: char, output: Option<Box<dyn Write + Send>>) -> Self
+    {
+        Self { output: output, sender: sender, seq: 0, round: 0, sent_this_round: 0, enemy_sent_this_round: 0, last_sent: HashMap::new() }
+    }
+
+    /// Creates a broadcaster writing to a file descriptor, messages are dropped if the descriptor is not open.
+    pub fn from_fd(sender: char, fd: i32) -> Self
+    {
+        let output = open_fd(fd).map(|file| Box::new(file) as Box<dyn Write + Send>);
+        Self::new(sender, output)
+    }
+
+    /// True while messages can still be written to the team.
+    pub fn is_connected(&self) -> bool
+    {
+        self.output.is_some()
+    }
+
+    /// Resets the rate limit for a new round.
+    pub fn start_round(&mut self, round: u32)
+    {
+        self.round = round;
+        self.sent_this_round = 0;
+        self.enemy_sent_this_round = 0;
+    }
+
+    /// Sends a message to the team. Returns true if the message was written.
+    pub fn publish(&mut self, message: TeamMessage) -> bool
+    {
+        let about_enemy = is_about_enemy(&message);
+        let (sent, budget) = if about_enemy
+        {
+            (self.enemy_sent_this_round, MAX_ENEMY_MESSAGES_PER_ROUND)
+        }
+        else
+        {
+            (self.sent_this_round, MAX_MESSAGES_PER_ROUND)
+        };
+        if self.output.is_none() || sent >= budget
+        {
+            return false;
+        }
+
+        let kind = message_kind(&message);
+        let content = serde_json::to_string(&message).unwrap_or_default();
+        if let Some((last_content, last_round)) = kind.as_ref().and_then(|kind| self.last_sent.get(kind))
+        {
+            if *last_content == content && self.round < last_round + REPEAT_AFTER_ROUNDS
+            {
+                return false;
+            }
+        }
+
+        let envelope = TeamEnvelope::new(self.sender, self.round, self.seq, message);
+        let line = encode_message(&envelope) + "\n";
+
+        let written = match self.output.as_mut()
+        {
+            Some(output) => output.write_all(line.as_bytes()).and_then(|_| output.flush()),
+            None => return false,
+        };
+
+        if let Err(error) = written
+        {
+            eprintln!("Team channel closed, not sending messages anymore: {}", error);
+            self.output = None;
+            return false;
+        }
+
+        self.seq += 1;
+        if about_enemy
+        {
+            self.enemy_sent_this_round += 1;
+        }
+        else
+        {
+            self.sent_this_round += 1;
+        }
+        if let Some(kind) = kind
+        {
+            self.last_sent.insert(kind, (content, self.round));
+        }
+        true
+    }
+
+    /// Sends our position and facing direction.
+    pub fn publish_pose(&mut self, player: &Player) -> bool
+    {
+        self.publish(TeamMessage::Pose { position: player.get_position(), facing: player.get_facing_direction() })
+    }
+
+    /// Sends a scan accepted by the server, with the enemies it found as sightings.
+    ///  - scan_vector: The scan entries as returned by the server.
+    ///  - scan: The parsed scan, used to find the enemies.
+    pub fn publish_scan(&mut self, player: &Player, scan_vector: Vec<String>, scan: &mut ScanResponse)
+    {
+        let position = player.get_position();
+        let facing = player.get_facing_direction();
+
+        self.publish(TeamMessage::Scan { step: player.get_step_count(), position: position, facing: facing.clone(), scan: scan_vector });
+
+        for enemy in scan.get_enemy_positions(position, facing.as_str())
+        {
+            self.publish(TeamMessage::EnemySighting { enemy: enemy.get_occupied_by(), position: enemy.get_coords(), seen_round: player.get_rounds() });
+        }
+    }
+}
+
+/// True for the messages about enemies, they are counted against MAX_ENEMY_MESSAGES_PER_ROUND.
+fn is_about_enemy(message: &TeamMessage) -> bool
+{
+    matches!(message, TeamMessage::EnemySighting { .. } | TeamMessage::TargetClaim { .. } | TeamMessage::Shot { .. })
+}
+
+/// Kind of a message used to find repeated messages. Sightings and claims are kept per enemy.
+/// Shots are events, two identical shots are two shots, so they have no kind and are never repeats. Role proposals are
+/// sent again on purpose for the teammates that started later, so they are never repeats either.
+fn message_kind(message: &TeamMessage) -> Option<String>
+{
+    match message
+    {
+        TeamMessage::Scan { .. } => Some("scan".to_string()),
+        TeamMessage::Pose { .. } => Some("pose".to_string()),
+        TeamMessage::EnemySighting { enemy, .. } => Some(format!("sighting {}", enemy)),
+        TeamMessage::TargetClaim { enemy, .. } => Some(format!("claim {}", enemy)),
+        TeamMessage::Shot { .. } => None,
+        TeamMessage::Intention { .. } => Some("intention".to_string()),
+        TeamMessage::Health { .. } => Some("health".to_string()),
+        TeamMessage::RoleProposal { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listener_decisions_test()
+    {
+        let example_str = "{\"version\":1,\"sender\":\"G\",\"round\":3,\"seq\":0,\"message\":{\"type\":\"scan\",\"step\":1,\"position\":[0,-1,1],\"facing\":\"N\",\"scan\":[\"a\",\"W\",\"c\"]}}".to_string();
+        let mut player = <Player as crate::player::PlayerState>::initialize_player("V".to_string(), 20 as u32, 9, crate::player::tank_type::tank);
+        let mut board: board = board::initialize(9);
+        parse_thread_comms(example_str, &mut player, &mut board).unwrap();
+
+        let tile = board.get_tile(0, -2, 2).unwrap();
+        assert_eq!('W', tile.get_occupied_by(), "The wall the teammate scanned in front of it should be on the board.");
+        assert_eq!(Some('G'), tile.get_reported_by(), "The wall should be reported by G.");
+        assert_eq!((0, -1, 1), board.get_teammates()[0].get_coords(), "G should be on the position it scanned from.");
+
+        assert!(parse_thread_comms("SCAN {}".to_string(), &mut player, &mut board).is_err(), "A bad message should be reported as an error.");
+    }
+
+    #[test]
+    fn start_listener_thread_test()
+    {
+        // The input can be any reader, the robot uses the file descriptor given on the command line (see TeamListener::from_fd).
+        let input = std::io::Cursor::new("{\"version\":1,\"sender\":\"G\",\"round\":2,\"seq\":0,\"message\":{\"type\":\"pose\",\"position\":[1,-1,0],\"facing\":\"S\"}}\n\nnot a message\n");
+
+        // start by making 2 sets of transmitter/receiver pairs. One communicates the information from other tanks
+        // other one is for turning off the thread (end_tx/end_rx).
+        // do this when initializing the tank in the very beginning one time.
+        let (tx, rx) = mpsc::channel();
+        let (end_tx, end_rx) = mpsc::channel();
+        // create the thread with the transmitter/receiver pairs passed in
+        let x = start_listener_thread(input, tx, end_rx);
+        // the thread stops by itself once the input is closed
+        x.join().unwrap();
+        let _ = end_tx.send(());
+
+        // creating a dummy player (needs to be passed into function: parse_thread_comms)
+        let mut player = <Player as crate::player::PlayerState>::initialize_player("V".to_string(), 20 as u32, 9, crate::player::tank_type::tank);
+        let mut board: board = board::initialize(9);
+
+        // this loop goes through all the messages in transmission line.
+        // this loop (or a similar loop) should be called beginning of every round.
+        let messages: Vec<String> = rx.try_iter().collect();
+        assert_eq!(2, messages.len(), "Empty lines should be skipped but got {} messages.", messages.len());
+
+        let results: Vec<bool> = messages.into_iter().map(|s| parse_thread_comms(s, &mut player, &mut board).is_ok()).collect();
+        assert_eq!(vec![true, false], results, "The pose should be accepted and the bad line rejected.");
+        assert_eq!((1, -1, 0), board.get_teammates()[0].get_coords(), "The teammate pose should be on the board.");
+    }
+
+    #[test]
+    fn team_listener_drain_test()
+    {
+        let listener = TeamListener::start(std::io::Cursor::new("first\nsecond\n"));
+        let timeout = Duration::from_secs(5);
+
+        assert_eq!(Some("first".to_string()), listener.next_message(timeout), "The first line should arrive first.");
+        assert_eq!(Some("second".to_string()), listener.next_message(timeout), "The second line should arrive next.");
+        assert_eq!(None, listener.next_message(timeout), "Nothing should arrive once the input is closed.");
+        assert!(listener.drain().is_empty(), "The inbox should be empty after being drained.");
+        listener.shutdown();
+    }
+
+    #[test]
+    fn team_listener_skips_invalid_utf8_test()
+    {
+        let input: Vec<u8> = [b"first\n".as_slice(), &[0xff, 0xfe, b'\n'], b"second\n"].concat();
+        let listener = TeamListener::start(std::io::Cursor::new(input));
+        let timeout = Duration::from_secs(5);
+
+        assert_eq!(Some("first".to_string()), listener.next_message(timeout), "The line before the bad one should arrive.");
+        assert_eq!(Some("second".to_string()), listener.next_message(timeout), "The listener should keep reading after a line that is not UTF-8.");
+    }
+
+    /// Input that never ends, like the team channel of a robot whose teammates are still playing.
+    struct OpenInput(Receiver<()>);
+
+    impl Read for OpenInput
+    {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize>
+        {
+            let _ = self.0.recv();
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn team_listener_shutdown_joins_test()
+    {
+        let listener = TeamListener::start(std::io::Cursor::new("last\n"));
+        assert_eq!(Some("last".to_string()), listener.next_message(Duration::from_secs(5)), "The line should arrive.");
+        listener.shutdown();
+
+        let (close_tx, close_rx) = mpsc::channel();
+        let blocked = TeamListener::start(OpenInput(close_rx));
+        assert!(!blocked.is_stopped(), "The listener should wait for its input.");
+        blocked.shutdown();
+        drop(close_tx);
+    }
+
+    #[test]
+    fn open_fd_test()
+    {
+        assert!(open_fd(-1).is_none(), "A negative descriptor is never open.");
+        assert!(open_fd(1 << 20).is_none(), "A descriptor that is not open should not be used.");
+    }
+
+    /// Output shared with the test, so what the broadcaster wrote can be checked.
+    #[derive(Clone, Default)]
+    struct SharedOutput(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput
+    {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    /// Output that behaves like a closed descriptor.
+    struct ClosedOutput;
+
+    impl Write for ClosedOutput
+    {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> { Err(std::io::Error::from(std::io::ErrorKind::BrokenPipe)) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn broadcaster_deduplicates_test()
+    {
+        let output = SharedOutput::default();
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(output.clone())));
+        let mut player = <Player as crate::player::PlayerState>::initialize_player("G".to_string(), 20 as u32, 9, crate::player::tank_type::tank);
+        player.calibrate(1, -1, 0);
+
+        broadcaster.start_round(1);
+        assert!(broadcaster.publish_pose(&player), "The first pose should be sent.");
+        assert!(!broadcaster.publish_pose(&player), "The same pose should not be sent twice.");
+
+        broadcaster.start_round(1 + REPEAT_AFTER_ROUNDS);
+        assert!(broadcaster.publish_pose(&player), "The same pose should be repeated after a few rounds.");
+
+        let written = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let sequence: Vec<u64> = written.lines().map(|line| decode_message(line).unwrap().seq).collect();
+        assert_eq!(vec![0, 1], sequence, "Each line should be one message with increasing sequence numbers.");
+    }
+
+    #[test]
+    fn broadcaster_repeats_shots_test()
+    {
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(SharedOutput::default())));
+        broadcaster.start_round(1);
+
+        assert!(broadcaster.publish(TeamMessage::Shot { enemy: 'R', position: (1, -1, 0) }), "The first shot should be sent.");
+        assert!(broadcaster.publish(TeamMessage::Shot { enemy: 'R', position: (1, -1, 0) }), "A second shot at the same hex is another shot.");
+    }
+
+    #[test]
+    fn broadcaster_rate_limit_test()
+    {
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(SharedOutput::default())));
+        broadcaster.start_round(1);
+
+        let sent = (0..20).filter(|health| broadcaster.publish(TeamMessage::Health { health: *health })).count();
+        assert_eq!(MAX_MESSAGES_PER_ROUND as usize, sent, "Only {} messages should be sent in a round.", MAX_MESSAGES_PER_ROUND);
+    }
+
+    #[test]
+    fn broadcaster_sightings_have_their_own_budget_test()
+    {
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(SharedOutput::default())));
+        broadcaster.start_round(1);
+
+        let poses = (0..MAX_MESSAGES_PER_ROUND as i32).filter(|q| broadcaster.publish(TeamMessage::Pose { position: (*q, -*q, 0), facing: "N".to_string() })).count();
+        assert_eq!(MAX_MESSAGES_PER_ROUND as usize, poses, "Every pose should fit in the routine budget.");
+        assert!(!broadcaster.publish(TeamMessage::Health { health: 2 }), "The routine budget of the round is used up.");
+        assert!(broadcaster.publish(TeamMessage::EnemySighting { enemy: 'Y', position: (2, -1, -1), seen_round: 1 }), "A sighting should still go out after {} poses.", MAX_MESSAGES_PER_ROUND);
+        assert!(broadcaster.publish(TeamMessage::Shot { enemy: 'Y', position: (2, -1, -1) }), "A shot should still go out after {} poses.", MAX_MESSAGES_PER_ROUND);
+    }
+
+    #[test]
+    fn broadcaster_closed_output_test()
+    {
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(ClosedOutput)));
+        broadcaster.start_round(1);
+
+        assert!(!broadcaster.publish(TeamMessage::Health { health: 2 }), "Nothing can be sent on a closed channel.");
+        assert!(!broadcaster.is_connected(), "The broadcaster should stop writing to a closed channel.");
+    }
 }
\ No newline at end of file
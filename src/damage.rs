@@ -0,0 +1,202 @@
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::CoordSystem::{board, board_operations, direction_vector, hex_distance, neighbours};
+use crate::action_manager::playerOutput;
+use crate::game::TeamColor;
+use crate::move_manager::{NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST, SOUTH_WEST};
+use crate::player::PlayerState;
+use crate::strategy::{Command, GameView};
+use crate::threat_map::ThreatMap;
+
+/// Share of the probability given to the hex where the shot path starts, the rest of the cone shares what is left.
+const SHOOTER_ORIGIN_WEIGHT: f32 = 0.5;
+
+/**
+ * A hit we took.
+ *  - hit: Where we were when the shot hit us.
+ *  - directions: The directions the shot travelled, as in a SHOOT command. ['N', 'NE'] is a shot that went north
+ *    then north-east to reach us.
+ *  - round: The round of the hit.
+ *  - cone: The likely positions of the shooter with their probability, see shooter_cone.
+ *  - shooter: The enemy the hit was put on by EnemyTracker::record_damage, if only one could have made it.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageReport {
+    hit: (i32, i32, i32),
+    directions: Vec<String>,
+    round: u32,
+    cone: Vec<((i32, i32, i32), f32)>,
+    #[serde(default)]
+    shooter: Option<TeamColor>,
+}
+
+impl DamageReport {
+
+    /**
+     * Create the report of a hit taken on the given position, the cone is computed from the directions.
+     */
+    pub fn new(hit: (i32, i32, i32), directions: Vec<String>, round: u32, game_board: &board) -> Self {
+        let cone = shooter_cone(hit, &directions, game_board);
+        return Self { hit, directions, round, cone, shooter: None };
+    }
+
+    pub fn get_hit(&self) -> (i32, i32, i32) {
+        return self.hit;
+    }
+
+    pub fn get_directions(&self) -> &[String] {
+        return &self.directions;
+    }
+
+    pub fn get_round(&self) -> u32 {
+        return self.round;
+    }
+
+    pub fn get_cone(&self) -> &[((i32, i32, i32), f32)] {
+        return &self.cone;
+    }
+
+    pub fn get_shooter(&self) -> Option<TeamColor> {
+        return self.shooter;
+    }
+
+    pub fn set_shooter(&mut self, shooter: TeamColor) {
+        self.shooter = Some(shooter);
+    }
+}
+
+/**
+ * Return the directions of a DAMAGE message, for example ['DAMAGE', 'N', 'NE'] gives ['N', 'NE']. Directions joined as
+ * in a SHOOT command ("N-NE") are split, anything that is not a direction is dropped.
+ */
+pub fn parse_damage_directions(args: &[String]) -> Vec<String> {
+    return args.iter()
+        .skip(1)
+        .flat_map(|arg| arg.split('-'))
+        .filter(|direction| [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST].contains(direction))
+        .map(|direction| direction.to_string())
+        .collect();
+}
+
+/**
+ * Likely positions of the shooter, most likely first. Following the directions of the shot back from the hit gives the
+ * hex the shot came from, it gets SHOOTER_ORIGIN_WEIGHT. The shooter may have driven one hex since, and our own position
+ * may be a hex off, so the hexes around it that are not closer to us share the rest: a cone that opens away from us.
+ * Walls and hexes outside of the board can't hold the shooter. Empty if the message had no direction.
+ */
+pub fn shooter_cone(hit: (i32, i32, i32), directions: &[String], game_board: &board) -> Vec<((i32, i32, i32), f32)> {
+    if directions.is_empty() {
+        return Vec::new();
+    }
+
+    let origin = directions.iter().fold(hit, |position, direction| {
+        let (dq, dr, ds) = direction_vector(direction);
+        (position.0 - dq, position.1 - dr, position.2 - ds)
+    });
+    let can_hold = |hex: &(i32, i32, i32)| -> bool {
+        *hex != hit && game_board.get_tile(hex.0, hex.1, hex.2).map_or(false, |tile| tile.get_occupied_by() != 'W')
+    };
+
+    let spread: Vec<(i32, i32, i32)> = neighbours(origin).into_iter()
+        .filter(|hex| hex_distance(*hex, hit) >= hex_distance(origin, hit))
+        .filter(can_hold)
+        .collect();
+
+    let mut cone: Vec<((i32, i32, i32), f32)> = Vec::new();
+    let origin_weight = if spread.is_empty() { 1.0 } else { SHOOTER_ORIGIN_WEIGHT };
+    if can_hold(&origin) {
+        cone.push((origin, origin_weight));
+    }
+    let spread_weight = (1.0 - cone.iter().map(|(_, weight)| weight).sum::<f32>()) / spread.len().max(1) as f32;
+    cone.extend(spread.into_iter().map(|hex| (hex, spread_weight)));
+    return cone;
+}
+
+/**
+ * Evasive manoeuvre after a hit: drive to the free neighbour with the lowest threat (see ThreatMap), the furthest from
+ * the shooter on a tie, turning to it first if needed. None once the tank stands out of reach of the shooter's cone, or
+ * it can't drive anymore this round, so the strategy plays the rest of the round.
+ */
+pub fn evasive_command(view: &GameView, report: &DamageReport) -> Option<Command> {
+    let player = view.player;
+    if !player.is_calibrated() || !player.get_drive_actions_check() {
+        return None;
+    }
+
+    let position = player.get_position();
+    let distance_to_shooter = |hex: (i32, i32, i32)| -> u32 {
+        return report.get_cone().iter().map(|(shooter, _)| hex_distance(*shooter, hex)).min().unwrap_or(u32::MAX);
+    };
+    if distance_to_shooter(position) > crate::game::ENEMY_SHOT_RANGE {
+        return None;
+    }
+
+    let threat = ThreatMap::build(view.tracker, view.board, player.get_rounds());
+    let free = |hex: &(i32, i32, i32)| -> bool {
+        return view.board.get_tile(hex.0, hex.1, hex.2).map_or(false, |tile| tile.get_occupied_by() != 'W')
+            && view.board.tank_probability(hex.0, hex.1, hex.2, player.get_rounds()) < 0.5;
+    };
+    let (_, direction) = [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST].iter()
+        .map(|direction| {
+            let (dq, dr, ds) = direction_vector(direction);
+            ((position.0 + dq, position.1 + dr, position.2 + ds), direction.to_string())
+        })
+        .filter(|(next, _)| free(next))
+        .min_by_key(|(next, _)| (threat.get_threat(*next), Reverse(distance_to_shooter(*next))))?;
+
+    if player.get_facing_direction() == direction {
+        return Some(Command::Drive);
+    }
+    // Only one turn between two other actions.
+    return if matches!(view.last_action, playerOutput::TURN) { None } else { Some(Command::Turn(direction)) };
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::damage::*;
+    use crate::CoordSystem::OWN_SCAN_CONFIDENCE;
+    use crate::game::EnemyTracker;
+    use crate::parser;
+    use crate::player::{Player, tank_type};
+    use crate::scan_parser::{GetScanReturn, ScanResponse};
+
+    #[test]
+    fn test_parse_damage_and_cone() {
+        let args = parser::get_args("DAMAGE N NE".to_string());
+        assert_eq!(vec!["N".to_string(), "NE".to_string()], parse_damage_directions(&args), "The directions follow DAMAGE.");
+        assert_eq!(vec!["S".to_string(), "SW".to_string()], parse_damage_directions(&parser::get_args("DAMAGE S-SW X".to_string())), "Joined directions are split, others dropped.");
+
+        let mut game_board: board = board::initialize(5);
+        game_board.merge_tile((-1, -1, 2), 'W', 1, 1, None, OWN_SCAN_CONFIDENCE);
+        let report = DamageReport::new((0, 0, 0), parse_damage_directions(&args), 3, &game_board);
+        let cone = report.get_cone();
+        assert_eq!(((-1, 2, -1), SHOOTER_ORIGIN_WEIGHT), cone[0], "The shot went N then NE, it came from S-SW of us.");
+        assert!(cone.iter().all(|(hex, _)| hex_distance(*hex, (0, 0, 0)) >= 2), "The cone opens away from us.");
+        assert!((cone.iter().map(|(_, weight)| weight).sum::<f32>() - 1.0).abs() < 0.001, "The cone is a probability.");
+        assert!(shooter_cone((0, 0, 0), &[], &game_board).is_empty(), "No direction, no cone.");
+    }
+
+    #[test]
+    fn test_evade_after_a_hit() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut tracker = EnemyTracker::new();
+        tracker.record_damage(DamageReport::new((0, 0, 0), vec!["S".to_string()], 3, &game_board), &game_board);
+        let report = tracker.last_damage().unwrap().clone();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &tracker, last_action: playerOutput::NONE, action_counter: 0, target: None };
+        let command = evasive_command(&view, &report);
+        assert!(matches!(command, Some(Command::Turn(_))), "The tank facing the shooter turns away before driving.");
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &tracker, last_action: playerOutput::TURN, action_counter: 1, target: None };
+        assert_eq!(None, evasive_command(&view, &report), "Never two turns in a row.");
+
+        player.calibrate(0, 4, -4);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &tracker, last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(None, evasive_command(&view, &report), "Out of reach of the shooter there is nothing to evade.");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::CoordSystem::{board, board_operations, direction_vector, hex_distance};
+use crate::parser;
+
+/// Teammate positions older than this many rounds are too old to tell where the teammate is, they are not checked.
+pub const TEAMMATE_POSITION_MAX_AGE: u32 = 2;
+
+/**
+ * Veto layer for shots. Before a SHOOT command is sent, the hexes the shot goes through are checked against the
+ * last known position of each teammate:
+ *   - A teammate seen this round blocks its own hex.
+ *   - A teammate seen on an earlier round may have driven since, so it also blocks the hexes around it.
+ *   - A teammate not seen for more than TEAMMATE_POSITION_MAX_AGE rounds is not checked.
+ *
+ * A blocked shot is replaced by another order of the same directions, which ends on the same hex, when one of them is
+ * safe. Otherwise the shot is rejected.
+ */
+pub struct FireGuard {
+    teammates: Vec<((i32, i32, i32), u32)>, // (position, rounds since it was seen)
+}
+
+pub trait friendly_fire_guard {
+    fn initialize() -> Self;
+    fn update_teammates(&mut self, game_board: &board, round: u32);
+    fn is_safe(&self, origin: (i32, i32, i32), directions: &[String]) -> bool;
+    fn safe_shot(&self, origin: (i32, i32, i32), command: &str) -> Option<String>;
+}
+
+impl friendly_fire_guard for FireGuard {
+
+    /**
+     * Initialize the guard with no known teammate, every shot is safe until update_teammates is called.
+     */
+    fn initialize() -> Self {
+        Self {
+            teammates: Vec::new(),
+        }
+    }
+
+    /**
+     * Take the teammate positions known by the board at the start of a round.
+     */
+    fn update_teammates(&mut self, game_board: &board, round: u32) {
+        self.teammates = game_board.get_teammates().iter()
+            .map(|teammate| (teammate.get_coords(), round.saturating_sub(teammate.get_seen_round())))
+            .filter(|(_, age)| *age <= TEAMMATE_POSITION_MAX_AGE)
+            .collect();
+    }
+
+    /**
+     * Check that a shot fired from 'origin' along 'directions' doesn't go through a teammate.
+     */
+    fn is_safe(&self, origin: (i32, i32, i32), directions: &[String]) -> bool {
+        return shot_path(origin, directions).iter().all(|hex| {
+            self.teammates.iter().all(|(teammate, age)| hex_distance(*hex, *teammate) > (*age).min(1))
+        });
+    }
+
+    /**
+     * Return a safe version of a shoot command.
+     *  - origin: The position the shot is fired from.
+     *  - command: The shoot command, for example "SHOOT N-NE".
+     *
+     *  - Returns: The command itself if it is safe, otherwise the first safe order of the same directions, for example
+     *    "SHOOT NE-N", or None if every order goes through a teammate.
+     */
+    fn safe_shot(&self, origin: (i32, i32, i32), command: &str) -> Option<String> {
+        let directions: Vec<String> = parser::get_args(command.to_string()).into_iter().skip(1).filter(|direction| !direction.is_empty()).collect();
+
+        return permutations(&directions).into_iter()
+            .find(|path| self.is_safe(origin, path))
+            .map(|path| format!("SHOOT {}", path.join("-")));
+    }
+}
+
+/**
+ * Return the hexes a shot goes through, in order, without the hex it is fired from.
+ */
+pub fn shot_path(origin: (i32, i32, i32), directions: &[String]) -> Vec<(i32, i32, i32)> {
+    let mut position = origin;
+    let mut path: Vec<(i32, i32, i32)> = Vec::new();
+
+    for direction in directions {
+        let (dq, dr, ds) = direction_vector(direction.as_str());
+        position = (position.0 + dq, position.1 + dr, position.2 + ds);
+        path.push(position);
+    }
+    return path;
+}
+
+/**
+ * Return the distinct orders of the directions, the given order first.
+ */
+fn permutations(directions: &[String]) -> Vec<Vec<String>> {
+    if directions.len() <= 1 {
+        return vec![directions.to_vec()];
+    }
+
+    let mut orders: Vec<Vec<String>> = Vec::new();
+    for (index, first) in directions.iter().enumerate() {
+        let mut rest = directions.to_vec();
+        rest.remove(index);
+
+        for mut order in permutations(&rest) {
+            order.insert(0, first.clone());
+            if !orders.contains(&order) {
+                orders.push(order);
+            }
+        }
+    }
+    return orders;
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::fire_guard::*;
+
+    #[test]
+    fn test_reroute_shot_around_teammate() {
+        let mut game_board: board = board::initialize(5);
+        game_board.set_teammate('B', (0, -1, 1), 4);
+
+        let mut guard: FireGuard = FireGuard::initialize();
+        guard.update_teammates(&game_board, 4);
+
+        assert_eq!(Some("SHOOT NE-N".to_string()), guard.safe_shot((0, 0, 0), "SHOOT N-NE"), "The teammate is on N, the shot should go NE first.");
+        assert_eq!(Some("SHOOT SE".to_string()), guard.safe_shot((0, 0, 0), "SHOOT SE"), "A shot away from the teammate should not change.");
+        assert_eq!(None, guard.safe_shot((0, 0, 0), "SHOOT N-N"), "There is no safe order for a shot straight through the teammate.");
+    }
+
+    #[test]
+    fn test_teammate_recency() {
+        let mut game_board: board = board::initialize(5);
+        game_board.set_teammate('B', (0, -2, 2), 3);
+
+        let mut guard: FireGuard = FireGuard::initialize();
+        guard.update_teammates(&game_board, 4);
+        assert_eq!(None, guard.safe_shot((0, 0, 0), "SHOOT N"), "A teammate seen last round may have driven next to the target.");
+
+        guard.update_teammates(&game_board, 4 + TEAMMATE_POSITION_MAX_AGE);
+        assert_eq!(Some("SHOOT N-N".to_string()), guard.safe_shot((0, 0, 0), "SHOOT N-N"), "A teammate position that is too old should not block shots.");
+    }
+}
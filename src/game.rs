@@ -1,34 +1,468 @@
-enum TeamColor {
-    R,
-    O,
-    Y,
-    G,
-    B,
-    V,
-}
-struct EnemiesPos {
-    // Position := (x_pos,y_pos,z_pos)
-    lastseen_x_pos: i32,
-    lastseen_y_pos: i32,
-    lastseen_z_pos: i32,
-    team: TeamColor,
-    seen_at_round: u32,
-}
-
-impl EnemiesPos {
-    pub fn new(
-        relative_x: i32,
-        relative_y: i32,
-        relative_z: i32,
-        team: TeamColor,
-        seen_at_round: u32,
-    ) -> Self {
-        Self {
-            lastseen_x_pos: relative_x,
-            lastseen_y_pos: relative_y,
-            lastseen_z_pos: relative_z,
-            team: team,
-            seen_at_round: seen_at_round,
-        }
-    }
-}
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::CoordSystem::{board, board_operations, hex_distance, neighbours};
+use crate::damage::DamageReport;
+
+/// Moves per round assumed for an enemy whose tank type we don't know, the scout has the most.
+pub const DEFAULT_ENEMY_MAX_MOVES: u32 = 4;
+
+/// Hexes a shot reaches, an enemy this close to a hex can hit it.
+pub const ENEMY_SHOT_RANGE: u32 = 2;
+
+/// A hit is only put on an enemy seen within this many rounds.
+const DAMAGE_ATTRIBUTION_AGE: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum TeamColor {
+    R,
+    O,
+    Y,
+    G,
+    B,
+    V,
+}
+
+impl TeamColor {
+
+    /**
+     * Return the colour of a tank letter, None if the letter is not a tank.
+     */
+    pub fn from_char(letter: char) -> Option<TeamColor> {
+        return match letter {
+            'R' => Some(TeamColor::R),
+            'O' => Some(TeamColor::O),
+            'Y' => Some(TeamColor::Y),
+            'G' => Some(TeamColor::G),
+            'B' => Some(TeamColor::B),
+            'V' => Some(TeamColor::V),
+            _ => None,
+        };
+    }
+
+    /**
+     * Return the letter of the colour, as the server writes it.
+     */
+    pub fn to_char(&self) -> char {
+        return match self {
+            TeamColor::R => 'R',
+            TeamColor::O => 'O',
+            TeamColor::Y => 'Y',
+            TeamColor::G => 'G',
+            TeamColor::B => 'B',
+            TeamColor::V => 'V',
+        };
+    }
+}
+
+/**
+ * Where a sighting comes from.
+ *  - Seen: The enemy was on a scan, ours or a teammate's.
+ *  - Inferred: Nobody saw the enemy, it is only the most likely hex it shot us from (see EnemyTracker::record_damage).
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SightingSource {
+    #[default]
+    Seen,
+    Inferred,
+}
+
+/**
+ * One sighting of an enemy.
+ *  - lastseen_x_pos, lastseen_y_pos, lastseen_z_pos: The absolute (q, r, s) position the enemy was seen on.
+ *  - team: The colour of the enemy.
+ *  - seen_at_round: The round of the sighting.
+ *  - reported_by: The colour of the teammate that saw it, None if we saw it ourselves.
+ *  - source: Whether the enemy was really seen there or only inferred from a hit.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnemiesPos {
+    // Position := (x_pos,y_pos,z_pos)
+    #[serde(rename = "q")]
+    lastseen_x_pos: i32,
+    #[serde(rename = "r")]
+    lastseen_y_pos: i32,
+    #[serde(rename = "s")]
+    lastseen_z_pos: i32,
+    team: TeamColor,
+    #[serde(rename = "round")]
+    seen_at_round: u32,
+    #[serde(default)]
+    reported_by: Option<char>,
+    #[serde(default)]
+    source: SightingSource,
+}
+
+impl EnemiesPos {
+    pub fn new(
+        relative_x: i32,
+        relative_y: i32,
+        relative_z: i32,
+        team: TeamColor,
+        seen_at_round: u32,
+        reported_by: Option<char>,
+    ) -> Self {
+        Self {
+            lastseen_x_pos: relative_x,
+            lastseen_y_pos: relative_y,
+            lastseen_z_pos: relative_z,
+            team: team,
+            seen_at_round: seen_at_round,
+            reported_by: reported_by,
+            source: SightingSource::Seen,
+        }
+    }
+
+    /**
+     * Sighting of an enemy nobody saw, inferred from a hit we took.
+     */
+    pub fn inferred(x: i32, y: i32, z: i32, team: TeamColor, round: u32) -> Self {
+        return Self { source: SightingSource::Inferred, ..Self::new(x, y, z, team, round, None) };
+    }
+
+    pub fn get_position(&self) -> (i32, i32, i32) {
+        return (self.lastseen_x_pos, self.lastseen_y_pos, self.lastseen_z_pos);
+    }
+
+    pub fn get_team(&self) -> TeamColor {
+        return self.team;
+    }
+
+    pub fn get_seen_at_round(&self) -> u32 {
+        return self.seen_at_round;
+    }
+
+    pub fn get_reported_by(&self) -> Option<char> {
+        return self.reported_by;
+    }
+
+    pub fn get_source(&self) -> SightingSource {
+        return self.source;
+    }
+}
+
+/**
+ * Every sighting of every enemy, by colour, with the round and who saw it. From the last sighting and the moves an
+ * enemy has per round, the tracker predicts where the enemy can be now (likely_positions), and where it can get to by
+ * the end of the current round (reachable_this_round, enemies_reaching) for targeting and avoidance.
+ * The hits we took are kept too (record_damage), a hit that only one enemy could have made is also a sighting of it.
+ * Only the walls of the board stop an enemy, unknown hexes are assumed free.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "TrackerSnapshot", from = "TrackerSnapshot")]
+pub struct EnemyTracker {
+    sightings: HashMap<TeamColor, Vec<EnemiesPos>>,
+    max_moves: HashMap<TeamColor, u32>,
+    damage: Vec<DamageReport>,
+}
+
+/**
+ * Serialized form of the tracker: the sightings of every enemy in one list, and the maps sorted by colour so snapshots
+ * don't change from one run to the next.
+ */
+#[derive(Serialize, Deserialize)]
+struct TrackerSnapshot {
+    #[serde(default)]
+    sightings: Vec<EnemiesPos>,
+    #[serde(default)]
+    max_moves: BTreeMap<TeamColor, u32>,
+    #[serde(default)]
+    damage: Vec<DamageReport>,
+}
+
+impl From<EnemyTracker> for TrackerSnapshot {
+    fn from(tracker: EnemyTracker) -> Self {
+        let mut sightings: Vec<EnemiesPos> = tracker.sightings.into_values().flatten().collect();
+        sightings.sort_by_key(|sighting| (sighting.team, sighting.seen_at_round));
+
+        Self {
+            sightings: sightings,
+            max_moves: tracker.max_moves.into_iter().collect(),
+            damage: tracker.damage,
+        }
+    }
+}
+
+impl From<TrackerSnapshot> for EnemyTracker {
+    fn from(snapshot: TrackerSnapshot) -> Self {
+        let mut tracker = EnemyTracker::new();
+        for sighting in snapshot.sightings {
+            tracker.record(sighting);
+        }
+        tracker.max_moves = snapshot.max_moves.into_iter().collect();
+        tracker.damage = snapshot.damage;
+        return tracker;
+    }
+}
+
+impl EnemyTracker {
+
+    /**
+     * Create a tracker with no sighting.
+     */
+    pub fn new() -> Self {
+        Self {
+            sightings: HashMap::new(),
+            max_moves: HashMap::new(),
+            damage: Vec::new(),
+        }
+    }
+
+    /**
+     * Record a sighting. The same sighting reported twice, for example by us and by a teammate, is only kept once.
+     * An inferred sighting that is then really seen becomes a seen one.
+     */
+    pub fn record(&mut self, sighting: EnemiesPos) {
+        let history = self.sightings.entry(sighting.team).or_insert_with(Vec::new);
+        match history.iter_mut().find(|seen| seen.get_position() == sighting.get_position() && seen.seen_at_round == sighting.seen_at_round) {
+            Some(repeated) => {
+                if sighting.source == SightingSource::Seen {
+                    repeated.source = SightingSource::Seen;
+                }
+            },
+            None => {
+                history.push(sighting);
+                history.sort_by_key(|seen| seen.seen_at_round);
+            },
+        }
+    }
+
+    /**
+     * Record a hit we took. If exactly one enemy seen in the last DAMAGE_ATTRIBUTION_AGE rounds could have been in the
+     * shooter cone, the hit is put on it and the most likely hex of the cone it could reach is recorded as an inferred sighting.
+     * Return the enemy the hit was put on.
+     */
+    pub fn record_damage(&mut self, mut report: DamageReport, game_board: &board) -> Option<TeamColor> {
+        let round = report.get_round();
+        let suspects: Vec<(TeamColor, (i32, i32, i32))> = self.get_colours().into_iter()
+            .filter(|colour| self.last_sighting(*colour).map_or(false, |sighting| round.saturating_sub(sighting.seen_at_round) <= DAMAGE_ATTRIBUTION_AGE))
+            .filter_map(|colour| {
+                let reachable = self.reachable_this_round(colour, round, game_board);
+                // The cone is sorted most likely first.
+                report.get_cone().iter().find(|(hex, _)| reachable.contains(hex)).map(|(hex, _)| (colour, *hex))
+            })
+            .collect();
+
+        let shooter = match suspects.as_slice() {
+            [(colour, hex)] => {
+                self.record(EnemiesPos::inferred(hex.0, hex.1, hex.2, *colour, round));
+                report.set_shooter(*colour);
+                Some(*colour)
+            },
+            _ => None,
+        };
+        self.damage.push(report);
+        return shooter;
+    }
+
+    /**
+     * Return the hits we took, oldest first.
+     */
+    pub fn get_damage_reports(&self) -> &[DamageReport] {
+        return &self.damage;
+    }
+
+    /**
+     * Return the last hit we took.
+     */
+    pub fn last_damage(&self) -> Option<&DamageReport> {
+        return self.damage.last();
+    }
+
+    /**
+     * Hits of the last 'max_age' rounds that could not be put on an enemy: the shooter is only known by its cone.
+     */
+    pub fn unknown_shooters(&self, round: u32, max_age: u32) -> Vec<&DamageReport> {
+        return self.damage.iter()
+            .filter(|report| report.get_shooter().is_none() && !report.get_cone().is_empty())
+            .filter(|report| round.saturating_sub(report.get_round()) <= max_age)
+            .collect();
+    }
+
+    /**
+     * Every hex the unknown shooter of a hit can be on by the end of the given round, from any hex of its cone.
+     */
+    pub fn shooter_reachable_this_round(&self, report: &DamageReport, round: u32, game_board: &board) -> Vec<(i32, i32, i32)> {
+        let moves = DEFAULT_ENEMY_MAX_MOVES * (round.saturating_sub(report.get_round()) + 1);
+        let mut hexes: Vec<(i32, i32, i32)> = report.get_cone().iter()
+            .flat_map(|(hex, _)| reachable_hexes(game_board, *hex, moves).into_keys())
+            .collect();
+        hexes.sort();
+        hexes.dedup();
+        return hexes;
+    }
+
+    /**
+     * Set the moves per round of an enemy, once its tank type is known.
+     */
+    pub fn set_max_moves(&mut self, team: TeamColor, moves: u32) {
+        self.max_moves.insert(team, moves);
+    }
+
+    /**
+     * Return the moves per round of an enemy, DEFAULT_ENEMY_MAX_MOVES if its tank type is not known.
+     */
+    pub fn get_max_moves(&self, team: TeamColor) -> u32 {
+        return *self.max_moves.get(&team).unwrap_or(&DEFAULT_ENEMY_MAX_MOVES);
+    }
+
+    /**
+     * Return the sightings of an enemy, oldest first.
+     */
+    pub fn get_sightings(&self, team: TeamColor) -> &[EnemiesPos] {
+        return self.sightings.get(&team).map_or(&[], |history| history.as_slice());
+    }
+
+    /**
+     * Return the most recent sighting of an enemy.
+     */
+    pub fn last_sighting(&self, team: TeamColor) -> Option<&EnemiesPos> {
+        return self.get_sightings(team).last();
+    }
+
+    /**
+     * Return the colours of the enemies seen at least once.
+     */
+    pub fn get_colours(&self) -> Vec<TeamColor> {
+        let mut colours: Vec<TeamColor> = self.sightings.keys().copied().collect();
+        colours.sort_by_key(|colour| colour.to_char());
+        return colours;
+    }
+
+    /**
+     * Where the enemy can be at the start of the given round, with the probability of each hex, most likely first.
+     * An enemy seen this round is where it was seen. Otherwise every hex it could have driven to since is possible,
+     * the closer to the sighting the more likely, since tanks don't use all their moves every round.
+     */
+    pub fn likely_positions(&self, team: TeamColor, round: u32, game_board: &board) -> Vec<((i32, i32, i32), f32)> {
+        let sighting = match self.last_sighting(team) {
+            Some(sighting) => sighting,
+            None => return Vec::new(),
+        };
+        let moves = self.get_max_moves(team) * round.saturating_sub(sighting.seen_at_round);
+
+        let reachable = reachable_hexes(game_board, sighting.get_position(), moves);
+        let total: f32 = reachable.values().map(|distance| 1.0 / (1 + distance) as f32).sum();
+        let mut positions: Vec<((i32, i32, i32), f32)> = reachable.into_iter()
+            .map(|(hex, distance)| (hex, 1.0 / (1 + distance) as f32 / total))
+            .collect();
+
+        positions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.0.cmp(&b.0)));
+        return positions;
+    }
+
+    /**
+     * Every hex the enemy can be on by the end of the given round.
+     */
+    pub fn reachable_this_round(&self, team: TeamColor, round: u32, game_board: &board) -> Vec<(i32, i32, i32)> {
+        let sighting = match self.last_sighting(team) {
+            Some(sighting) => sighting,
+            None => return Vec::new(),
+        };
+        let moves = self.get_max_moves(team) * (round.saturating_sub(sighting.seen_at_round) + 1);
+
+        let mut hexes: Vec<(i32, i32, i32)> = reachable_hexes(game_board, sighting.get_position(), moves).into_keys().collect();
+        hexes.sort();
+        return hexes;
+    }
+
+    /**
+     * Enemies seen in the last 'max_age' rounds that can get within shot range of the position by the end of the round.
+     */
+    pub fn enemies_reaching(&self, position: (i32, i32, i32), round: u32, max_age: u32, game_board: &board) -> Vec<TeamColor> {
+        return self.get_colours().into_iter()
+            .filter(|colour| self.last_sighting(*colour).map_or(false, |sighting| round.saturating_sub(sighting.seen_at_round) <= max_age))
+            .filter(|colour| {
+                self.reachable_this_round(*colour, round, game_board).iter().any(|hex| hex_distance(*hex, position) <= ENEMY_SHOT_RANGE)
+            })
+            .collect();
+    }
+}
+
+/**
+ * Hexes of the board within 'moves' drives of the start, going around the walls, with their number of drives.
+ */
+fn reachable_hexes(game_board: &board, start: (i32, i32, i32), moves: u32) -> HashMap<(i32, i32, i32), u32> {
+    // No enemy needs more drives than the width of the board.
+    let moves = moves.min(2 * game_board.get_side_len() as u32);
+    let mut distances: HashMap<(i32, i32, i32), u32> = HashMap::new();
+    let mut queue: VecDeque<(i32, i32, i32)> = VecDeque::from(vec![start]);
+    distances.insert(start, 0);
+
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[&current];
+        if distance >= moves {
+            continue;
+        }
+        for next in neighbours(current) {
+            let free = game_board.get_tile(next.0, next.1, next.2).map_or(false, |tile| tile.get_occupied_by() != 'W');
+            if free && !distances.contains_key(&next) {
+                distances.insert(next, distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    return distances;
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::game::*;
+    use crate::CoordSystem::OWN_SCAN_CONFIDENCE;
+
+    #[test]
+    fn test_records_sightings_per_colour() {
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(1, -1, 0, TeamColor::B, 3, None));
+        tracker.record(EnemiesPos::new(0, 0, 0, TeamColor::B, 2, Some('G')));
+        tracker.record(EnemiesPos::new(1, -1, 0, TeamColor::B, 3, Some('G')));
+
+        assert_eq!(2, tracker.get_sightings(TeamColor::B).len(), "The same sighting reported twice should be kept once.");
+        assert_eq!((1, -1, 0), tracker.last_sighting(TeamColor::B).unwrap().get_position(), "The last sighting is the most recent round.");
+        assert_eq!(Some('G'), tracker.get_sightings(TeamColor::B)[0].get_reported_by(), "The sighting should remember who saw it.");
+        assert!(tracker.get_sightings(TeamColor::R).is_empty(), "R was never seen.");
+    }
+
+    #[test]
+    fn test_predicts_positions_from_max_moves() {
+        let mut game_board: board = board::initialize(5);
+        game_board.merge_tile((0, -1, 1), 'W', 1, 1, None, OWN_SCAN_CONFIDENCE);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, 0, 0, TeamColor::O, 4, None));
+        tracker.set_max_moves(TeamColor::O, 1);
+
+        assert_eq!(vec![((0, 0, 0), 1.0)], tracker.likely_positions(TeamColor::O, 4, &game_board), "An enemy seen this round is where it was seen.");
+
+        let positions = tracker.likely_positions(TeamColor::O, 5, &game_board);
+        assert_eq!(6, positions.len(), "One round later it can be on its hex or 5 of its neighbours, the wall blocks the sixth.");
+        assert_eq!((0, 0, 0), positions[0].0, "Staying put is the most likely.");
+        assert!(!positions.iter().any(|(hex, _)| *hex == (0, -1, 1)), "An enemy can't be on a wall.");
+
+        assert_eq!(vec![TeamColor::O], tracker.enemies_reaching((0, 3, -3), 4, 2, &game_board), "O can drive within shot range of (0, 3, -3) this round.");
+        assert!(tracker.enemies_reaching((0, 4, -4), 4, 2, &game_board).is_empty(), "(0, 4, -4) is out of reach this round.");
+    }
+
+    #[test]
+    fn test_damage_is_put_on_the_only_suspect() {
+        let game_board: board = board::initialize(5);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -3, 3, TeamColor::Y, 4, None));
+        tracker.set_max_moves(TeamColor::Y, 1);
+        tracker.record(EnemiesPos::new(4, 0, -4, TeamColor::G, 4, None));
+        tracker.set_max_moves(TeamColor::G, 1);
+
+        let report = DamageReport::new((0, 0, 0), vec!["S".to_string(), "S".to_string()], 5, &game_board);
+        assert_eq!(Some(TeamColor::Y), tracker.record_damage(report, &game_board), "Only Y could have shot from the north.");
+        assert_eq!((0, -2, 2), tracker.last_sighting(TeamColor::Y).unwrap().get_position(), "Y is where the shot came from.");
+        assert_eq!(SightingSource::Inferred, tracker.last_sighting(TeamColor::Y).unwrap().get_source(), "Nobody saw Y there, the sighting is inferred.");
+        assert!(tracker.unknown_shooters(5, 1).is_empty(), "The hit has a shooter.");
+
+        let report = DamageReport::new((0, 0, 0), vec!["N".to_string()], 5, &game_board);
+        assert_eq!(None, tracker.record_damage(report, &game_board), "No enemy could have shot from the south.");
+        assert_eq!(1, tracker.unknown_shooters(5, 1).len(), "The second hit has an unknown shooter.");
+        assert!(tracker.shooter_reachable_this_round(tracker.last_damage().unwrap(), 5, &game_board).contains(&(0, 1, -1)), "The unknown shooter is around its cone.");
+    }
+
+}
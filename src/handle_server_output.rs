@@ -1,223 +1,443 @@
-use std::io::{self, stdin, stdout, BufRead, Write};
-use std::process::exit;
-
-use crate::CoordSystem::{self, board_operations, board};
-use crate::action_manager::{action_manager, playerOutput, manage_player_action};
-use crate::parser::{self, get_args};
-use crate::player::{self, Player, PlayerState};
-use crate::robot_strategies;
-use crate::scan_parser::{self, GetScanReturn, ScanResponse};
-use crate::strategy_controller::{strategy_controller, startegies, strategiesType};
-
-const POINTS_EXPENDED_INDEX: usize = 1;
-const ROUND_NUMBER_INDEX: usize = 1;
-
-enum ServerResponseType {
-    // TODO: cleanup
-    // Start,
-    Damage,
-    Dead,
-    Move,
-    Ok,
-    Huh,
-    Action,
-    Timeout,
-    Finish,
-}
-
-/**
- * Actions_history helps to keep track of action performed last and wha was the server response.
- */
-struct Actions_history {
-    server_resp: String,
-    player_action: String,
-    constructed_server_resp: Vec<String>,
-}
-
-/**
- * Scan response struct is reponsible to hold information about a scan action:
- *  - scanned_positions: A vector of String that contains positions from 'a' to 'k' in alphabetical order.
- *  - enemies_pos: A vector of usize that holds the position of enemies returned by the scan. for example:
- *      -> If the returned scan is abc Refgh ijk. This means that there is an enemy R and the index of the enemy on scanned_position vector
- *      will be 3.
- *  - walls_pos: A vector of usize that holds the position of walls returned by the scan. for example:
- *      -> If the returned scan is abW defWW ijW. This means that there are walls 'W' and their index on scanned_position vector are [2, 6, 7, 10].
- * - enemy_detected: True if the scan detected an enemy, enemies has the following possible letters [R, O, Y, G, B, V].
- * - wall_detected: true if there are walls detected by the scan.
- *   
- */
-
-impl Actions_history {
-    /**
-     * Initialize actions_history
-     */
-    fn initialize() -> Self {
-        Self {
-            server_resp: " ".to_string(),
-            player_action: " ".to_string(),
-            constructed_server_resp: Vec::new(),
-        }
-    }
-
-    /**
-     * Record the last action the player has performed.
-     */
-    fn set_player_action(&mut self, action: String) {
-        self.player_action = action;
-    }
-
-    fn get_arg_vector(self) -> Vec<String>{
-        return self.constructed_server_resp.clone();
-    }
-
-    /**
-     * Record the server response to the last action the player has performed, or the last server response.
-     */
-    fn set_server_resp(&mut self, response: String) {
-        self.server_resp = response;
-        self.constructed_server_resp = parser::get_args(self.server_resp.clone());
-        // This a vec of string i.e ['DAMAGE', 'N', 'NE']
-    }
-
-    fn get_server_response_type(&mut self) -> ServerResponseType {
-        match self.constructed_server_resp.get(0).unwrap().as_str() {
-            // "START" => ServerResponseType::Start,
-            "MOVE" => ServerResponseType::Move,
-            "FINISH" => ServerResponseType::Finish,
-            "DEAD" => ServerResponseType::Dead,
-            "ACTION!" => ServerResponseType::Action,
-            "TIMEOUT!" => ServerResponseType::Timeout,
-            "OK" => ServerResponseType::Ok,
-            "HUH?" => ServerResponseType::Huh,
-            "DAMAGE" => ServerResponseType::Damage,
-            _ => ServerResponseType::Action,
-        }
-    }
-}
-
-pub fn get_input() -> String {
-    let mut line = String::new();
-    io::stdin()
-        .lock()
-        .read_line(&mut line)
-        .expect("Could not read line");
-    return line;
-}
-
-/**
- * Handles server output, it is the main controller of the program and should be the one responsible to update other modules
- *  such as player and board. 
- * 
- * It is also responsible to contact the startege controller and pass on the active strategy to the robot strategy. 
- * 
- * The flow is as follows:
- * 
- *  1. Wait to receive a message from the recerver on its STDIN
- *  2. Match the resopnse type.
- *  3. Update player model.
- *  4. Stratehy manager will validate which strategy should be used.
- *  5. Run the defined strategy.
- * 
- * Each strategy has a simple AI to veryfy the players state and make a decision, it will take in consideration:
- *     - Round number.
- *     - Player step [how many actions has the player taken in the current round].
- *     - Others ??
- */
-pub fn handle_server_output(player :&mut Player) {
-
-
-    // INITIALIZE DATA STRUCTURES
-    let mut startegy_manager :startegies = strategy_controller::initialize_initial_startegy();      // --> Responsible to decide active strategy.
-    let mut action_manager :action_manager = action_manager::initialize();                          // --> Controller that active player will use to communicate with server.
-    let mut board :board = board::initialize(player.get_side_len() as i32);                         // --> Board data structure.
-    let mut scan :ScanResponse = GetScanReturn::initialize_scan_response();                         // --> scan object, returns informatino about the scanned area ussed on Robot_strategies.
-        
-
-    while player.get_health() > 0 {
-
-        // Initialize and set a data structure for the server response. Facilitates data parsing.
-        let mut action = Actions_history::initialize();
-        let line:String = get_input();
-        action.set_server_resp(line);
-        let response_type:ServerResponseType = action.get_server_response_type();
-
-
-        match response_type {
-            
-            ServerResponseType::Move => {
-
-                /////////////////////////////////////////////////////////////////////////////////////
-                ///  TODO: Might want to updated the board based on the other players comms channel
-                /// 
-                /// ************************ If the board is calibraded **************************
-                /////////////////////////////////////////////////////////////////////////////////////
-
-                //Update rond number
-                let new_round_num = action.get_arg_vector().get(ROUND_NUMBER_INDEX).unwrap().clone();
-                player.start_round(new_round_num.parse().unwrap());                           // => Reset player counters
-
-                action_manager.reset_counter();                                                             // => reset auxiliary function to cound actions taken that doesn't consume points but are important for the strategy
-                startegy_manager.avaliate_startegy(player, &mut action_manager);                            // => Check if player should changhe strategy
-                go_to_strategy(player, &mut startegy_manager, &mut scan, &mut action_manager);              // => call the right strategy
-
-
-            },
-            ServerResponseType::Finish => exit(0),
-            ServerResponseType::Dead => exit(0),
-            ServerResponseType::Ok => {                                                                                             // ==> If the response is Ok, than previous action was accepted.
-                let max_steps_allowed = 3;
-                // If players has taken all the 
-                if player.get_step_count() < max_steps_allowed{
-
-                    // Check player's last action
-                    match action_manager.get_last_action() {
-                        playerOutput::DRIVE =>{
-                            let points_expended = action.get_arg_vector().get(POINTS_EXPENDED_INDEX).unwrap().clone();
-                            player.update_points_count(points_expended.parse().unwrap());                                               // ==> Player needs to update the points, the OK after a drive will return the total points
-                        }
-                        _ =>()
-                    }
-                    startegy_manager.avaliate_startegy(player, &mut action_manager);                                                    // => Check if player should changhe strategy
-                    go_to_strategy(player, &mut startegy_manager, &mut scan, &mut action_manager);                                      // => call the right strategy
-                }
-            },
-            ServerResponseType::Huh => {
-                // TODO: Something was wrong in the last response to the server, fix it.
-                action_manager.end();
-            },
-            ServerResponseType::Damage => {
-                player.take_damage();
-                if player.get_health() == 0 {
-                    exit(0);
-                }
-            },
-            _ => {
-
-                match action_manager.get_last_action() {
-                    playerOutput::SCAN => {
-                        scan.scan_entry(action.get_arg_vector());
-                        //board.update_board(&mut scan);                                                            // => uncomment when board is updated and functional.
-                        startegy_manager.avaliate_startegy(player, &mut action_manager);                            // => Check if player should changhe strategy
-                        go_to_strategy(player, &mut startegy_manager, &mut scan, &mut action_manager);              // => call the right strategy
-                    },
-                    playerOutput::TURN => (),
-                    playerOutput::SKIP => (),
-                    _ =>{
-                        println!("END");
-                        stdout().flush();
-                    }
-                }
-            },
-        }
-    }
-}
-
-fn go_to_strategy(player :&mut Player, startegy_manager :&mut startegies, scan :&mut ScanResponse, action_manager :&mut action_manager){
-
-    match startegy_manager.get_active_strategy() {
-        strategiesType::find_corner => robot_strategies::orient_to_near_corner(player, scan, action_manager),
-        strategiesType::exlporer => robot_strategies::explorer(player, scan, action_manager),
-        strategiesType::traverse => robot_strategies::traverse(player, scan, action_manager),
-        _=> (),
-    }
-}
\ No newline at end of file
+use std::io::{self, stdin, stdout, BufRead, Write};
+use std::process::exit;
+
+use crate::CoordSystem::{self, board_operations, board};
+use crate::action_manager::{action_manager, playerOutput, manage_player_action};
+use crate::board_renderer;
+use crate::comms::{self, TeamBroadcaster, TeamListener};
+use crate::damage::{self, DamageReport};
+use crate::fire_guard::shot_path;
+use crate::game::{EnemiesPos, EnemyTracker, TeamColor};
+use crate::opponent_profile::OpponentProfiler;
+use crate::parser::{self, get_args, RunOptions};
+use crate::player::{self, Player, PlayerState};
+use crate::scan_parser::{self, GetScanReturn, ScanResponse};
+use crate::strategy::{CommandResult, StrategyRegistry};
+use crate::strategy_machine::StrategyMachine;
+use crate::strategy_controller::{strategy_controller, startegies};
+use crate::svg_export::{SvgExport, SvgExporter};
+use crate::team_coordination::{EnemyTarget, TargetCoordinator, target_coordination};
+use crate::team_protocol::TeamMessage;
+use crate::threat_map::ThreatMap;
+
+const POINTS_EXPENDED_INDEX: usize = 1;
+const ROUND_NUMBER_INDEX: usize = 1;
+
+enum ServerResponseType {
+    // TODO: cleanup
+    // Start,
+    Damage,
+    Dead,
+    Move,
+    Ok,
+    Huh,
+    Action,
+    Timeout,
+    Finish,
+}
+
+/**
+ * Actions_history helps to keep track of action performed last and wha was the server response.
+ */
+struct Actions_history {
+    server_resp: String,
+    player_action: String,
+    constructed_server_resp: Vec<String>,
+}
+
+/**
+ * Scan response struct is reponsible to hold information about a scan action:
+ *  - scanned_positions: A vector of String that contains positions from 'a' to 'k' in alphabetical order.
+ *  - enemies_pos: A vector of usize that holds the position of enemies returned by the scan. for example:
+ *      -> If the returned scan is abc Refgh ijk. This means that there is an enemy R and the index of the enemy on scanned_position vector
+ *      will be 3.
+ *  - walls_pos: A vector of usize that holds the position of walls returned by the scan. for example:
+ *      -> If the returned scan is abW defWW ijW. This means that there are walls 'W' and their index on scanned_position vector are [2, 6, 7, 10].
+ * - enemy_detected: True if the scan detected an enemy, enemies has the following possible letters [R, O, Y, G, B, V].
+ * - wall_detected: true if there are walls detected by the scan.
+ *   
+ */
+
+impl Actions_history {
+    /**
+     * Initialize actions_history
+     */
+    fn initialize() -> Self {
+        Self {
+            server_resp: " ".to_string(),
+            player_action: " ".to_string(),
+            constructed_server_resp: Vec::new(),
+        }
+    }
+
+    /**
+     * Record the last action the player has performed.
+     */
+    fn set_player_action(&mut self, action: String) {
+        self.player_action = action;
+    }
+
+    fn get_arg_vector(&self) -> Vec<String>{
+        return self.constructed_server_resp.clone();
+    }
+
+    /**
+     * Record the server response to the last action the player has performed, or the last server response.
+     */
+    fn set_server_resp(&mut self, response: String) {
+        self.server_resp = response;
+        self.constructed_server_resp = parser::get_args(self.server_resp.clone());
+        // This a vec of string i.e ['DAMAGE', 'N', 'NE']
+    }
+
+    fn get_server_response_type(&mut self) -> ServerResponseType {
+        match self.constructed_server_resp.get(0).unwrap().as_str() {
+            // "START" => ServerResponseType::Start,
+            "MOVE" => ServerResponseType::Move,
+            "FINISH" => ServerResponseType::Finish,
+            "DEAD" => ServerResponseType::Dead,
+            "ACTION!" => ServerResponseType::Action,
+            "TIMEOUT!" => ServerResponseType::Timeout,
+            "OK" => ServerResponseType::Ok,
+            "HUH?" => ServerResponseType::Huh,
+            "DAMAGE" => ServerResponseType::Damage,
+            _ => ServerResponseType::Action,
+        }
+    }
+}
+
+pub fn get_input() -> String {
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .expect("Could not read line");
+    return line;
+}
+
+/**
+ * Handles server output, it is the main controller of the program and should be the one responsible to update other modules
+ *  such as player and board. 
+ * 
+ * It is also responsible to contact the startege controller and pass on the active strategy to the robot strategy. 
+ * 
+ * The flow is as follows:
+ * 
+ *  1. Wait to receive a message from the recerver on its STDIN
+ *  2. Match the resopnse type.
+ *  3. Update player model.
+ *  4. Give the server answer to the active strategy.
+ *  5. Ask the active strategy for its next action and send it.
+ * 
+ * Each strategy has a simple AI to veryfy the players state and make a decision, it will take in consideration:
+ *     - Round number.
+ *     - Player step [how many actions has the player taken in the current round].
+ *     - Others ??
+ *
+ * The run options turn on debugging output such as printing the board to stderr at the start of each round.
+ *
+ * The team listener, when there is one, has the messages of the teammates. They are put into the board at the start of each round,
+ * and the listener is shut down when the game is over.
+ *
+ * The team broadcaster publishes our pose after each accepted drive and our scans (with the enemies they found) after each scan.
+ *
+ * A DAMAGE message carries the directions of the shot that hit us, the likely positions of the shooter go to the enemy tracker.
+ *
+ * With --profiles, the profiles of the enemies from the earlier games tune the enemy tracker, and the game is added to them when it ends.
+ */
+pub fn handle_server_output(player :&mut Player, options :&RunOptions, team_listener :Option<TeamListener>, mut team_broadcaster :TeamBroadcaster) {
+
+
+    // INITIALIZE DATA STRUCTURES
+    let mut startegy_manager :startegies = select_strategy(options);                              // --> Responsible to decide active strategy.
+    let mut action_manager :action_manager = action_manager::initialize();                          // --> Controller that active player will use to communicate with server.
+    let mut board :board = board::initialize(player.get_side_len() as i32);                         // --> Board data structure.
+    let mut scan :ScanResponse = GetScanReturn::initialize_scan_response();                         // --> scan object, returns informatino about the scanned area ussed on Robot_strategies.
+    let mut coordinator :TargetCoordinator = TargetCoordinator::initialize(player.get_colour().chars().next().unwrap_or(' '));   // --> Team target assignment.
+    let mut tracker :EnemyTracker = EnemyTracker::new();                                           // --> Every enemy sighting, predicts where the enemies are.
+    let mut svg_exporter :Option<SvgExporter> = options.svg_dir.as_ref().map(|dir| SvgExporter::initialize(dir));   // --> Debug: svg picture of each round.
+    let mut profiler :Option<OpponentProfiler> = options.profiles.as_ref().map(|path| OpponentProfiler::load(path));   // --> How the enemies play, across games.
+    let mut round_started :bool = false;                                                            // --> No round was played before the first MOVE.
+        
+
+    while player.get_health() > 0 {
+
+        // Initialize and set a data structure for the server response. Facilitates data parsing.
+        let mut action = Actions_history::initialize();
+        let line:String = get_input();
+        action.set_server_resp(line);
+        let response_type:ServerResponseType = action.get_server_response_type();
+
+
+        match response_type {
+            
+            ServerResponseType::Move => {
+
+                // Update the board with what the teammates told us since the last round.
+                if let Some(listener) = team_listener.as_ref() {
+                    for message in listener.drain() {
+                        match comms::parse_thread_comms(message, player, &mut board) {
+                            Ok(envelope) => match envelope.message {
+                                TeamMessage::EnemySighting { enemy, position, seen_round } => {
+                                    coordinator.record_sighting(enemy, position, seen_round);
+                                    record_enemy(&mut tracker, &board, player, enemy, position, seen_round, Some(envelope.sender));
+                                },
+                                TeamMessage::TargetClaim { enemy, .. } => coordinator.record_claim(envelope.sender, enemy, envelope.round),
+                                TeamMessage::Shot { enemy, position } => {
+                                    coordinator.record_shot(enemy, position);
+                                },
+                                _ => ()
+                            },
+                            Err(error) => eprintln!("Ignoring message from teammate: {}", error),
+                        }
+                    }
+                }
+
+                // Picture of the round that just ended, before the round counters are reset.
+                if let (Some(exporter), true) = (svg_exporter.as_mut(), round_started) {
+                    export_round(exporter, &board, player, &action_manager, &tracker);
+                }
+
+                //Update rond number
+                let new_round_num = action.get_arg_vector().get(ROUND_NUMBER_INDEX).unwrap().clone();
+                player.start_round(new_round_num.parse().unwrap());                           // => Reset player counters
+                round_started = true;
+                team_broadcaster.start_round(player.get_rounds());                                          // => Reset the team message rate limit
+                share_round_shots(&action_manager.get_round_shots(), startegy_manager.get_target(), &mut coordinator, &mut team_broadcaster);   // => The team counts our hits of the last round too
+                if player.is_calibrated() {
+                    team_broadcaster.publish_pose(player);
+                }
+                if let Some(profiler) = profiler.as_mut() {
+                    if player.is_calibrated() {
+                        profiler.record_position(player.get_rounds(), player.get_position());
+                    }
+                    profiler.adapt(&mut tracker);                                                           // => Enemy moves from their tank type
+                }
+
+                action_manager.update_fire_guard(&board, player.get_rounds());                             // => Teammates to avoid when shooting this round
+                action_manager.reset_counter();                                                             // => reset auxiliary function to cound actions taken that doesn't consume points but are important for the strategy
+                startegy_manager.avaliate_startegy(player, &mut coordinator);                              // => Update what the strategy should know
+                if let Some(target) = startegy_manager.get_target() {
+                    team_broadcaster.publish(TeamMessage::TargetClaim { enemy: target.colour, position: target.position });   // => Tell the team which enemy we are going after
+                }
+                startegy_manager.start_turn(player, &board, &scan, &tracker, &mut action_manager);         // => New round for the strategy
+                if options.debug_board || options.debug_board_file.is_some() {
+                    let path = startegy_manager.get_planned_path(player, &board, &scan, &tracker, &mut action_manager);
+                    if options.debug_board {
+                        board_renderer::print_board(&board, player, &path);                                 // => Debug: what the robot believes about the arena.
+                        board_renderer::print_threat_map(&board, player, &ThreatMap::build(&tracker, &board, player.get_rounds()));   // => Debug: where the enemies can shoot next round.
+                    }
+                    if let Some(file_name) = options.debug_board_file.as_ref() {
+                        if let Err(error) = board_renderer::append_board(file_name, &board, player, &path) {
+                            eprintln!("Could not write the board to {}: {}", file_name, error);
+                        }
+                    }
+                }
+                startegy_manager.play(player, &board, &scan, &tracker, &mut action_manager);               // => Send the strategy's first action
+
+
+            },
+            ServerResponseType::Finish | ServerResponseType::Dead => {
+                if let (Some(exporter), true) = (svg_exporter.as_mut(), round_started) {
+                    export_round(exporter, &board, player, &action_manager, &tracker);
+                }
+                save_profiles(profiler.as_mut(), &tracker);
+                if let Some(listener) = team_listener {
+                    listener.shutdown();
+                }
+                exit(0);
+            },
+            ServerResponseType::Ok => {                                                                                             // ==> If the response is Ok, than previous action was accepted.
+                // Check player's last action
+                match action_manager.get_last_action() {
+                    playerOutput::DRIVE =>{
+                        let points_expended = action.get_arg_vector().get(POINTS_EXPENDED_INDEX).unwrap().clone();
+                        player.update_points_count(points_expended.parse().unwrap());                                               // ==> Player needs to update the points, the OK after a drive will return the total points
+                        player.drive_forward();                                                                                     // ==> Drive was accepted, the player moved one tile forward.
+                        if let (Some(exporter), true) = (svg_exporter.as_mut(), player.is_calibrated()) {
+                            exporter.record_position(player.get_position());
+                        }
+                        if player.is_calibrated() {
+                            team_broadcaster.publish_pose(player);                                                                  // ==> Tell the team where we are now.
+                        }
+                    }
+                    _ =>()
+                }
+                startegy_manager.on_result(CommandResult::Accepted, player);                                                    // => The strategy learns its command was accepted
+
+                // If players has taken all the steps of the round, wait for the next round
+                if player.get_step_count() < player::MAX_STEPS_PER_ROUND{
+                    startegy_manager.avaliate_startegy(player, &mut coordinator);                                               // => Update what the strategy should know
+                    startegy_manager.play(player, &board, &scan, &tracker, &mut action_manager);                               // => Send the strategy's next action
+                }
+            },
+            ServerResponseType::Huh => {
+                // TODO: Something was wrong in the last response to the server, fix it.
+                startegy_manager.on_result(CommandResult::Refused, player);
+                action_manager.end();
+            },
+            ServerResponseType::Damage => {
+                player.take_damage();
+
+                // Follow the directions of the shot back to the shooter, the strategy controller evades next round.
+                let directions = damage::parse_damage_directions(&action.get_arg_vector());
+                if player.is_calibrated() {
+                    let report = DamageReport::new(player.get_position(), directions, player.get_rounds(), &board);
+                    match tracker.record_damage(report, &board) {
+                        Some(shooter) => eprintln!("Hit on round {} by {}", player.get_rounds(), shooter.to_char()),
+                        None => eprintln!("Hit on round {} by an unknown shooter", player.get_rounds()),
+                    }
+                }
+                if player.get_health() == 0 {
+                    save_profiles(profiler.as_mut(), &tracker);
+                    if let Some(listener) = team_listener {
+                        listener.shutdown();
+                    }
+                    exit(0);
+                }
+            },
+            _ => {
+
+                match action_manager.get_last_action() {
+                    playerOutput::SCAN => {
+                        scan.scan_entry(action.get_arg_vector());
+                        if player.is_calibrated(){
+                            let facing = player.get_facing_direction();
+                            board.update_board(&mut scan, player.get_position(), facing.as_str(), player.get_rounds(), player.get_step_count());
+                            team_broadcaster.publish_scan(player, action.get_arg_vector(), &mut scan);                 // => Tell the team what we saw.
+                            for enemy in scan.get_enemy_positions(player.get_position(), facing.as_str()) {
+                                coordinator.record_sighting(enemy.get_occupied_by(), enemy.get_coords(), player.get_rounds());
+                                record_enemy(&mut tracker, &board, player, enemy.get_occupied_by(), enemy.get_coords(), player.get_rounds(), None);
+                            }
+                        }
+                        startegy_manager.on_result(CommandResult::Scanned(scan.clone()), player);                  // => The strategy reads the scan
+                        startegy_manager.avaliate_startegy(player, &mut coordinator);                              // => Update what the strategy should know
+                        startegy_manager.play(player, &board, &scan, &tracker, &mut action_manager);               // => Send the strategy's next action
+                    },
+                    playerOutput::TURN => (),
+                    playerOutput::SKIP => (),
+                    _ =>{
+                        println!("END");
+                        stdout().flush();
+                    }
+                }
+            },
+        }
+    }
+}
+
+/**
+ * Record an enemy sighting in the tracker. Our own tank and our teammates seen on a scan are not enemies.
+ */
+fn record_enemy(tracker :&mut EnemyTracker, board :&board, player :&Player, enemy :char, position :(i32, i32, i32), round :u32, reporter :Option<char>){
+    let friendly = player.get_colour().contains(enemy) || board.get_teammates().iter().any(|teammate| teammate.get_occupied_by() == enemy);
+    if let (Some(colour), false) = (TeamColor::from_char(enemy), friendly) {
+        tracker.record(EnemiesPos::new(position.0, position.1, position.2, colour, round, reporter));
+    }
+}
+
+/**
+ * Tell the team about the shots of the round that just ended that reached our target.
+ * Call it after the team round was started, so the messages count against the budget of the new round and carry its
+ * number.
+ *
+ *  - shots: The shots of the round with the position they were fired from, see get_round_shots.
+ *  - target: The enemy we were shooting at, nothing is sent without one.
+ */
+fn share_round_shots(shots :&[(String, (i32, i32, i32))], target :Option<EnemyTarget>, coordinator :&mut TargetCoordinator, team_broadcaster :&mut TeamBroadcaster){
+    if let Some(target) = target {
+        for (command, origin) in shots {
+            let directions: Vec<String> = get_args(command.clone()).into_iter().skip(1).filter(|direction| !direction.is_empty()).collect();
+            if let Some(end) = shot_path(*origin, &directions).last().copied() {
+                if coordinator.record_shot(target.colour, end) {
+                    team_broadcaster.publish(TeamMessage::Shot { enemy: target.colour, position: end });
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Create the strategy chosen on the command line: the strategy machine of --strategy-config if given, otherwise the
+ * strategy named by --strategy. The default strategy is used if they can't be created.
+ */
+fn select_strategy(options :&RunOptions) -> startegies{
+    let registry = StrategyRegistry::with_options(options);
+
+    if let Some(path) = options.strategy_config.as_ref() {
+        match StrategyMachine::load(path, &registry) {
+            Ok(machine) => return startegies::from_strategy(Box::new(machine)),
+            Err(error) => eprintln!("Could not load the strategy machine {}: {}", path, error),
+        }
+    }
+
+    let name = options.strategy.as_str();
+    return match startegies::from_name(name, &registry) {
+        Some(strategy) => strategy,
+        None => {
+            eprintln!("Unknown strategy {}, available strategies: {:?}. Playing the default one.", name, registry.names());
+            strategy_controller::initialize_initial_startegy()
+        }
+    };
+}
+
+/**
+ * Add the game to the opponent profiles and write them, if they are kept.
+ */
+fn save_profiles(profiler :Option<&mut OpponentProfiler>, tracker :&EnemyTracker){
+    if let Some(profiler) = profiler {
+        if let Err(error) = profiler.save(tracker) {
+            eprintln!("Could not save the opponent profiles: {}", error);
+        }
+    }
+}
+
+/**
+ * Record the shots and position of the round that just ended and write its svg picture.
+ */
+fn export_round(exporter :&mut SvgExporter, board :&board, player :&Player, action_manager :&action_manager, tracker :&EnemyTracker){
+    exporter.record_shots(player.get_rounds(), action_manager.get_round_shots());
+    exporter.record_threat(ThreatMap::build(tracker, board, player.get_rounds()));
+    if player.is_calibrated(){
+        exporter.record_position(player.get_position());
+    }
+    if let Err(error) = exporter.write_round(board, player){
+        eprintln!("Could not write the svg picture of round {}: {}", player.get_rounds(), error);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::handle_server_output::*;
+    use crate::team_bus::{TeamBus, TeamBusConfig};
+    use crate::team_protocol::decode_message;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_shots_reach_the_team_on_the_first_step_of_a_round() {
+        let bus = TeamBus::new(TeamBusConfig::default());
+        let (_reader, writer) = bus.connect();
+        let (teammate_reader, _teammate_writer) = bus.connect();
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(writer)));
+        let mut coordinator = TargetCoordinator::initialize('G');
+        coordinator.record_sighting('R', (0, -2, 2), 1);
+        let target = coordinator.get_enemy('R');
+
+        // The sightings of round 1 use up its whole budget.
+        broadcaster.start_round(1);
+        for q in 0..comms::MAX_ENEMY_MESSAGES_PER_ROUND as i32 {
+            broadcaster.publish(TeamMessage::EnemySighting { enemy: 'Y', position: (q, -q, 0), seen_round: 1 });
+        }
+
+        broadcaster.start_round(2);
+        share_round_shots(&[("SHOOT N-N".to_string(), (0, 0, 0))], target, &mut coordinator, &mut broadcaster);
+        bus.close();
+
+        let shots: Vec<(u32, TeamMessage)> = BufReader::new(teammate_reader).lines()
+            .map(|line| decode_message(&line.unwrap()).unwrap())
+            .filter(|envelope| matches!(envelope.message, TeamMessage::Shot { .. }))
+            .map(|envelope| (envelope.round, envelope.message))
+            .collect();
+        assert_eq!(vec![(2, TeamMessage::Shot { enemy: 'R', position: (0, -2, 2) })], shots, "The shot should reach the team with the round it was sent in.");
+    }
+}
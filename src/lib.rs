@@ -1,10 +1,28 @@
-pub mod parser;
-pub mod player;
-pub mod scan_parser;
-pub mod move_manager;
-pub mod robot_strategies;
-pub mod handle_server_output;
-pub mod comms;
-pub mod CoordSystem;
-pub mod action_manager;
-pub mod strategy_controller;
+pub mod parser;
+pub mod player;
+pub mod scan_parser;
+pub mod move_manager;
+pub mod robot_strategies;
+pub mod handle_server_output;
+pub mod comms;
+pub mod team_protocol;
+pub mod team_coordination;
+pub mod fire_guard;
+pub mod role_negotiation;
+pub mod team_bus;
+pub mod clock;
+pub mod CoordSystem;
+pub mod game;
+pub mod threat_map;
+pub mod damage;
+pub mod opponent_profile;
+pub mod action_manager;
+pub mod strategy;
+pub mod strategy_machine;
+pub mod behaviour_tree;
+pub mod utility_ai;
+pub mod mcts;
+pub mod strategy_controller;
+pub mod board_renderer;
+pub mod svg_export;
+pub mod snapshot;
@@ -1,110 +1,134 @@
-use lib::handle_server_output::get_input;
-use lib::{*};
-use std::env;
-use std::io::{self, BufRead, stdout, Write};
-use std::process::exit;
-mod robot_strategies;
-use lib::player::{Player, PlayerState, tank_type};
-use parser::get_args;
-use std::io::{Error, ErrorKind};
-
-mod mock_server;
-
-const ROBOT_COLOR_INDEX :usize = 2;
-const EXPLORATION_TURNS_INDEX :usize = 3;
-const SIDE_LEN_INDEX: usize = 1;
-
-
-
-fn main() {
-    
-    let mut tank_check = false;
-    let mut scout_check = false;
-    let mut heavy_check = false;
-
-    // Get list of arguments supplied to program
-    let args: Vec<String> = env::args().collect();
-
-    // Get the arguments, the argument we are interested in is on the inderx one of the list.
-    let tank_type_initialization = match args.get(1).unwrap().as_str() {
-        "T"|"t" => {
-                tank_check = true;
-                tank_type::tank
-            },
-        "H"|"h" =>{ 
-                heavy_check = true;
-                tank_type::heavy
-            },
-        "S"|"s" => {
-                scout_check = true;
-                tank_type::scout
-            },
-        _ => tank_type::error
-    };
-
-    // Check if tank initialization has errors
-    let check_error = match tank_type_initialization {
-        tank_type::error => true,
-        _ => false
-    };
-
-    // Throw error if thank option is not acceptable
-    if check_error{
-        Error::new(ErrorKind::InvalidData, "The executable should be called with one of the 3 possible arguments: (T or t): normal tank, (H or h): Heavy tank, (S or s): Scout tank.");
-    }
-
-
-    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 5, tank_type_initialization);
-
-    let first_input = get_args(handle_server_output::get_input());
-
-    match first_input[0].as_str() {
-        "START" => {
-            player.set_initial_information(
-                first_input[ROBOT_COLOR_INDEX].clone(), 
-                first_input[EXPLORATION_TURNS_INDEX].parse().unwrap(), 
-                first_input[SIDE_LEN_INDEX].parse().unwrap());
-
-                println!("IAM {}", args.get(1).unwrap().as_str());
-
-                let mut is_valid :bool = false;
-                
-                while !is_valid{
-                    let scond_input = get_args(handle_server_output::get_input());
-
-                    match scond_input[0].as_str(){
-                        "OK" => is_valid = true,
-                        _ => {
-                            if !tank_check{
-                                println!("IAM T");
-                            }else if !scout_check{
-                                println!("IAM S");
-                                scout_check = true;
-                            }else if !heavy_check {
-                                println!("IAM H");
-                                heavy_check = true;
-                            }else{
-                                is_valid = true;
-                                println!("The tank options where not valid due time out or duplicated tank definition.");
-                                exit(1);
-                            }
-                        }
-                    };
-                }
-
-                ////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-                //                                              :TODO
-                // comms::start_listener_thread(); ** we need to fire a thread for listening to other player responses.**
-                // This listener should provide information to the current player so that he makes informed decisions.
-                //
-                // If needed pass the listener as argument to the function 'handle_server_output' or initialize it on that function
-                ////////////////////////////////////////////////////////////////////////////////////////////////////////////////
-
-                handle_server_output::handle_server_output(&mut player);
-        }
-        _ => {
-            // We should do nothing here because we handle server output inside (execute_robot_strategies)
-            println!("Something went wrong, server probably didn't send anything valid, or reading from the CLI is incorrect.");
-        }
-    }
-}
+use lib::handle_server_output::get_input;
+use lib::{*};
+use std::env;
+use std::io::{self, BufRead, stdout, Write};
+use std::process::exit;
+mod robot_strategies;
+use lib::player::{Player, PlayerState, tank_type};
+use parser::{get_args, get_run_options, RunOptions};
+use std::io::{Error, ErrorKind};
+
+mod mock_server;
+
+const ROBOT_COLOR_INDEX :usize = 2;
+const EXPLORATION_TURNS_INDEX :usize = 3;
+const SIDE_LEN_INDEX: usize = 1;
+
+
+
+fn main() {
+    
+    let mut tank_check = false;
+    let mut scout_check = false;
+    let mut heavy_check = false;
+
+    // Get list of arguments supplied to program
+    let args: Vec<String> = env::args().collect();
+
+    // Get the arguments, the argument we are interested in is on the inderx one of the list.
+    let tank_type_initialization = match args.get(1).unwrap().as_str() {
+        "T"|"t" => {
+                tank_check = true;
+                tank_type::tank
+            },
+        "H"|"h" =>{ 
+                heavy_check = true;
+                tank_type::heavy
+            },
+        "S"|"s" => {
+                scout_check = true;
+                tank_type::scout
+            },
+        _ => tank_type::error
+    };
+
+    // Check if tank initialization has errors
+    let check_error = match tank_type_initialization {
+        tank_type::error => true,
+        _ => false
+    };
+
+    // Throw error if thank option is not acceptable
+    if check_error{
+        Error::new(ErrorKind::InvalidData, "The executable should be called with one of the 3 possible arguments: (T or t): normal tank, (H or h): Heavy tank, (S or s): Scout tank.");
+    }
+
+
+    // Optional debugging arguments after the tank type, for example: --debug-board
+    let options: RunOptions = get_run_options(&args);
+
+    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 5, tank_type_initialization);
+
+    let first_input = get_args(handle_server_output::get_input());
+
+    match first_input[0].as_str() {
+        "START" => {
+            player.set_initial_information(
+                first_input[ROBOT_COLOR_INDEX].clone(), 
+                first_input[EXPLORATION_TURNS_INDEX].parse().unwrap(), 
+                first_input[SIDE_LEN_INDEX].parse().unwrap());
+
+                // Open the team channel first, the robots of the team agree on their roles before sending IAM.
+                // There is no team channel when the robot runs solo, the robot then plays on its own with the role it was given.
+                // What the robot learns is published to the team, messages are dropped when there is no team channel.
+                let own_colour = player.get_colour().chars().next().unwrap_or('R');
+                let (team_listener, mut team_broadcaster) = comms::open_team_channel(own_colour, &options);
+                if team_listener.is_none() {
+                    eprintln!("No team channel, playing without teammates.");
+                }
+
+                let preferred_role = args.get(1).unwrap().chars().next().unwrap_or('T').to_ascii_uppercase();
+                let mut role = role_negotiation::negotiate_role(own_colour, preferred_role, team_listener.as_ref(), &mut team_broadcaster, &clock::SystemClock::new());
+                tank_check = role == 'T';
+                scout_check = role == 'S';
+                heavy_check = role == 'H';
+
+                println!("IAM {}", role);
+
+                let mut is_valid :bool = false;
+                
+                while !is_valid{
+                    let scond_input = get_args(handle_server_output::get_input());
+
+                    match scond_input[0].as_str(){
+                        "OK" => is_valid = true,
+                        _ => {
+                            if !tank_check{
+                                role = 'T';
+                                println!("IAM T");
+                                tank_check = true;
+                            }else if !scout_check{
+                                role = 'S';
+                                println!("IAM S");
+                                scout_check = true;
+                            }else if !heavy_check {
+                                role = 'H';
+                                println!("IAM H");
+                                heavy_check = true;
+                            }else{
+                                is_valid = true;
+                                println!("The tank options where not valid due time out or duplicated tank definition.");
+                                exit(1);
+                            }
+                        }
+                    };
+                }
+
+                // The server accepted the role, which may not be the one the player was started with.
+                if role_negotiation::role_tank_type(role) != player.get_tank_type() {
+                    let colour = player.get_colour();
+                    player = Player::initialize_player(colour.clone(), 100 as u32, 5, role_negotiation::role_tank_type(role));
+                    player.set_initial_information(
+                        colour,
+                        first_input[EXPLORATION_TURNS_INDEX].parse().unwrap(),
+                        first_input[SIDE_LEN_INDEX].parse().unwrap());
+                }
+
+                handle_server_output::handle_server_output(&mut player, &options, team_listener, team_broadcaster);
+        }
+        _ => {
+            // We should do nothing here because we handle server output inside (execute_robot_strategies)
+            println!("Something went wrong, server probably didn't send anything valid, or reading from the CLI is incorrect.");
+        }
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::CoordSystem::{board, board_operations, direction_vector, hex_distance, scan_area};
+use crate::action_manager::playerOutput;
+use crate::fire_guard::shot_path;
+use crate::move_manager::clear_shot;
+use crate::game::ENEMY_SHOT_RANGE;
+use crate::move_manager::{NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST, SOUTH_WEST};
+use crate::parser::get_args;
+use crate::player::{Player, PlayerState, tank_type, MAX_STEPS_PER_ROUND};
+use crate::robot_strategies::FindCornerStrategy;
+use crate::strategy::{Command, CommandResult, GameView, Strategy};
+use crate::threat_map::THREAT_MAX_AGE;
+
+/// Reward of a shot that hits a sampled enemy.
+const HIT_REWARD: f32 = 1.0;
+
+/// Reward of a scan that covers only unknown or old hexes, less for a scan of fresh hexes.
+const INFORMATION_REWARD: f32 = 0.5;
+
+/// Reward per hex the round takes the tank closer to the centre of the arena.
+const POSITION_REWARD: f32 = 0.1;
+
+/// Penalty per sampled enemy that can shoot the tank where it ends the round.
+const THREAT_PENALTY: f32 = 0.75;
+
+/**
+ * Settings of the planner.
+ *  - time_budget_ms: The planner stops searching after this long, it has to answer before the server's time per move runs out.
+ *  - max_iterations: The planner stops after this many simulated rounds, even with time left.
+ *  - exploration: The UCT exploration constant, higher tries more of the less promising commands.
+ *  - seed: Seed of the random generator, the same seed samples the same enemies and rollouts.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct MctsConfig {
+    pub time_budget_ms: u64,
+    pub max_iterations: u32,
+    pub exploration: f32,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            time_budget_ms: 100,
+            max_iterations: 20_000,
+            exploration: 1.4,
+            seed: 0,
+        }
+    }
+}
+
+/**
+ * Model of the rest of our round, the rules the planner simulates:
+ *  - Every scan, drive and shot uses a step and a point, within the budgets of the tank type (see Player). A turn is free
+ *    but two turns in a row are not allowed.
+ *  - A drive is refused onto walls, hexes outside the board, likely tanks of the board and the sampled enemies.
+ *  - A shot that ends on a sampled enemy hits it, shots don't go through walls.
+ *  - The enemies don't move during our round, they shoot at us after it.
+ * Unknown hexes are assumed free.
+ */
+#[derive(Clone)]
+struct SimTurn {
+    player: Player,
+    last_action: playerOutput,
+    enemies: Vec<(i32, i32, i32)>,
+    hits: u32,
+    information: f32,
+    revealed: HashSet<(i32, i32, i32)>,
+    ended: bool,
+}
+
+impl SimTurn {
+
+    fn start(view: &GameView, enemies: Vec<(i32, i32, i32)>) -> Self {
+        Self {
+            player: view.player.clone(),
+            last_action: view.last_action,
+            enemies: enemies,
+            hits: 0,
+            information: 0.0,
+            revealed: HashSet::new(),
+            ended: false,
+        }
+    }
+
+    /**
+     * Commands allowed from this state. Empty once the round is over.
+     */
+    fn legal_commands(&self, game_board: &board) -> Vec<Command> {
+        let player = &self.player;
+        let mut commands: Vec<Command> = Vec::new();
+        if self.ended || player.get_step_count() >= MAX_STEPS_PER_ROUND {
+            return commands;
+        }
+
+        let position = player.get_position();
+        if player.get_shoot_action_check() {
+            for enemy in self.enemies.iter().filter(|enemy| hex_distance(position, **enemy) <= ENEMY_SHOT_RANGE) {
+                if let Some(shot) = clear_shot(game_board, position, *enemy).map(Command::Shoot) {
+                    if !commands.contains(&shot) {
+                        commands.push(shot);
+                    }
+                }
+            }
+        }
+        // A second scan from the same pose shows nothing new.
+        if player.get_scan_actions_check() && !matches!(self.last_action, playerOutput::SCAN) {
+            commands.push(Command::Scan);
+        }
+        if player.get_drive_actions_check() && self.can_drive(game_board) {
+            commands.push(Command::Drive);
+        }
+        if !matches!(self.last_action, playerOutput::TURN) {
+            let facing = player.get_facing_direction();
+            for direction in [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST] {
+                if direction != facing.as_str() {
+                    commands.push(Command::Turn(direction.to_string()));
+                }
+            }
+        }
+        commands.push(Command::End);
+        return commands;
+    }
+
+    fn can_drive(&self, game_board: &board) -> bool {
+        let position = self.player.get_position();
+        let (dq, dr, ds) = direction_vector(self.player.get_facing_direction().as_str());
+        let (q, r, s) = (position.0 + dq, position.1 + dr, position.2 + ds);
+        return match game_board.get_tile(q, r, s) {
+            Some(tile) => tile.get_occupied_by() != 'W'
+                && game_board.tank_probability(q, r, s, self.player.get_rounds()) < 0.5
+                && !self.enemies.contains(&(q, r, s)),
+            None => false,
+        };
+    }
+
+    /**
+     * Play a command. The command is assumed legal.
+     */
+    fn apply(&mut self, command: &Command, game_board: &board) {
+        let round = self.player.get_rounds();
+        match command {
+            Command::Scan => {
+                self.use_step();
+                self.player.add_scan_action();
+                let position = self.player.get_position();
+                let facing = self.player.get_facing_direction();
+                let area = scan_area(position, facing.as_str(), self.player.get_tank_type() == tank_type::scout);
+                let size = area.len() as f32;
+                for (q, r, s) in area {
+                    let stale = game_board.get_tile(q, r, s).map_or(false, |tile| !tile.is_known() || tile.get_seen_round() < round);
+                    if stale && self.revealed.insert((q, r, s)) {
+                        self.information += 1.0 / size;
+                    }
+                }
+                self.last_action = playerOutput::SCAN;
+            },
+            Command::Drive => {
+                self.use_step();
+                self.player.add_drive_action();
+                self.player.drive_forward();
+                self.last_action = playerOutput::DRIVE;
+            },
+            Command::Turn(direction) => {
+                self.player.update_facing_direction(direction);
+                self.last_action = playerOutput::TURN;
+            },
+            Command::Shoot(shot) => {
+                self.use_step();
+                self.player.add_shoot_action();
+                let directions: Vec<String> = get_args(shot.clone()).into_iter().skip(1).collect();
+                let target = shot_path(self.player.get_position(), &directions).last().copied();
+                if target.map_or(false, |target| self.enemies.contains(&target)) {
+                    self.hits += 1;
+                }
+                self.last_action = playerOutput::SHOOT;
+            },
+            Command::Skip => {
+                self.use_step();
+                self.player.add_skip_action();
+                self.last_action = playerOutput::SKIP;
+            },
+            Command::End => {
+                self.ended = true;
+                self.last_action = playerOutput::END;
+            },
+        }
+    }
+
+    fn use_step(&mut self) {
+        self.player.add_step();
+        self.player.update_points_count(1);
+    }
+
+    /**
+     * Value of the round: the hits, what the scans revealed and the progress towards the centre, minus the enemies that
+     * can shoot the tank where it ends.
+     */
+    fn reward(&self, start: (i32, i32, i32), game_board: &board) -> f32 {
+        let position = self.player.get_position();
+        let closer = hex_distance(start, (0, 0, 0)) as f32 - hex_distance(position, (0, 0, 0)) as f32;
+        let exposed = self.enemies.iter()
+            .filter(|enemy| hex_distance(**enemy, position) <= ENEMY_SHOT_RANGE && clear_shot(game_board, **enemy, position).is_some())
+            .count() as f32;
+
+        return HIT_REWARD * self.hits as f32 + INFORMATION_REWARD * self.information + POSITION_REWARD * closer - THREAT_PENALTY * exposed;
+    }
+}
+
+/// Node of the search tree, reached from its parent by playing its command.
+struct Node {
+    command: Option<Command>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: u32,
+    total: f32,
+}
+
+/**
+ * Monte Carlo tree search over the commands of the rest of our round. Every iteration samples a position for each
+ * enemy seen in the last THREAT_MAX_AGE rounds from EnemyTracker::likely_positions, walks down the tree with UCT among
+ * the commands legal for that sample, adds one new command, plays random commands to the end of the round and backs
+ * the reward of the round up the tree.
+ */
+pub struct MctsPlanner {
+    config: MctsConfig,
+    rng: StdRng,
+    last_iterations: u32,
+}
+
+impl MctsPlanner {
+    pub fn new(config: MctsConfig) -> Self {
+        Self {
+            config: config,
+            rng: StdRng::seed_from_u64(config.seed),
+            last_iterations: 0,
+        }
+    }
+
+    /**
+     * Return the number of rounds simulated by the last plan.
+     */
+    pub fn get_last_iterations(&self) -> u32 {
+        return self.last_iterations;
+    }
+
+    /**
+     * Search the best command sequence for the rest of the round, within the time budget. The sequence follows the most
+     * visited command at each level of the tree, it is empty if the tank is not calibrated.
+     */
+    pub fn plan(&mut self, view: &GameView) -> Vec<Command> {
+        self.last_iterations = 0;
+        if !view.player.is_calibrated() {
+            return Vec::new();
+        }
+
+        let start = view.player.get_position();
+        let deadline = Instant::now() + Duration::from_millis(self.config.time_budget_ms);
+        let mut nodes: Vec<Node> = vec![Node { command: None, parent: None, children: Vec::new(), visits: 0, total: 0.0 }];
+
+        while self.last_iterations < self.config.max_iterations && Instant::now() < deadline {
+            let enemies = self.sample_enemies(view);
+            let mut sim = SimTurn::start(view, enemies);
+            let mut current = 0;
+
+            // Selection and expansion.
+            loop {
+                let legal = sim.legal_commands(view.board);
+                if legal.is_empty() {
+                    break;
+                }
+                let tried: Vec<&Command> = nodes[current].children.iter().filter_map(|child| nodes[*child].command.as_ref()).collect();
+                if let Some(command) = legal.iter().find(|command| !tried.contains(command)).cloned() {
+                    sim.apply(&command, view.board);
+                    nodes.push(Node { command: Some(command), parent: Some(current), children: Vec::new(), visits: 0, total: 0.0 });
+                    let child = nodes.len() - 1;
+                    nodes[current].children.push(child);
+                    current = child;
+                    break;
+                }
+
+                let parent_visits = nodes[current].visits.max(1) as f32;
+                let exploration = self.config.exploration;
+                let best = nodes[current].children.iter().copied()
+                    .filter(|child| nodes[*child].command.as_ref().map_or(false, |command| legal.contains(command)))
+                    .max_by(|a, b| {
+                        let uct = |node: &Node| node.total / node.visits as f32 + exploration * (parent_visits.ln() / node.visits as f32).sqrt();
+                        uct(&nodes[*a]).partial_cmp(&uct(&nodes[*b])).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                match best {
+                    Some(child) => {
+                        sim.apply(nodes[child].command.as_ref().unwrap(), view.board);
+                        current = child;
+                    },
+                    None => break,
+                }
+            }
+
+            // Rollout.
+            loop {
+                let legal = sim.legal_commands(view.board);
+                if legal.is_empty() {
+                    break;
+                }
+                let command = legal[self.rng.gen_range(0..legal.len())].clone();
+                sim.apply(&command, view.board);
+            }
+
+            // Backpropagation.
+            let reward = sim.reward(start, view.board);
+            let mut node = Some(current);
+            while let Some(index) = node {
+                nodes[index].visits += 1;
+                nodes[index].total += reward;
+                node = nodes[index].parent;
+            }
+            self.last_iterations += 1;
+        }
+
+        let mut sequence: Vec<Command> = Vec::new();
+        let mut current = 0;
+        while let Some(child) = nodes[current].children.iter().copied().max_by_key(|child| nodes[*child].visits) {
+            let command = nodes[child].command.clone().unwrap();
+            let end = command == Command::End;
+            sequence.push(command);
+            if end {
+                break;
+            }
+            current = child;
+        }
+        return sequence;
+    }
+
+    /// One position for each recently seen enemy, drawn from the likely positions of the tracker.
+    fn sample_enemies(&mut self, view: &GameView) -> Vec<(i32, i32, i32)> {
+        let round = view.player.get_rounds();
+        let mut enemies: Vec<(i32, i32, i32)> = Vec::new();
+
+        for colour in view.tracker.get_colours() {
+            let recent = view.tracker.last_sighting(colour).map_or(false, |sighting| round.saturating_sub(sighting.get_seen_at_round()) <= THREAT_MAX_AGE);
+            if !recent {
+                continue;
+            }
+            let positions = view.tracker.likely_positions(colour, round, view.board);
+            let mut draw: f32 = self.rng.gen();
+            for (position, probability) in positions.iter() {
+                draw -= probability;
+                if draw <= 0.0 {
+                    enemies.push(*position);
+                    break;
+                }
+            }
+            // Rounding left a bit of probability, it goes to the least likely position.
+            if draw > 0.0 {
+                if let Some((position, _)) = positions.last() {
+                    enemies.push(*position);
+                }
+            }
+        }
+        return enemies;
+    }
+}
+
+/**
+ * Plays the MCTS plan of the round. The round is planned once, with the time budget of the planner, and the commands
+ * of the plan are played one after the other, free turns included. The rest of the round is only planned again when
+ * the server refuses a command of the plan, or when the plan is over. Until the tank is calibrated its position is
+ * unknown and the planner can't simulate, the find corner strategy plays.
+ */
+pub struct MctsStrategy {
+    planner: MctsPlanner,
+    plan: Vec<Command>, // Commands of the plan not played yet, the next one first.
+    find_corner: FindCornerStrategy,
+    finding_corner: bool,
+}
+
+impl MctsStrategy {
+    pub fn new(config: MctsConfig) -> Self {
+        Self {
+            planner: MctsPlanner::new(config),
+            plan: Vec::new(),
+            find_corner: FindCornerStrategy::new(),
+            finding_corner: false,
+        }
+    }
+}
+
+impl Strategy for MctsStrategy {
+
+    fn name(&self) -> &str {
+        return "mcts";
+    }
+
+    fn on_turn_start(&mut self, view: &GameView) {
+        self.plan.clear();
+        self.find_corner.on_turn_start(view);
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        self.finding_corner = !view.player.is_calibrated();
+        if self.finding_corner {
+            return self.find_corner.next_action(view);
+        }
+
+        if self.plan.is_empty() {
+            self.plan = self.planner.plan(view);
+        }
+        if self.plan.is_empty() {
+            return Command::End;
+        }
+        return self.plan.remove(0);
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, player: &mut Player) {
+        if self.finding_corner {
+            self.find_corner.on_result(command, result, player);
+        } else if let CommandResult::Refused = result {
+            // The round went differently than simulated, the rest of the plan no longer holds.
+            self.plan.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::mcts::*;
+    use crate::game::{EnemiesPos, EnemyTracker, TeamColor};
+    use crate::scan_parser::{GetScanReturn, ScanResponse};
+
+    #[test]
+    fn test_plan_shoots_then_gets_out_of_range() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -2, 2, TeamColor::B, player.get_rounds(), None));
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &tracker, last_action: playerOutput::NONE, action_counter: 0, target: None };
+        let mut planner = MctsPlanner::new(MctsConfig { time_budget_ms: 10_000, max_iterations: 3000, ..MctsConfig::default() });
+        let plan = planner.plan(&view);
+
+        assert!(plan.contains(&Command::Shoot("SHOOT N-N".to_string())), "The enemy two hexes north should be shot: {:?}", plan);
+        let mut sim = SimTurn::start(&view, vec![(0, -2, 2)]);
+        for command in plan.iter() {
+            sim.apply(command, &game_board);
+        }
+        assert!(hex_distance(sim.player.get_position(), (0, -2, 2)) > ENEMY_SHOT_RANGE, "The plan should end out of the enemy's range: {:?}", plan);
+        assert!(sim.player.get_step_count() <= MAX_STEPS_PER_ROUND, "The plan should fit in the steps of a round.");
+    }
+
+    #[test]
+    fn test_strategy_plays_the_plan_of_the_round() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -2, 2, TeamColor::B, player.get_rounds(), None));
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &tracker, last_action: playerOutput::NONE, action_counter: 0, target: None };
+
+        let config = MctsConfig { time_budget_ms: 10_000, max_iterations: 500, ..MctsConfig::default() };
+        let plan = MctsPlanner::new(config).plan(&view);
+        let mut strategy = MctsStrategy::new(config);
+
+        let mut played: Vec<Command> = Vec::new();
+        for _ in 0..plan.len() {
+            let command = strategy.next_action(&view);
+            strategy.on_result(&command, &CommandResult::Accepted, &mut player.clone());
+            played.push(command);
+        }
+        assert_eq!(plan, played, "The commands of the round should come from a single plan.");
+
+        strategy.next_action(&view);
+        strategy.on_result(&Command::Scan, &CommandResult::Refused, &mut player.clone());
+        assert!(strategy.plan.is_empty(), "A refused command should drop the rest of the plan.");
+    }
+
+    #[test]
+    fn test_plan_stays_within_time_budget() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::scout);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+
+        let mut planner = MctsPlanner::new(MctsConfig { time_budget_ms: 20, max_iterations: u32::MAX, ..MctsConfig::default() });
+        let started = Instant::now();
+        let plan = planner.plan(&view);
+
+        assert!(started.elapsed() < Duration::from_millis(500), "The planner should stop at its time budget.");
+        assert!(planner.get_last_iterations() > 0, "The planner should simulate at least one round.");
+        assert!(!plan.is_empty(), "A calibrated tank always has a plan.");
+    }
+}
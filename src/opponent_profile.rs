@@ -0,0 +1,409 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::CoordSystem::hex_distance;
+use crate::game::{EnemyTracker, SightingSource, TeamColor, ENEMY_SHOT_RANGE};
+use crate::player::tank_type;
+
+/// Version written on every profile file. Increase it when the format changes so old files are rejected with a clear error.
+pub const PROFILE_VERSION: u32 = 1;
+
+/**
+ * How an enemy colour plays, counted over the rounds it was seen. The counters are summed over the games, the
+ * behaviour is read from them with the methods.
+ *  - games: Number of games the enemy was seen in.
+ *  - rounds_seen: Number of rounds the enemy was seen in.
+ *  - hexes_moved, rounds_tracked: Hexes between two sightings, and the rounds between them, for the movement speed.
+ *  - max_moves_seen: Most hexes the enemy was seen to move in one round.
+ *  - movement_samples, approaches: Pairs of sightings, and the ones where the enemy got closer to us.
+ *  - rounds_in_range: Rounds the enemy was seen within shot range of us.
+ *  - hits_on_us: Hits we took that were put on the enemy (see EnemyTracker::record_damage).
+ *  - max_hits_in_a_round: Most hits the enemy made on us in one round.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpponentProfile {
+    pub games: u32,
+    pub rounds_seen: u32,
+    pub hexes_moved: u32,
+    pub rounds_tracked: u32,
+    pub max_moves_seen: u32,
+    pub movement_samples: u32,
+    pub approaches: u32,
+    pub rounds_in_range: u32,
+    pub hits_on_us: u32,
+    pub max_hits_in_a_round: u32,
+}
+
+impl OpponentProfile {
+
+    /**
+     * Hexes moved per round, 0 if the enemy was never seen twice.
+     */
+    pub fn speed(&self) -> f32 {
+        return if self.rounds_tracked == 0 { 0.0 } else { self.hexes_moved as f32 / self.rounds_tracked as f32 };
+    }
+
+    /**
+     * Share of the moves that brought the enemy closer to us, between 0 and 1.
+     */
+    pub fn aggressiveness(&self) -> f32 {
+        return if self.movement_samples == 0 { 0.0 } else { self.approaches as f32 / self.movement_samples as f32 };
+    }
+
+    /**
+     * Hits on us per round the enemy was within shot range of us, between 0 and 1. Only the shots that hit us are known.
+     */
+    pub fn shot_rate(&self) -> f32 {
+        let rounds = self.rounds_in_range.max(self.hits_on_us);
+        return if rounds == 0 { 0.0 } else { self.hits_on_us as f32 / rounds as f32 };
+    }
+
+    /**
+     * The tank type the actions of the enemy give away: only the scout moves more than 2 hexes in a round, only the heavy
+     * shoots twice in a round. None while it did neither.
+     */
+    pub fn inferred_tank_type(&self) -> Option<tank_type> {
+        if self.max_moves_seen > 2 {
+            return Some(tank_type::scout);
+        }
+        if self.max_hits_in_a_round > 1 {
+            return Some(tank_type::heavy);
+        }
+        return None;
+    }
+
+    /**
+     * Add the counters of another profile, for example the one of the game that just ended.
+     */
+    pub fn merge(&mut self, other: &OpponentProfile) {
+        self.games += other.games;
+        self.rounds_seen += other.rounds_seen;
+        self.hexes_moved += other.hexes_moved;
+        self.rounds_tracked += other.rounds_tracked;
+        self.max_moves_seen = self.max_moves_seen.max(other.max_moves_seen);
+        self.movement_samples += other.movement_samples;
+        self.approaches += other.approaches;
+        self.rounds_in_range += other.rounds_in_range;
+        self.hits_on_us += other.hits_on_us;
+        self.max_hits_in_a_round = self.max_hits_in_a_round.max(other.max_hits_in_a_round);
+    }
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::Io(error) => write!(f, "could not access the profile file: {}", error),
+            ProfileError::Json(error) => write!(f, "the profiles are not valid: {}", error),
+            ProfileError::UnsupportedVersion(version) => write!(f, "profile version {} is not supported, expected version {}", version, PROFILE_VERSION),
+        }
+    }
+}
+
+impl From<io::Error> for ProfileError {
+    fn from(error: io::Error) -> Self {
+        ProfileError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ProfileError {
+    fn from(error: serde_json::Error) -> Self {
+        ProfileError::Json(error)
+    }
+}
+
+/**
+ * Profiles of every enemy colour we played against, kept in a json file between games.
+ *  - version: The file format version, see PROFILE_VERSION.
+ *  - profiles: The profile of each colour.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileBook {
+    pub version: u32,
+    pub profiles: BTreeMap<TeamColor, OpponentProfile>,
+}
+
+impl ProfileBook {
+
+    /**
+     * Create a book with no profile.
+     */
+    pub fn new() -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /**
+     * Return the profile of a colour, None if we never played against it.
+     */
+    pub fn get_profile(&self, team: TeamColor) -> Option<&OpponentProfile> {
+        return self.profiles.get(&team);
+    }
+
+    /**
+     * Add the profiles of a game to the book.
+     */
+    pub fn merge_game(&mut self, game: &BTreeMap<TeamColor, OpponentProfile>) {
+        for (team, profile) in game {
+            self.profiles.entry(*team).or_default().merge(profile);
+        }
+    }
+
+    /**
+     * Serialize the book to pretty printed json, so it can be read and edited by hand.
+     */
+    pub fn to_json(&self) -> Result<String, ProfileError> {
+        return Ok(serde_json::to_string_pretty(self)?);
+    }
+
+    /**
+     * Load a book from json. Books written with another format version are rejected.
+     */
+    pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|version| version.as_u64()).unwrap_or(0) as u32;
+
+        if version != PROFILE_VERSION {
+            return Err(ProfileError::UnsupportedVersion(version));
+        }
+        return Ok(serde_json::from_value(value)?);
+    }
+
+    /**
+     * Write the book to a file.
+     */
+    pub fn save(&self, path: &str) -> Result<(), ProfileError> {
+        fs::write(path, self.to_json()?)?;
+        return Ok(());
+    }
+
+    /**
+     * Load a book from a file. A file that doesn't exist yet is an empty book, it is the first game.
+     */
+    pub fn load(path: &str) -> Result<Self, ProfileError> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(error) => Err(error.into()),
+        };
+    }
+}
+
+/**
+ * Builds the profiles of the enemies during a game and keeps them in the book of the earlier games.
+ *  - path: The file the book is loaded from and saved to.
+ *  - book: The profiles of the earlier games.
+ *  - positions: Our position at each round, to tell if an enemy comes at us.
+ */
+pub struct OpponentProfiler {
+    path: String,
+    book: ProfileBook,
+    positions: BTreeMap<u32, (i32, i32, i32)>,
+}
+
+impl OpponentProfiler {
+
+    /**
+     * Load the book of the earlier games from the file. A book that can't be read is replaced by an empty one, it will
+     * be overwritten at the end of the game.
+     */
+    pub fn load(path: &str) -> Self {
+        let book = ProfileBook::load(path).unwrap_or_else(|error| {
+            eprintln!("Could not load the opponent profiles {}: {}", path, error);
+            ProfileBook::new()
+        });
+        return Self::with_book(path, book);
+    }
+
+    /**
+     * Use the given book, saved to the given file.
+     */
+    pub fn with_book(path: &str, book: ProfileBook) -> Self {
+        Self {
+            path: path.to_string(),
+            book: book,
+            positions: BTreeMap::new(),
+        }
+    }
+
+    pub fn get_book(&self) -> &ProfileBook {
+        return &self.book;
+    }
+
+    /**
+     * Record our position at the start of a round.
+     */
+    pub fn record_position(&mut self, round: u32, position: (i32, i32, i32)) {
+        self.positions.insert(round, position);
+    }
+
+    /// Our last known position at the given round.
+    fn position_at(&self, round: u32) -> Option<(i32, i32, i32)> {
+        return self.positions.range(..=round).next_back().map(|(_, position)| *position);
+    }
+
+    /**
+     * Profile of every enemy in the tracker, from this game only.
+     */
+    pub fn game_profiles(&self, tracker: &EnemyTracker) -> BTreeMap<TeamColor, OpponentProfile> {
+        let mut profiles: BTreeMap<TeamColor, OpponentProfile> = BTreeMap::new();
+
+        for team in tracker.get_colours() {
+            let mut profile = OpponentProfile { games: 1, ..OpponentProfile::default() };
+
+            // One sighting per round, the first one. Inferred sightings are only guesses from a hit, they would make up
+            // moves the enemy never made.
+            let mut sightings: Vec<(u32, (i32, i32, i32))> = Vec::new();
+            for sighting in tracker.get_sightings(team).iter().filter(|sighting| sighting.get_source() == SightingSource::Seen) {
+                if sightings.last().map_or(true, |(round, _)| *round != sighting.get_seen_at_round()) {
+                    sightings.push((sighting.get_seen_at_round(), sighting.get_position()));
+                }
+            }
+            profile.rounds_seen = sightings.len() as u32;
+
+            for (round, position) in sightings.iter() {
+                if self.position_at(*round).map_or(false, |us| hex_distance(us, *position) <= ENEMY_SHOT_RANGE) {
+                    profile.rounds_in_range += 1;
+                }
+            }
+
+            for pair in sightings.windows(2) {
+                let ((from_round, from), (to_round, to)) = (pair[0], pair[1]);
+                let moved = hex_distance(from, to);
+                profile.hexes_moved += moved;
+                profile.rounds_tracked += to_round - from_round;
+                if to_round - from_round == 1 {
+                    profile.max_moves_seen = profile.max_moves_seen.max(moved);
+                }
+
+                profile.movement_samples += 1;
+                if let (Some(us_before), Some(us_after)) = (self.position_at(from_round), self.position_at(to_round)) {
+                    if hex_distance(us_after, to) < hex_distance(us_before, from) {
+                        profile.approaches += 1;
+                    }
+                }
+            }
+
+            let mut hits_by_round: BTreeMap<u32, u32> = BTreeMap::new();
+            for report in tracker.get_damage_reports().iter().filter(|report| report.get_shooter() == Some(team)) {
+                *hits_by_round.entry(report.get_round()).or_insert(0) += 1;
+            }
+            profile.hits_on_us = hits_by_round.values().sum();
+            profile.max_hits_in_a_round = hits_by_round.values().copied().max().unwrap_or(0);
+
+            profiles.insert(team, profile);
+        }
+        return profiles;
+    }
+
+    /**
+     * Profile of an enemy: the earlier games and this one together.
+     */
+    pub fn profile(&self, team: TeamColor, tracker: &EnemyTracker) -> OpponentProfile {
+        let mut profile = self.book.get_profile(team).copied().unwrap_or_default();
+        if let Some(game) = self.game_profiles(tracker).get(&team) {
+            profile.merge(game);
+        }
+        return profile;
+    }
+
+    /**
+     * Tell the tracker the moves per round of the enemies whose tank type is known, from this game or the earlier ones,
+     * so its predictions fit the enemy.
+     */
+    pub fn adapt(&self, tracker: &mut EnemyTracker) {
+        let teams: Vec<TeamColor> = tracker.get_colours().into_iter().chain(self.book.profiles.keys().copied()).collect();
+        for team in teams {
+            match self.profile(team, tracker).inferred_tank_type() {
+                Some(tank_type::scout) => tracker.set_max_moves(team, 4),
+                Some(_) => tracker.set_max_moves(team, 2),
+                None => (),
+            }
+        }
+    }
+
+    /**
+     * Add this game to the book and write it to the file, called once at the end of the game.
+     */
+    pub fn save(&mut self, tracker: &EnemyTracker) -> Result<(), ProfileError> {
+        let game = self.game_profiles(tracker);
+        self.book.merge_game(&game);
+        return self.book.save(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::opponent_profile::*;
+    use crate::CoordSystem::{board, board_operations};
+    use crate::damage::DamageReport;
+    use crate::game::EnemiesPos;
+
+    #[test]
+    fn test_profile_of_a_game() {
+        let game_board: board = board::initialize(5);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -4, 4, TeamColor::V, 2, None));
+        tracker.record(EnemiesPos::new(0, -1, 1, TeamColor::V, 3, Some('G')));
+        tracker.record_damage(DamageReport::new((0, 1, -1), vec!["S".to_string(), "S".to_string()], 3, &game_board), &game_board);
+
+        let mut profiler = OpponentProfiler::with_book("unused.json", ProfileBook::new());
+        profiler.record_position(2, (0, 1, -1));
+        let profile = profiler.game_profiles(&tracker)[&TeamColor::V];
+
+        assert_eq!(2, profile.rounds_seen, "V was seen on two rounds.");
+        assert_eq!(3.0, profile.speed(), "V moved 3 hexes in one round.");
+        assert_eq!(Some(tank_type::scout), profile.inferred_tank_type(), "Only a scout moves 3 hexes in a round.");
+        assert_eq!(1.0, profile.aggressiveness(), "V came at us.");
+        assert_eq!(1, profile.hits_on_us, "The hit from the north was V.");
+        assert_eq!(1.0, profile.shot_rate(), "V hit us the only round it was in range.");
+
+        profiler.adapt(&mut tracker);
+        assert_eq!(4, tracker.get_max_moves(TeamColor::V), "The tracker predicts V with the moves of a scout.");
+    }
+
+    #[test]
+    fn test_inferred_sightings_are_not_movement() {
+        let game_board: board = board::initialize(5);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -4, 4, TeamColor::V, 2, None));
+        let report = DamageReport::new((0, 1, -1), vec!["S".to_string(), "S".to_string()], 3, &game_board);
+        assert_eq!(Some(TeamColor::V), tracker.record_damage(report, &game_board), "Only V could have shot us.");
+
+        let profiler = OpponentProfiler::with_book("unused.json", ProfileBook::new());
+        let profile = profiler.game_profiles(&tracker)[&TeamColor::V];
+
+        assert_eq!(1, profile.rounds_seen, "V was only seen on round 2, round 3 is inferred from the hit.");
+        assert_eq!(0, profile.movement_samples, "The inferred sighting should not count as a move of V.");
+        assert_eq!(None, profile.inferred_tank_type(), "Nothing is known of the speed of V.");
+        assert_eq!(1, profile.hits_on_us, "The hit is still put on V.");
+    }
+
+    #[test]
+    fn test_book_round_trip() {
+        let mut game: BTreeMap<TeamColor, OpponentProfile> = BTreeMap::new();
+        game.insert(TeamColor::O, OpponentProfile { games: 1, rounds_seen: 4, max_hits_in_a_round: 2, ..OpponentProfile::default() });
+        let mut book = ProfileBook::new();
+        book.merge_game(&game);
+        book.merge_game(&game);
+
+        let loaded = ProfileBook::from_json(&book.to_json().unwrap()).unwrap();
+        assert_eq!(book, loaded, "The profiles should survive the file.");
+        assert_eq!(2, loaded.get_profile(TeamColor::O).unwrap().games, "O was met in two games.");
+        assert_eq!(Some(tank_type::heavy), loaded.get_profile(TeamColor::O).unwrap().inferred_tank_type(), "Two hits in a round is a heavy.");
+        assert!(matches!(ProfileBook::from_json("{\"version\": 999}"), Err(ProfileError::UnsupportedVersion(999))), "Profiles from another version should be rejected.");
+    }
+}
@@ -1,36 +1,200 @@
-use regex::Regex;
-
-// Split command on whitespace and return a vector of arguments
-pub fn get_args(line: String) -> Vec<String> {
-    let mut arguments: Vec<String> = Vec::new();
-
-    for arg in Regex::new("[/\\s-]+").unwrap().split(line.as_str()){
-            arguments.push(arg.to_string());
-    }
-    return arguments;
-}
-
-#[cfg(test)]
-mod unit_test{
-    use super::get_args;
-
-    #[test]
-    fn get_first_arg(){
-        let args: Vec<String> = get_args("START R 0".to_string());
-        assert_eq!("START", args.get(0).unwrap(), "The first agrument is START, but something else was caught.");
-    }
-
-    #[test]
-    fn get_second_arg(){
-        let args: Vec<String> = get_args("START       R 0".to_string());
-        assert_eq!("R", args.get(1).unwrap(), "The second agrument is R, but something else was caught.");
-    }
-
-    #[test]
-    fn get_third_arg(){
-        let args: Vec<String> = get_args("START R        0".to_string());
-        assert_eq!("0", args.get(2).unwrap(), "The third agrument is 0, but something else was caught.");
-    }
-
-    
-}
\ No newline at end of file
+use regex::Regex;
+
+use crate::strategy::DEFAULT_STRATEGY;
+
+/// File descriptor the team listens to when --team is given.
+pub const DEFAULT_TEAM_IN_FD: i32 = 3;
+
+/// File descriptor the team outputs to when --team is given.
+pub const DEFAULT_TEAM_OUT_FD: i32 = 4;
+
+// Split command on whitespace and return a vector of arguments
+pub fn get_args(line: String) -> Vec<String> {
+    let mut arguments: Vec<String> = Vec::new();
+
+    for arg in Regex::new("[/\\s-]+").unwrap().split(line.as_str()){
+            arguments.push(arg.to_string());
+    }
+    return arguments;
+}
+
+/**
+ * Options supplied on the command line after the tank type, for example: T --debug-board
+ *
+ *  - debug_board: Print what the robot knows about the board to stderr every round. Stdout is reserved to talk with the server.
+ *  - debug_board_file: File the same picture of the board is appended to every round, given with: --debug-board-file <file>
+ *  - svg_dir: Directory where an SVG picture of the board is written every round, given with: --svg-dir <dir>
+ *  - team_in_fd: File descriptor the messages of the teammates are read from, given with: --team-in <fd>
+ *  - team_out_fd: File descriptor the messages to the teammates are written to, given with: --team-out <fd>
+ *    The robot plays without teammates unless the descriptors are given, or --team is given to use the descriptors
+ *    DEFAULT_TEAM_IN_FD and DEFAULT_TEAM_OUT_FD.
+ *  - strategy: Name of the strategy to play, given with: --strategy <name>
+ *  - strategy_config: Json file of a strategy machine to play instead, given with: --strategy-config <file>
+ *  - profiles: Json file the profiles of the enemies are read from at the start and written to at the end, given with: --profiles <file>
+ *  - mcts_budget_ms: Time the MCTS planner may search each time it plans, in milliseconds, given with: --mcts-budget <ms>
+ *    The default budget of MctsConfig is used if it is not given.
+ */
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    pub debug_board: bool,
+    pub debug_board_file: Option<String>,
+    pub svg_dir: Option<String>,
+    pub team_in_fd: Option<i32>,
+    pub team_out_fd: Option<i32>,
+    pub strategy: String,
+    pub strategy_config: Option<String>,
+    pub profiles: Option<String>,
+    pub mcts_budget_ms: Option<u64>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            debug_board: false,
+            debug_board_file: None,
+            svg_dir: None,
+            team_in_fd: None,
+            team_out_fd: None,
+            strategy: DEFAULT_STRATEGY.to_string(),
+            strategy_config: None,
+            profiles: None,
+            mcts_budget_ms: None,
+        }
+    }
+}
+
+/**
+ * Parse the command line arguments into run options. Unknown arguments are ignored.
+ *
+ *  - args: All the arguments supplied to the program, including the program name and the tank type.
+ */
+pub fn get_run_options(args: &[String]) -> RunOptions {
+    let mut options: RunOptions = RunOptions::default();
+
+    let mut arguments = args.iter().skip(2);
+
+    while let Some(arg) = arguments.next() {
+        match arg.as_str() {
+            "--debug-board" => options.debug_board = true,
+            "--debug-board-file" => options.debug_board_file = arguments.next().cloned(),
+            "--svg-dir" => options.svg_dir = arguments.next().cloned(),
+            "--team" => {
+                options.team_in_fd = options.team_in_fd.or(Some(DEFAULT_TEAM_IN_FD));
+                options.team_out_fd = options.team_out_fd.or(Some(DEFAULT_TEAM_OUT_FD));
+            },
+            "--team-in" => options.team_in_fd = parse_fd(arguments.next()),
+            "--team-out" => options.team_out_fd = parse_fd(arguments.next()),
+            "--strategy" => options.strategy = arguments.next().cloned().unwrap_or(options.strategy),
+            "--strategy-config" => options.strategy_config = arguments.next().cloned(),
+            "--profiles" => options.profiles = arguments.next().cloned(),
+            "--mcts-budget" => options.mcts_budget_ms = parse_ms(arguments.next()),
+            _ => eprintln!("Ignoring unknown argument {}", arg),
+        }
+    }
+    return options;
+}
+
+/**
+ * Parse a file descriptor argument, None if it is missing or not a number.
+ */
+fn parse_fd(arg: Option<&String>) -> Option<i32> {
+    return match arg.map(|fd| fd.parse::<i32>()) {
+        Some(Ok(fd)) => Some(fd),
+        _ => {
+            eprintln!("Invalid file descriptor argument, ignoring it");
+            None
+        }
+    };
+}
+
+/**
+ * Parse a duration argument in milliseconds, None if it is missing or not a number.
+ */
+fn parse_ms(arg: Option<&String>) -> Option<u64> {
+    return match arg.map(|ms| ms.parse::<u64>()) {
+        Some(Ok(ms)) => Some(ms),
+        _ => {
+            eprintln!("Invalid duration argument, ignoring it");
+            None
+        }
+    };
+}
+
+#[cfg(test)]
+mod unit_test{
+    use super::{get_args, get_run_options, DEFAULT_TEAM_IN_FD, DEFAULT_TEAM_OUT_FD};
+
+    #[test]
+    fn get_first_arg(){
+        let args: Vec<String> = get_args("START R 0".to_string());
+        assert_eq!("START", args.get(0).unwrap(), "The first agrument is START, but something else was caught.");
+    }
+
+    #[test]
+    fn get_second_arg(){
+        let args: Vec<String> = get_args("START       R 0".to_string());
+        assert_eq!("R", args.get(1).unwrap(), "The second agrument is R, but something else was caught.");
+    }
+
+    #[test]
+    fn get_third_arg(){
+        let args: Vec<String> = get_args("START R        0".to_string());
+        assert_eq!("0", args.get(2).unwrap(), "The third agrument is 0, but something else was caught.");
+    }
+
+    #[test]
+    fn get_debug_board_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--debug-board".to_string()];
+        assert!(get_run_options(&args).debug_board, "The --debug-board argument was supplied but the option is off.");
+    }
+
+    #[test]
+    fn get_debug_board_file_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--debug-board-file".to_string(), "board.txt".to_string()];
+        let options = get_run_options(&args);
+        assert_eq!(Some("board.txt".to_string()), options.debug_board_file, "The file after --debug-board-file should receive the board.");
+        assert!(!options.debug_board, "Writing the board to a file should not print it to stderr.");
+    }
+
+    #[test]
+    fn get_svg_dir_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--svg-dir".to_string(), "games/1".to_string()];
+        assert_eq!(Some("games/1".to_string()), get_run_options(&args).svg_dir, "The directory after --svg-dir should be the svg output directory.");
+    }
+
+    #[test]
+    fn get_team_fd_options(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--team-in".to_string(), "7".to_string()];
+        let options = get_run_options(&args);
+        assert_eq!(Some(7), options.team_in_fd, "The team input should be read from the descriptor after --team-in.");
+        assert_eq!(None, options.team_out_fd, "The team output should only be used when asked for.");
+
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--team".to_string()];
+        let options = get_run_options(&args);
+        assert_eq!((Some(DEFAULT_TEAM_IN_FD), Some(DEFAULT_TEAM_OUT_FD)), (options.team_in_fd, options.team_out_fd), "--team should use the default descriptors.");
+
+        let options = get_run_options(&["team-delta".to_string(), "T".to_string()]);
+        assert_eq!((None, None), (options.team_in_fd, options.team_out_fd), "Without --team the robot plays solo.");
+    }
+
+    #[test]
+    fn get_strategy_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--strategy".to_string(), "find_corner".to_string()];
+        assert_eq!("find_corner", get_run_options(&args).strategy, "The strategy after --strategy should be played.");
+    }
+
+    #[test]
+    fn get_profiles_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--profiles".to_string(), "league.json".to_string()];
+        assert_eq!(Some("league.json".to_string()), get_run_options(&args).profiles, "The file after --profiles should hold the opponent profiles.");
+    }
+
+    #[test]
+    fn get_mcts_budget_option(){
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--mcts-budget".to_string(), "40".to_string()];
+        assert_eq!(Some(40), get_run_options(&args).mcts_budget_ms, "The planner should search for the milliseconds after --mcts-budget.");
+
+        let args: Vec<String> = vec!["team-delta".to_string(), "T".to_string(), "--mcts-budget".to_string(), "soon".to_string()];
+        assert_eq!(None, get_run_options(&args).mcts_budget_ms, "A budget that is not a number should be ignored.");
+    }
+}
@@ -1,475 +1,1290 @@
-use crate::move_manager::{
-    construct_drive_moves, construct_shoot_move, NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST,
-    SOUTH_WEST,
-};
-use crate::parser::get_args;
-use crate::player::{Player, PlayerState, tank_type};
-use crate::scan_parser::{self, GetScanReturn, ScanResponse};
-use crate::strategy_controller::{strategiesType};
-use crate::action_manager::{playerOutput, action_manager, manage_player_action};
-
-// Chooses decisions on now the control the tank, based on 'phases'.
-// Starts with phase 1, continues to phase 2 etc.
-// Phase n does not necessarily have to be followed by phase n+1
-// but all phases must flow into a different phase.
-
-/// Turn type. Either Left or Right.
-/// Left -> counter-clockwise
-/// Right -> clockwise
-/// 
-
-enum Turn {
-    Left,
-    Right,
-}
-
-static DIRECTIONS: &'static [&str] =
-    &[NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST];
-
-
-
-
-/// Helper function.
-/// Turns the robot left or right from current position
-fn turn_LR(player: &mut Player, turn_direction: Turn, action_manager :&mut action_manager) {
-    let facing = player.get_facing_direction();
-    let index = DIRECTIONS.iter().position(|&dir| dir == facing).unwrap();
-    // Gets the corresponding index for facing direction
-
-    let direction: &str;
-
-    if matches!(turn_direction, Turn::Right) {
-        direction = DIRECTIONS[(index + 1) % 6];
-    } else {
-        direction = DIRECTIONS[(index + 6 - 1) % 6];
-    }
-    action_manager.turn(direction, player);
-}
-
-/// Helper function.
-/// Turns the robot left or right from current position N times but in a single turn move
-fn turn_LR_n(player: &mut Player, turn_direction: Turn, n: usize) {
-    let facing = player.get_facing_direction();
-    let index = DIRECTIONS.iter().position(|&dir| dir == facing).unwrap();
-    // Gets the corresponding index for facing direction
-
-    let direction: &str;
-
-    if matches!(turn_direction, Turn::Right) {
-        direction = DIRECTIONS[(index + n) % 6];
-    } else {
-        direction = DIRECTIONS[(index + n - 1) % 6];
-    }
-}
-
-/// Helper function.
-/// Turns the tank to a random direction
-fn turn_rand(player: &mut Player, action_manager :&mut action_manager) {
-    action_manager.turn(DIRECTIONS[(rand::random::<u32>() % 5) as usize], player);
-}
-
-/// Helper function.
-/// Turns the tank opposite current direction i.e. player.facing_direction
-fn turn_opposite(player: &mut Player, action_manager :&mut action_manager) {
-    let direction = match player.get_facing_direction().as_str() // Translate.
-    {
-        NORTH => SOUTH,
-        SOUTH => NORTH,
-        NORTH_EAST => SOUTH_WEST,
-        SOUTH_WEST => NORTH_EAST,
-        NORTH_WEST => SOUTH_EAST,
-        SOUTH_EAST => NORTH_WEST,
-        _ => ""
-    };
-    action_manager.turn(direction, player);
-}
-
-/// phase 1. Starts by finding a nearby corner. Then turns inward
-/// to face the center of the board. Flows into phase 2.
-/// Expects external call to phase 2.
-pub fn orient_to_near_corner(player: &mut Player, scan :&mut ScanResponse, action_manager :&mut action_manager) {
- //   V    R
-    //     /\
-    //  B |  | O
-    //     \/
-    //   G    Y
-    // let raw_scan = String::new();
-
-    ////////////////////////////////////////////////////////////////////////
-    /// If on round one and in the first actions of player, turn tank
-
-    if player.get_rounds() == 1 && action_manager.get_action_counter() == 0{
-        match player.get_colour().as_str() {
-            "R" => action_manager.turn(SOUTH_EAST, player),
-            "O" => action_manager.turn(SOUTH, player),
-            "Y" => action_manager.turn(SOUTH_WEST, player),
-            "G" => action_manager.turn(NORTH_WEST, player),
-            "B" => action_manager.turn(NORTH, player),
-            "V" => action_manager.turn(NORTH_EAST, player),
-            _ => (),
-        }
-    } else {
-
-        ////////////////////////////////////////////////////////////////////////
-        /// If the forst action of the round scan
-        if player.get_scan_action_count() == 0{
-            action_manager.scan(player);
-        } else{
-
-            ////////////////////////////////////////////////////////////////////////
-            /// If last action was a scan, check if ther is a wall right in front of the tank, if not then drive.
-            match action_manager.get_last_action() {
-                playerOutput::SCAN => {
-                    if scan.get_walls().contains(&1) {
-                        player.corner_found();                      
-
-                    } else {
-                        if player.get_drive_actions_check(){
-                            // ------ BEFORE MOVING, DO WE HAVE ENOUGH POINTS TO MOVE? ---//
-                            action_manager.drive(player);
-                        }else{
-                            action_manager.end();
-                        }
-                    }
-                }
-                ,
-                playerOutput::DRIVE =>{
-                    ////////////////////////////////////////////////////////////////////////
-                    /// If last action was a drive, tank is on position 1, so we check if there is a wall in fornt of it, if not the 
-                    if !scan.get_walls().contains(&5){
-                        if player.get_drive_actions_check(){
-                            // ------ BEFORE MOVING, DO WE HAVE ENOUGH POINTS TO MOVE? ---//
-                            action_manager.drive(player);
-                        }else{
-                            action_manager.end();
-                        }
-                    }else {
-                        player.corner_found();
-                    }
-                },
-                _=> ()
-            }
-        
-        }
-
-    }
-
-    if player.get_corner_status(){
-
-        //////////////////////////////////////////////////////////////////////////////
-        // CORNER WAS FOUND THE BOARD SHOULD BE CALIBRADED
-        // 
-        //  -- Board or player should have a flag taht when the board is calibraded the strategy controller will move to next strategy.
-        //
-        //  the q_coord, r_coord, and s_coord are the real coord
-        //////////////////////////////////////////////////////////////////////////////
-        match player.get_colour().as_str() {
-            "R" => {
-                    let q_coord :i32 = (player.get_side_len() -1) as i32;
-                    let r_coord :i32 = -((player.get_side_len() -1) as i32);
-                    let s_coord :i32 = 0;
-                },
-            "O" => {
-                    let q_coord :i32 = (player.get_side_len() -1) as i32;
-                    let r_coord :i32 = 0;
-                    let s_coord :i32 = -((player.get_side_len() -1) as i32);
-                },
-            "Y" => {
-                    let q_coord :i32 = 0;
-                    let r_coord :i32 = (player.get_side_len() -1) as i32;
-                    let s_coord :i32 = -((player.get_side_len() -1) as i32);
-                },
-            "G" => {
-                    let q_coord :i32 = -((player.get_side_len() -1) as i32);
-                    let r_coord :i32 = (player.get_side_len() -1) as i32;
-                    let s_coord :i32 = 0;
-                },
-            "B" => {
-                    let q_coord :i32 = -((player.get_side_len() -1) as i32);
-                    let r_coord :i32 = 0;
-                    let s_coord :i32 = (player.get_side_len() -1) as i32;
-                },
-            "V" => {
-                    let q_coord :i32 = 0;
-                    let r_coord :i32 = -((player.get_side_len() -1) as i32);
-                    let s_coord :i32 = (player.get_side_len() -1) as i32;
-                },
-            _ => (),
-        }
-
-        let max_steps = 3;
-        if player.get_step_count() < max_steps {
-            action_manager.skip(player);
-        }
-    }
-    
-
-}
-
-/**
- * Function that should be used by normal tank or scout, if its heavy then just skip untill the end
- */
-pub fn explorer(player: &mut Player, scan :&mut ScanResponse, action_manager :&mut action_manager) {
-
-    match player.get_tank_type() {
-        tank_type::heavy => action_manager.end(),
-        _ => {
-            // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn'  so actions_member does keep track of that as it keeps track of player last action.
-            match player.get_step_count(){
-                1 => {
-                    // TODO: What to do on the first action the player performs.
-                },
-                2 => {
-                    // TODO: What to do on the second action the player performs.
-                },
-                3 => {
-                    // TODO: What to do on the third action the player performs.
-                },
-                _ => ()
-            };
-        }
-    };
-}
-
-/**
- * Function to traverse the mmap after the initial exploration round is done.
- */
-pub fn traverse (player: &mut Player, scan :&mut ScanResponse, action_manager :&mut action_manager) {
-
-
-    match player.get_tank_type() {
-        tank_type::heavy => {
-            // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn'  so actions_member does keep track of that as it keeps track of player last action.
-            match player.get_step_count(){
-                1 => {
-                    // TODO: What to do on the first action the player performs.
-                },
-                2 => {
-                    // TODO: What to do on the second action the player performs.
-                },
-                3 => {
-                    // TODO: What to do on the third action the player performs.
-                },
-                _ => ()
-            };
-        },
-        tank_type::scout => {
-            // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn'  so actions_member does keep track of that as it keeps track of player last action.
-            match player.get_step_count(){
-                1 => {
-                    // TODO: What to do on the first action the player performs.
-                },
-                2 => {
-                    // TODO: What to do on the second action the player performs.
-                },
-                3 => {
-                    // TODO: What to do on the third action the player performs.
-                },
-                _ => ()
-            };
-
-        },
-        tank_type::tank => {
-            // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn'  so actions_member does keep track of that as it keeps track of player last action.
-            match player.get_step_count(){
-                1 => {
-                    // TODO: What to do on the first action the player performs.
-                },
-                2 => {
-                    // TODO: What to do on the second action the player performs.
-                },
-                3 => {
-                    // TODO: What to do on the third action the player performs.
-                },
-                _ => ()
-            };
-        },
-        _ =>()
-    };
-}
-
-// Exploration
-/*
-   Find corner
-    |
-    v
-   looker -> stalker -> tank_movement
-
-*/
-
-// / phase 2. Goes toward the center twice and then drives around in a
-// / smaller hexagon while looking for enemies. Flows into phase 3 (internal call)
-// / Also flows into phase 4 if function ends (expected to be called externally).
-// fn looker(player: &mut Player) {
-//     // Drive inside loop
-//     // Scan left and right
-//     // drive forward
-//     // drive twice since can scan 3 tiles away
-//     player.drive();
-//     player.end();
-//     player.drive();
-//     player.end();
-//     // turn counter clockwise
-//     turn_LR(player, Turn::Left);
-//     let mut response: scan_parser::ScanResponse;
-//     let mut drive_count = 0;
-//     loop {
-//         // 2 inside the hex so 2+1
-//         if drive_count >= player.get_side_len() - 3 {
-//             drive_count = 0;
-//             turn_LR(player, Turn::Right);
-//         }
-//         turn_LR(player, Turn::Left);
-
-//         response = player.scan();
-//         player.end();
-
-//         if response.get_enemy_detected() {
-//             stalk(player);
-//         };
-
-//         turn_LR_n(player, Turn::Right, 2);
-//         response = player.scan();
-//         player.end();
-
-//         if response.get_enemy_detected() {
-//             stalk(player);
-//         };
-
-//         turn_LR(player, Turn::Left);
-//         player.drive();
-//         player.end();
-//         drive_count += 1;
-
-//         // if player rounds played >= exploration rounds, break
-
-//         if player.get_rounds() >= player.get_exploration_rounds() {
-//             break;
-//         }
-//     }
-// }
-
-// /// Helper function. Assumes enemy is directly in front of robot.
-// /// Goes left and around the enemy, expects no obstacles adjacent to enemy
-// fn flank(player: &mut Player) -> bool {
-//     turn_LR(player, Turn::Left);
-//     player.drive();
-//     player.end();
-//     turn_LR(player, Turn::Right);
-//     player.drive();
-//     player.end();
-//     turn_LR_n(player, Turn::Right, 2);
-//     return true;
-// }
-
-// /// phase 3. Stalks an enemy player. Closes the distance and attempts to circle around.
-// /// ONLY FOR EXPLORATIOIN ROUNDS!
-// /// Does not attack, only observes.
-// /// returns false if target is lost, returns true if target is in vision and exploration rounds are over
-// fn stalk(player: &mut Player) -> bool {
-//     let mut player_lost_for_rounds = 0;
-//     let mut response: ScanResponse;
-
-//     loop {
-//         // if exploration rounds are over, go to the tank phase
-//         if player.get_rounds() >= player.get_exploration_rounds() {
-//             return true;
-//         }
-
-//         response = player.scan();
-//         player.end();
-
-//         if response.get_enemy_detected() {
-//             if response.get_enemies().contains(&(1 as usize)) {
-//                 // upper: Driving twice
-//                 if response.get_walls().contains(&0) && // check if we can get around player
-//                     response.get_walls().contains(&4)
-//                 {
-//                     player.skip();
-//                 } else {
-//                     flank(player); // drives twice
-//                 }
-//             }
-//             // Face towards enemy, then drive.
-//             else if !response.get_enemies().contains(&8)
-//                 && !response.get_enemies().contains(&9)
-//                 && !response.get_enemies().contains(&10)
-//             {
-//                 let mut move_dir = construct_shoot_move(
-//                     player.get_facing_direction(),
-//                     response.get_enemies()[0] as usize,
-//                 );
-//                 move_dir = get_args(move_dir).get(1).unwrap().to_string();
-//                 player.turn(&*move_dir);
-//                 player.drive();
-//                 player.end();
-//             } else {
-//                 // just drive forward hopefully in enemy direction
-//                 player.drive();
-//                 player.end();
-//             }
-//         } else {
-//             player_lost_for_rounds += 1;
-//             player.drive();
-//             player.end();
-//         }
-
-//         if player_lost_for_rounds > 2 {
-//             return false;
-//         }
-//     }
-// }
-
-// /// phase 4. Chosen for its exceptional survivability and adaptability.
-// /// A very general set of moves that work after exploration rounds are over.
-// /// Directly influenced by the professor's tank algorithm.
-// /// Main difference is that it scans first instead of randomly turns first.
-// /// This is to take advantage of previous functions that set up robot for the kill
-// fn tank_movement(player: &mut Player) {
-//     // eprintln!("Reached tank!");
-//     let mut response: ScanResponse;
-
-//     loop {
-//         // start with scan
-//         // We scan in a random direction and shoot at the closest thing to us,
-//         response = player.scan();
-//         player.end();
-
-//         // We saw an enemy and they're in range, so we shoot!
-//         if response.get_enemy_detected() && response.get_enemies()[0] <= 7 {
-//             let shoot_command =
-//                 construct_shoot_move(player.get_facing_direction(), response.get_enemies()[0]);
-//             player.shoot(&*shoot_command);
-//         }
-
-//         let mut valid_moves = construct_drive_moves(player.get_facing_direction(), &mut response);
-
-//         if valid_moves.len() > 0 {
-//             let num_moves = valid_moves.len() as usize;
-//             player.turn(&valid_moves[rand::random::<usize>() % num_moves][6..]);
-//             player.drive();
-//             player.end();
-//         } else {
-//             turn_opposite(player);
-//             response = player.scan();
-//             player.end();
-
-//             valid_moves = construct_drive_moves(player.get_facing_direction(), &mut response);
-
-//             let num_moves = valid_moves.len() as usize;
-//             player.turn(&valid_moves[rand::random::<usize>() % num_moves][6..]);
-//             player.drive();
-//             player.end();
-//         }
-
-//         // in the end turn to a random direction
-//         turn_rand(player);
-//     }
-// }
-
-// /// The public facing function that starts executing the robot strategies
-// pub fn execute_robot_strategies(player: &mut Player) {
-//     orient_to_near_corner(player);
-//     looker(player);
-//     tank_movement(player);
-// }
+use std::cmp::Reverse;
+
+use crate::move_manager::{
+    construct_drive_moves, construct_shoot_move, NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST,
+    SOUTH_WEST,
+};
+use crate::parser::get_args;
+use crate::player::{Player, PlayerState, tank_type, MAX_STEPS_PER_ROUND};
+use crate::scan_parser::{self, GetScanReturn, ScanResponse};
+use crate::action_manager::{playerOutput, action_manager, manage_player_action};
+use crate::behaviour_tree::BehaviourTree;
+use crate::CoordSystem::{board_operations, direction_vector, hex_distance, neighbours, scan_area};
+use crate::move_manager::clear_shot;
+use crate::threat_map::ThreatMap;
+use crate::strategy::{Command, CommandResult, GameView, Strategy, StrategyRegistry};
+
+// Chooses decisions on now the control the tank, based on 'phases'.
+// Starts with phase 1, continues to phase 2 etc.
+// Phase n does not necessarily have to be followed by phase n+1
+// but all phases must flow into a different phase.
+
+/// Turn type. Either Left or Right.
+/// Left -> counter-clockwise
+/// Right -> clockwise
+/// 
+
+enum Turn {
+    Left,
+    Right,
+}
+
+static DIRECTIONS: &'static [&str] =
+    &[NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST];
+
+
+
+
+/// Helper function.
+/// Turns the robot left or right from current position
+fn turn_LR(player: &mut Player, turn_direction: Turn, action_manager :&mut action_manager) {
+    let facing = player.get_facing_direction();
+    let index = DIRECTIONS.iter().position(|&dir| dir == facing).unwrap();
+    // Gets the corresponding index for facing direction
+
+    let direction: &str;
+
+    if matches!(turn_direction, Turn::Right) {
+        direction = DIRECTIONS[(index + 1) % 6];
+    } else {
+        direction = DIRECTIONS[(index + 6 - 1) % 6];
+    }
+    action_manager.turn(direction, player);
+}
+
+/// Helper function.
+/// Turns the robot left or right from current position N times but in a single turn move
+fn turn_LR_n(player: &mut Player, turn_direction: Turn, n: usize) {
+    let facing = player.get_facing_direction();
+    let index = DIRECTIONS.iter().position(|&dir| dir == facing).unwrap();
+    // Gets the corresponding index for facing direction
+
+    let direction: &str;
+
+    if matches!(turn_direction, Turn::Right) {
+        direction = DIRECTIONS[(index + n) % 6];
+    } else {
+        direction = DIRECTIONS[(index + n - 1) % 6];
+    }
+}
+
+/// Helper function.
+/// Turns the tank to a random direction
+fn turn_rand(player: &mut Player, action_manager :&mut action_manager) {
+    action_manager.turn(DIRECTIONS[(rand::random::<u32>() % 5) as usize], player);
+}
+
+/// Helper function.
+/// Turns the tank opposite current direction i.e. player.facing_direction
+fn turn_opposite(player: &mut Player, action_manager :&mut action_manager) {
+    let direction = match player.get_facing_direction().as_str() // Translate.
+    {
+        NORTH => SOUTH,
+        SOUTH => NORTH,
+        NORTH_EAST => SOUTH_WEST,
+        SOUTH_WEST => NORTH_EAST,
+        NORTH_WEST => SOUTH_EAST,
+        SOUTH_EAST => NORTH_WEST,
+        _ => ""
+    };
+    action_manager.turn(direction, player);
+}
+
+/// Register the strategies of this module, see StrategyRegistry.
+pub fn register_strategies(registry: &mut StrategyRegistry) {
+    registry.register("find_corner", || Box::new(FindCornerStrategy::new()));
+    registry.register("explorer", || Box::new(ExplorerStrategy::new()));
+    registry.register("traverse", || Box::new(TraverseStrategy::new()));
+    registry.register("turret", || Box::new(TurretStrategy::new()));
+    registry.register("spotter", || Box::new(SpotterStrategy::new()));
+}
+
+/// phase 1. Starts by finding a nearby corner. Then turns inward
+/// to face the center of the board. Flows into phase 2.
+/// The default strategy machine moves on to phase 2 once the corner is found.
+pub struct FindCornerStrategy {
+    last_scan: Option<ScanResponse>,
+}
+
+impl FindCornerStrategy {
+    pub fn new() -> Self {
+        Self { last_scan: None }
+    }
+
+    /// True if the last scan has a wall on the given scan index.
+    fn wall_at(&self, index: usize) -> bool {
+        return self.last_scan.as_ref().map_or(false, |scan| scan.get_walls().contains(&index));
+    }
+}
+
+impl Strategy for FindCornerStrategy {
+
+    fn name(&self) -> &str {
+        return "find_corner";
+    }
+
+    fn on_turn_start(&mut self, _view: &GameView) {}
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+     //   V    R
+        //     /\
+        //  B |  | O
+        //     \/
+        //   G    Y
+        let player = view.player;
+
+        // If on round one and in the first actions of player, turn tank
+        if player.get_rounds() == 1 && view.action_counter == 0 {
+            let direction = match player.get_colour().as_str() {
+                "R" => SOUTH_EAST,
+                "O" => SOUTH,
+                "Y" => SOUTH_WEST,
+                "G" => NORTH_WEST,
+                "B" => NORTH,
+                "V" => NORTH_EAST,
+                _ => return Command::Skip,
+            };
+            return Command::Turn(direction.to_string());
+        }
+
+        // Corner was found on the last result, nothing left to do this round.
+        if player.get_corner_status() {
+            return if player.get_step_count() < MAX_STEPS_PER_ROUND { Command::Skip } else { Command::End };
+        }
+
+        // If the first action of the round, scan
+        if player.get_scan_action_count() == 0 {
+            return Command::Scan;
+        }
+
+        // After a scan the tank checks the tile in front of it (index 1), after a drive the tile after that one (index 5).
+        // The corner is found in on_result, when that tile is a wall.
+        match view.last_action {
+            playerOutput::SCAN | playerOutput::DRIVE => {
+                // ------ BEFORE MOVING, DO WE HAVE ENOUGH POINTS TO MOVE? ---//
+                if player.get_drive_actions_check() {
+                    Command::Drive
+                } else {
+                    Command::End
+                }
+            },
+            _ => Command::End
+        }
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, player: &mut Player) {
+        match (command, result) {
+            (Command::Scan, CommandResult::Scanned(scan)) => {
+                self.last_scan = Some(scan.clone());
+                if self.wall_at(1) {
+                    corner_found(player);
+                }
+            },
+            (Command::Drive, CommandResult::Accepted) => {
+                if self.wall_at(5) {
+                    corner_found(player);
+                }
+            },
+            _ => ()
+        }
+    }
+}
+
+/**
+ * The tank reached its corner: the player model is calibrated with the real coordinates of the corner of its colour.
+ */
+fn corner_found(player: &mut Player) {
+    player.corner_found();
+
+    let max_coord = (player.get_side_len() - 1) as i32;
+    let corner = match player.get_colour().as_str() {
+        "R" => Some((max_coord, -max_coord, 0)),
+        "O" => Some((max_coord, 0, -max_coord)),
+        "Y" => Some((0, max_coord, -max_coord)),
+        "G" => Some((-max_coord, max_coord, 0)),
+        "B" => Some((-max_coord, 0, max_coord)),
+        "V" => Some((0, -max_coord, max_coord)),
+        _ => None,
+    };
+
+    if let Some((q_coord, r_coord, s_coord)) = corner {
+        player.calibrate(q_coord, r_coord, s_coord);
+    }
+}
+
+/**
+ * Frontier based exploration, used during the exploration rounds once the tank knows where it is.
+ * Before each drive the tank turns to the direction where a scan uncovers the most unseen hexes and scans. Then it
+ * drives along the shortest path to the nearest frontier between the known and the unknown parts of the board, or to
+ * the centre if nothing is known yet. The points of the round are respected through the player budget checks.
+ */
+pub struct ExplorerStrategy {
+    scanned: bool,
+}
+
+impl ExplorerStrategy {
+    pub fn new() -> Self {
+        Self { scanned: false }
+    }
+}
+
+impl Strategy for ExplorerStrategy {
+
+    fn name(&self) -> &str {
+        return "explorer";
+    }
+
+    fn on_turn_start(&mut self, _view: &GameView) {
+        self.scanned = false;
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        let player = view.player;
+        if !player.is_calibrated() || player.get_rounds() >= player.get_exploration_rounds() {
+            return Command::End;
+        }
+
+        // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn', so only one
+        // turn is made between two other actions.
+        let can_turn = !matches!(view.last_action, playerOutput::TURN);
+        let position = player.get_position();
+        let facing = player.get_facing_direction();
+
+        if !self.scanned && player.get_scan_actions_check() {
+            let direction = best_scan_direction(view);
+            if direction != facing && can_turn {
+                return Command::Turn(direction);
+            }
+            return Command::Scan;
+        }
+
+        if !player.get_drive_actions_check() {
+            return Command::End;
+        }
+        let next = match nearest_frontier_path(view).and_then(|path| path.first().copied()) {
+            Some(next) => next,
+            None => return Command::End,
+        };
+        if view.board.tank_probability(next.0, next.1, next.2, player.get_rounds()) >= 0.5 {
+            return Command::End;
+        }
+
+        let direction = DIRECTIONS.iter()
+            .find(|direction| {
+                let (dq, dr, ds) = direction_vector(direction);
+                (position.0 + dq, position.1 + dr, position.2 + ds) == next
+            })
+            .map(|direction| direction.to_string())
+            .unwrap_or(facing.clone());
+
+        if direction == facing {
+            return Command::Drive;
+        }
+        return if can_turn { Command::Turn(direction) } else { Command::End };
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, _player: &mut Player) {
+        match (command, result) {
+            (Command::Scan, CommandResult::Scanned(_)) => self.scanned = true,
+            (Command::Drive, CommandResult::Accepted) => self.scanned = false,
+            _ => ()
+        }
+    }
+
+    fn planned_path(&self, view: &GameView) -> Vec<(i32, i32, i32)> {
+        if !view.player.is_calibrated() {
+            return Vec::new();
+        }
+        return nearest_frontier_path(view).unwrap_or_default();
+    }
+}
+
+/**
+ * Direction where a scan from the current position covers the most tiles that are unknown or were not seen this round.
+ * On a tie the tank keeps its facing, so it doesn't turn for nothing.
+ */
+fn best_scan_direction(view: &GameView) -> String {
+    let player = view.player;
+    let unseen = |direction: &str| -> usize {
+        scan_area(player.get_position(), direction, player.get_tank_type() == tank_type::scout).into_iter().filter(|(q, r, s)| match view.board.get_tile(*q, *r, *s) {
+            Some(tile) => !tile.is_known() || tile.get_seen_round() < player.get_rounds(),
+            None => false,
+        }).count()
+    };
+
+    let facing = player.get_facing_direction();
+    let mut best = (unseen(facing.as_str()), facing.clone());
+    for direction in DIRECTIONS.iter() {
+        let count = unseen(direction);
+        if count > best.0 {
+            best = (count, direction.to_string());
+        }
+    }
+    return best.1;
+}
+
+/**
+ * Shortest path to the nearest frontier tile other than the one the tank is on. Before anything is known the tank
+ * heads to the centre of the board.
+ */
+fn nearest_frontier_path(view: &GameView) -> Option<Vec<(i32, i32, i32)>> {
+    let position = view.player.get_position();
+    let mut targets: Vec<(i32, i32, i32)> = view.board.frontier().into_iter().filter(|target| *target != position).collect();
+    if targets.is_empty() && position != (0, 0, 0) {
+        targets.push((0, 0, 0));
+    }
+
+    return targets.into_iter()
+        .filter_map(|target| view.board.find_path(position, target))
+        .min_by_key(|path| path.len());
+}
+
+/**
+ * How a tank type fights.
+ *  - preferred_distance: The tank drives towards its target until it is this many hexes away. Shots reach 2 hexes.
+ *  - max_target_age: Enemies not seen for more rounds than this are not hunted anymore.
+ *  - retreat_rounds: Number of rounds the tank moves away from the enemies after taking damage, 0 to hold its ground.
+ *  - confirm_before_shooting: Scan the target again before shooting if it was not seen this round.
+ *  - patrol_shoots_after_scan_only: While patrolling, only shoot an enemy of the scan sent just before, not of any scan
+ *    of the round.
+ *  - patrol_turn: Turn action of the patrol when the way is blocked, turn_left or turn_right.
+ *  - patrol_turns: Number of times per round the patrol turns when the way is blocked, 0 to never turn.
+ *  - patrol_turns_after_scan_only: Only turn right after a scan, so the new direction is scanned before driving.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct CombatProfile {
+    pub preferred_distance: u32,
+    pub max_target_age: u32,
+    pub retreat_rounds: u32,
+    pub confirm_before_shooting: bool,
+    pub patrol_shoots_after_scan_only: bool,
+    pub patrol_turn: &'static str,
+    pub patrol_turns: u32,
+    pub patrol_turns_after_scan_only: bool,
+}
+
+impl CombatProfile {
+
+    /**
+     * The profile of each tank type: the heavy has the most shots so it closes in and shoots at old sightings too, the
+     * scout is the most fragile so it backs off the longest. The scout sees the furthest, so it keeps scanning and
+     * moving while patrolling and only shoots what is right in front of its last scan.
+     */
+    pub fn for_tank_type(tank_type: tank_type) -> Self {
+        return match tank_type {
+            tank_type::heavy => Self {
+                preferred_distance: 1, max_target_age: 3, retreat_rounds: 0, confirm_before_shooting: false,
+                patrol_shoots_after_scan_only: false, patrol_turn: "turn_right", patrol_turns: 0, patrol_turns_after_scan_only: false,
+            },
+            tank_type::scout => Self {
+                preferred_distance: 2, max_target_age: 2, retreat_rounds: 2, confirm_before_shooting: true,
+                patrol_shoots_after_scan_only: true, patrol_turn: "turn_left", patrol_turns: 2, patrol_turns_after_scan_only: false,
+            },
+            _ => Self {
+                preferred_distance: 2, max_target_age: 2, retreat_rounds: 1, confirm_before_shooting: true,
+                patrol_shoots_after_scan_only: false, patrol_turn: "turn_right", patrol_turns: 1, patrol_turns_after_scan_only: true,
+            },
+        };
+    }
+
+    /**
+     * Behaviour tree the tank patrols with when it has no enemy to hunt: shoot an enemy of the last scan, otherwise
+     * scan, drive forward when the way is clear and turn when it is blocked, as the profile says.
+     */
+    pub fn patrol_tree(&self) -> String {
+        let shoot_condition = if self.patrol_shoots_after_scan_only { "last_action_scan" } else { "scanned_this_round" };
+        let mut tree = format!("
+selector
+  sequence
+    {}
+    enemy_in_scan
+    can_shoot
+    shoot_enemy
+  sequence
+    can_scan
+    not
+      last_action_scan
+    scan
+  sequence
+    path_clear
+    can_drive
+    drive
+", shoot_condition);
+
+        if self.patrol_turns > 0 && self.patrol_turns_after_scan_only {
+            tree += &format!("  sequence\n    last_action_scan\n    limit {}\n      {}\n", self.patrol_turns, self.patrol_turn);
+        } else if self.patrol_turns > 0 {
+            tree += &format!("  limit {}\n    {}\n", self.patrol_turns, self.patrol_turn);
+        }
+        tree += "  end\n";
+        return tree;
+    }
+}
+
+/**
+ * Strategy to traverse the map after the initial exploration round is done.
+ *  - Hunt: Drive towards the last known position of the team target, or of the nearest enemy on the board.
+ *  - Engage: Once the target is within reach, scan it if needed and shoot along a path that is not blocked by a wall.
+ *  - Disengage: After taking damage, move away from the known enemies for a few rounds (see CombatProfile).
+ * With no enemy to hunt, each tank type plays its own behaviour tree to patrol.
+ */
+pub struct TraverseStrategy {
+    heavy: BehaviourTree,
+    scout: BehaviourTree,
+    tank: BehaviourTree,
+    last_health: Option<u8>,
+    retreat_until: u32,
+    scanned_this_round: bool,
+}
+
+impl TraverseStrategy {
+    pub fn new() -> Self {
+        Self {
+            heavy: patrol_tree(tank_type::heavy),
+            scout: patrol_tree(tank_type::scout),
+            tank: patrol_tree(tank_type::tank),
+            last_health: None,
+            retreat_until: 0,
+            scanned_this_round: false,
+        }
+    }
+
+    /// Start a retreat when the health went down since the last time it was checked.
+    fn check_damage(&mut self, player: &Player, profile: &CombatProfile) {
+        let health = player.get_health();
+        if let Some(last_health) = self.last_health {
+            if health < last_health && profile.retreat_rounds > 0 {
+                self.retreat_until = player.get_rounds() + profile.retreat_rounds - 1;
+            }
+        }
+        self.last_health = Some(health);
+    }
+
+    fn patrol(&mut self, view: &GameView) -> Command {
+        // OBS: There are actions that don't count as player actions [don't consume a step], like 'turn'  so actions_member does keep track of that as it keeps track of player last action.
+        let tree = match view.player.get_tank_type() {
+            tank_type::heavy => &mut self.heavy,
+            tank_type::scout => &mut self.scout,
+            _ => &mut self.tank,
+        };
+        return tree.tick(view).unwrap_or(Command::End);
+    }
+}
+
+impl Strategy for TraverseStrategy {
+
+    fn name(&self) -> &str {
+        return "traverse";
+    }
+
+    fn on_turn_start(&mut self, view: &GameView) {
+        self.heavy.start_round();
+        self.scout.start_round();
+        self.tank.start_round();
+        self.scanned_this_round = false;
+        self.check_damage(view.player, &CombatProfile::for_tank_type(view.player.get_tank_type()));
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        let player = view.player;
+        let profile = CombatProfile::for_tank_type(player.get_tank_type());
+        self.check_damage(player, &profile);
+
+        if !player.is_calibrated() {
+            return self.patrol(view);
+        }
+
+        let position = player.get_position();
+        let target = combat_target(view, &profile);
+        let retreating = player.get_rounds() <= self.retreat_until;
+
+        // Engage: a target within reach is shot, even while retreating.
+        if let Some((target_position, seen_round)) = target {
+            if hex_distance(position, target_position) <= 2 && player.get_shoot_action_check() {
+                let confirmed = seen_round == player.get_rounds() || !profile.confirm_before_shooting;
+                if !confirmed && !self.scanned_this_round && player.get_scan_actions_check() {
+                    return face_or(view, towards(position, target_position), Command::Scan);
+                }
+                // A scan this round that didn't see the target again means it moved, the shot would be wasted.
+                if let Some(shot) = clear_shot(view.board, position, target_position).filter(|_| confirmed) {
+                    return Command::Shoot(shot);
+                }
+            }
+        }
+
+        // Disengage: drive to the neighbour furthest from the known enemies.
+        if retreating {
+            return match retreat_step(view, &profile) {
+                Some(next) if player.get_drive_actions_check() => face_or(view, towards(position, next), Command::Drive),
+                _ => Command::End,
+            };
+        }
+
+        // Hunt: close in on the target, then look at it.
+        if let Some((target_position, _)) = target {
+            if hex_distance(position, target_position) > profile.preferred_distance && player.get_drive_actions_check() {
+                let next = view.board.find_path(position, target_position).and_then(|path| path.first().copied());
+                if let Some(next) = next.filter(|next| *next != target_position && is_free(view, *next)) {
+                    return face_or(view, towards(position, next), Command::Drive);
+                }
+            }
+            if !self.scanned_this_round && player.get_scan_actions_check() {
+                return face_or(view, towards(position, target_position), Command::Scan);
+            }
+            return Command::End;
+        }
+
+        return self.patrol(view);
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, _player: &mut Player) {
+        if let (Command::Scan, CommandResult::Scanned(_)) = (command, result) {
+            self.scanned_this_round = true;
+        }
+    }
+
+    fn planned_path(&self, view: &GameView) -> Vec<(i32, i32, i32)> {
+        if !view.player.is_calibrated() {
+            return Vec::new();
+        }
+        let profile = CombatProfile::for_tank_type(view.player.get_tank_type());
+        return combat_target(view, &profile)
+            .and_then(|(target_position, _)| view.board.find_path(view.player.get_position(), target_position))
+            .unwrap_or_default();
+    }
+}
+
+/**
+ * Patrol behaviour tree of a tank type, see CombatProfile::patrol_tree.
+ */
+fn patrol_tree(tank_type: tank_type) -> BehaviourTree {
+    return BehaviourTree::parse(&CombatProfile::for_tank_type(tank_type).patrol_tree()).expect("the patrol trees are valid");
+}
+
+/**
+ * Position and sighting round of the enemy to fight: the team target if it is recent enough, otherwise the nearest enemy
+ * seen on the board within the max_target_age of the profile. Our own tank and our teammates are not enemies.
+ */
+fn combat_target(view: &GameView, profile: &CombatProfile) -> Option<((i32, i32, i32), u32)> {
+    let player = view.player;
+    let round = player.get_rounds();
+
+    if let Some(target) = view.target.as_ref() {
+        if round.saturating_sub(target.seen_round) <= profile.max_target_age {
+            return Some((target.position, target.seen_round));
+        }
+    }
+
+    return view.board.recent_enemies(player.get_colour().as_str(), round, profile.max_target_age).into_iter()
+        .min_by_key(|tile| hex_distance(player.get_position(), tile.get_coords()))
+        .map(|tile| (tile.get_coords(), tile.get_seen_round()));
+}
+
+/**
+ * Neighbour of the tank, free to drive to, that the fewest tracked enemies can shoot at this round, and then the
+ * furthest from the enemy seen on the board.
+ */
+fn retreat_step(view: &GameView, profile: &CombatProfile) -> Option<(i32, i32, i32)> {
+    let position = view.player.get_position();
+    let threat = combat_target(view, profile).map(|(enemy, _)| enemy);
+
+    return neighbours(position).into_iter()
+        .filter(|next| is_free(view, *next))
+        .max_by_key(|next| {
+            let reaching = view.tracker.enemies_reaching(*next, view.player.get_rounds(), profile.max_target_age, view.board).len();
+            (Reverse(reaching), threat.map_or(0, |enemy| hex_distance(*next, enemy)))
+        });
+}
+
+/// True if the tank can drive onto the tile: inside the board, not a wall and not likely to hold a tank.
+fn is_free(view: &GameView, position: (i32, i32, i32)) -> bool {
+    return match view.board.get_tile(position.0, position.1, position.2) {
+        Some(tile) => tile.get_occupied_by() != 'W' && view.board.tank_probability(position.0, position.1, position.2, view.player.get_rounds()) < 0.5,
+        None => false,
+    };
+}
+
+/**
+ * Direction of the first step from one position towards another. For a position that is not on a straight line, the
+ * direction whose step gets the closest.
+ */
+fn towards(from: (i32, i32, i32), to: (i32, i32, i32)) -> String {
+    return DIRECTIONS.iter()
+        .min_by_key(|direction| {
+            let (dq, dr, ds) = direction_vector(direction);
+            hex_distance((from.0 + dq, from.1 + dr, from.2 + ds), to)
+        })
+        .map(|direction| direction.to_string())
+        .unwrap_or(NORTH.to_string());
+}
+
+/**
+ * Turn to the direction first if the tank is not facing it, then play the command. Only one turn is made between two
+ * other actions, if the tank just turned the round ends instead.
+ */
+fn face_or(view: &GameView, direction: String, command: Command) -> Command {
+    if view.player.get_facing_direction() == direction {
+        return command;
+    }
+    return if matches!(view.last_action, playerOutput::TURN) { Command::End } else { Command::Turn(direction) };
+}
+
+/// A defensible hex is worth this much per side covered by a wall or the edge of the board.
+const POST_COVER_WEIGHT: i32 = 2;
+
+/// Number of hexes of the sight line towards the centre that count for a defensible hex.
+const POST_SIGHT_RANGE: u32 = 5;
+
+/// A defensible hex loses this much per enemy that could shoot it next round.
+const POST_THREAT_WEIGHT: i32 = 3;
+
+/**
+ * Strategy of the heavy tank: it has 2 shots but only 1 scan per round, so instead of exploring it holds a chokepoint.
+ * It picks a defensible hex on the board model, with walls on several sides and a long sight line towards the centre,
+ * drives there, then turns to a different open approach every round and scans it. Enemies within reach are shot, twice
+ * if they are still there.
+ */
+pub struct TurretStrategy {
+    post: Option<(i32, i32, i32)>,
+    watch: usize,
+    scanned_this_round: bool,
+}
+
+impl TurretStrategy {
+    pub fn new() -> Self {
+        Self {
+            post: None,
+            watch: 0,
+            scanned_this_round: false,
+        }
+    }
+
+    /**
+     * Return the hex the tank holds, if it chose one.
+     */
+    pub fn get_post(&self) -> Option<(i32, i32, i32)> {
+        return self.post;
+    }
+}
+
+impl Strategy for TurretStrategy {
+
+    fn name(&self) -> &str {
+        return "turret";
+    }
+
+    fn on_turn_start(&mut self, _view: &GameView) {
+        self.scanned_this_round = false;
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        let player = view.player;
+        if !player.is_calibrated() {
+            return Command::End;
+        }
+        let position = player.get_position();
+
+        // Enemies seen this round within reach are shot, the second shot of the round goes to the same enemy if it is still there.
+        if player.get_shoot_action_check() {
+            let profile = CombatProfile::for_tank_type(player.get_tank_type());
+            if let Some((enemy, seen_round)) = combat_target(view, &profile) {
+                if seen_round == player.get_rounds() {
+                    if let Some(shot) = clear_shot(view.board, position, enemy) {
+                        return Command::Shoot(shot);
+                    }
+                }
+            }
+        }
+
+        // The post is chosen again if something now stands on it.
+        if self.post.map_or(true, |post| post != position && !is_free(view, post)) {
+            self.post = Some(choose_post(view));
+        }
+        let post = self.post.unwrap();
+
+        if position != post && player.get_drive_actions_check() {
+            let next = view.board.find_path(position, post).and_then(|path| path.first().copied());
+            if let Some(next) = next.filter(|next| is_free(view, *next)) {
+                return face_or(view, towards(position, next), Command::Drive);
+            }
+        }
+
+        if !self.scanned_this_round && player.get_scan_actions_check() {
+            let approaches = open_approaches(view, position);
+            let direction = approaches.get(self.watch % approaches.len().max(1)).cloned().unwrap_or(player.get_facing_direction());
+            return face_or(view, direction, Command::Scan);
+        }
+        return Command::End;
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, _player: &mut Player) {
+        if let (Command::Scan, CommandResult::Scanned(_)) = (command, result) {
+            self.scanned_this_round = true;
+            self.watch += 1;
+        }
+    }
+}
+
+/**
+ * Directions from the position that are not closed by a wall or the edge of the board, clockwise from north.
+ */
+fn open_approaches(view: &GameView, position: (i32, i32, i32)) -> Vec<String> {
+    return DIRECTIONS.iter()
+        .filter(|direction| {
+            let (dq, dr, ds) = direction_vector(direction);
+            view.board.get_tile(position.0 + dq, position.1 + dr, position.2 + ds).map_or(false, |tile| tile.get_occupied_by() != 'W')
+        })
+        .map(|direction| direction.to_string())
+        .collect();
+}
+
+/**
+ * Most defensible hex the tank can reach, among the hexes of the board model that are known to be free.
+ * Each side covered by a wall or the edge of the board is worth POST_COVER_WEIGHT, up to 4 sides so the tank can still
+ * leave, and each hex of clear sight towards the centre is worth 1. Every drive needed to get there costs 1, and every
+ * enemy that could shoot the hex next round costs POST_THREAT_WEIGHT. When nothing better is known the tank stays
+ * where it is.
+ */
+fn choose_post(view: &GameView) -> (i32, i32, i32) {
+    let position = view.player.get_position();
+    let max_coord = view.board.get_side_len() - 1;
+    let threat = ThreatMap::build(view.tracker, view.board, view.player.get_rounds());
+
+    let score = |hex: (i32, i32, i32), drives: usize| -> i32 {
+        let cover = 6 - open_approaches(view, hex).len() as i32;
+        return POST_COVER_WEIGHT * cover.min(4) + sight_line(view, hex) as i32 - drives as i32 - POST_THREAT_WEIGHT * threat.get_threat(hex) as i32;
+    };
+
+    let mut best = (score(position, 0), position);
+    for q in -max_coord..=max_coord {
+        for r in -max_coord..=max_coord {
+            let hex = (q, r, -q - r);
+            let known = view.board.get_tile(q, r, -q - r).map_or(false, |tile| tile.is_known());
+            if !known || !is_free(view, hex) {
+                continue;
+            }
+            if let Some(path) = view.board.find_path(position, hex) {
+                let value = score(hex, path.len());
+                if value > best.0 {
+                    best = (value, hex);
+                }
+            }
+        }
+    }
+    return best.1;
+}
+
+/**
+ * Number of hexes, up to POST_SIGHT_RANGE, that can be seen from the hex towards the centre before a wall or the edge.
+ */
+fn sight_line(view: &GameView, hex: (i32, i32, i32)) -> u32 {
+    let mut current = hex;
+    let mut count = 0;
+
+    while count < POST_SIGHT_RANGE && current != (0, 0, 0) {
+        let (dq, dr, ds) = direction_vector(towards(current, (0, 0, 0)).as_str());
+        current = (current.0 + dq, current.1 + dr, current.2 + ds);
+        match view.board.get_tile(current.0, current.1, current.2) {
+            Some(tile) if tile.get_occupied_by() != 'W' => count += 1,
+            _ => break,
+        }
+    }
+    return count;
+}
+
+/// The spotter stays further than this many hexes from the enemies, shots reach 2 hexes.
+pub const SPOTTER_SAFE_DISTANCE: u32 = 3;
+
+/// Enemies seen within this many rounds are kept at a distance by the spotter.
+const SPOTTER_ENEMY_MEMORY: u32 = 2;
+
+/// Extra drives the spotter is ready to make to avoid a hex, per enemy that could shoot it next round.
+const SPOTTER_THREAT_COST: u32 = 2;
+
+/**
+ * Strategy of the scout: it has 4 moves and 3 scans but only 1 shot, so it spots for the team instead of fighting.
+ * Every scan is published to the team channel with the enemies it saw (see TeamBroadcaster::publish_scan), so the
+ * heavier teammates can shoot them. The scout turns each scan to where its bigger scan shape uncovers the most hexes
+ * that are unknown or old, drives to the nearest frontier or the oldest part of the map, and stays more than
+ * SPOTTER_SAFE_DISTANCE hexes away from the enemies. Its only shot goes to the team target, when it is within reach.
+ */
+pub struct SpotterStrategy {
+    scanned: bool,
+}
+
+impl SpotterStrategy {
+    pub fn new() -> Self {
+        Self { scanned: false }
+    }
+}
+
+impl Strategy for SpotterStrategy {
+
+    fn name(&self) -> &str {
+        return "spotter";
+    }
+
+    fn on_turn_start(&mut self, _view: &GameView) {
+        self.scanned = false;
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        let player = view.player;
+        if !player.is_calibrated() {
+            return Command::End;
+        }
+        let position = player.get_position();
+        let enemies = recent_enemies(view);
+
+        if let (Some(target), true) = (view.target.as_ref(), player.get_shoot_action_check()) {
+            if target.seen_round == player.get_rounds() {
+                if let Some(shot) = clear_shot(view.board, position, target.position) {
+                    return Command::Shoot(shot);
+                }
+            }
+        }
+
+        let threatened = enemies.iter().any(|enemy| hex_distance(position, *enemy) < SPOTTER_SAFE_DISTANCE);
+        if !threatened && !self.scanned && player.get_scan_actions_check() {
+            return face_or(view, best_scan_direction(view), Command::Scan);
+        }
+
+        if !player.get_drive_actions_check() {
+            return Command::End;
+        }
+        let is_safe = |hex: &(i32, i32, i32)| -> bool {
+            enemies.iter().all(|enemy| hex_distance(*hex, *enemy) >= SPOTTER_SAFE_DISTANCE)
+        };
+
+        // Too close: back off to the neighbour furthest from the enemies.
+        if threatened {
+            let away = neighbours(position).into_iter()
+                .filter(|next| is_free(view, *next))
+                .max_by_key(|next| enemies.iter().map(|enemy| hex_distance(*next, *enemy)).min().unwrap_or(0));
+            return match away {
+                Some(next) => face_or(view, towards(position, next), Command::Drive),
+                None => Command::End,
+            };
+        }
+
+        let next = coverage_path(view).into_iter()
+            .filter_map(|path| path.first().copied())
+            .find(|next| is_free(view, *next) && is_safe(next));
+        return match next {
+            Some(next) => face_or(view, towards(position, next), Command::Drive),
+            None => Command::End,
+        };
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, _player: &mut Player) {
+        match (command, result) {
+            (Command::Scan, CommandResult::Scanned(_)) => self.scanned = true,
+            (Command::Drive, CommandResult::Accepted) => self.scanned = false,
+            _ => ()
+        }
+    }
+
+    fn planned_path(&self, view: &GameView) -> Vec<(i32, i32, i32)> {
+        if !view.player.is_calibrated() {
+            return Vec::new();
+        }
+        return coverage_path(view).into_iter().next().unwrap_or_default();
+    }
+}
+
+/**
+ * Positions of the enemies seen on the board in the last SPOTTER_ENEMY_MEMORY rounds, teammates excluded.
+ */
+fn recent_enemies(view: &GameView) -> Vec<(i32, i32, i32)> {
+    return view.board.recent_enemies(view.player.get_colour().as_str(), view.player.get_rounds(), SPOTTER_ENEMY_MEMORY).iter()
+        .map(|tile| tile.get_coords())
+        .collect();
+}
+
+/**
+ * Paths that improve the coverage of the map, cheapest first: to the frontier tiles, or once the whole map is known, to
+ * the tiles that were seen the longest time ago. The paths go around the hexes of the threat map.
+ */
+fn coverage_path(view: &GameView) -> Vec<Vec<(i32, i32, i32)>> {
+    let position = view.player.get_position();
+    let mut targets: Vec<(i32, i32, i32)> = view.board.frontier();
+    if targets.is_empty() {
+        let oldest = view.board.stale_tiles(view.player.get_rounds(), 0).iter().map(|tile| tile.get_seen_round()).min();
+        targets = view.board.stale_tiles(view.player.get_rounds(), 0).into_iter()
+            .filter(|tile| Some(tile.get_seen_round()) == oldest)
+            .map(|tile| tile.get_coords())
+            .collect();
+    }
+
+    let threat = ThreatMap::build(view.tracker, view.board, view.player.get_rounds());
+    let cost = threat.path_cost(SPOTTER_THREAT_COST);
+    let path_cost = |path: &Vec<(i32, i32, i32)>| -> u32 { path.iter().map(|hex| 1 + cost(*hex)).sum() };
+
+    let mut paths: Vec<Vec<(i32, i32, i32)>> = targets.into_iter()
+        .filter(|target| *target != position)
+        .filter_map(|target| view.board.find_weighted_path(position, target, &cost))
+        .collect();
+    paths.sort_by_key(|path| path_cost(path));
+    return paths;
+}
+
+// Exploration
+/*
+   Find corner
+    |
+    v
+   looker -> stalker -> tank_movement
+
+*/
+
+// / phase 2. Goes toward the center twice and then drives around in a
+// / smaller hexagon while looking for enemies. Flows into phase 3 (internal call)
+// / Also flows into phase 4 if function ends (expected to be called externally).
+// fn looker(player: &mut Player) {
+//     // Drive inside loop
+//     // Scan left and right
+//     // drive forward
+//     // drive twice since can scan 3 tiles away
+//     player.drive();
+//     player.end();
+//     player.drive();
+//     player.end();
+//     // turn counter clockwise
+//     turn_LR(player, Turn::Left);
+//     let mut response: scan_parser::ScanResponse;
+//     let mut drive_count = 0;
+//     loop {
+//         // 2 inside the hex so 2+1
+//         if drive_count >= player.get_side_len() - 3 {
+//             drive_count = 0;
+//             turn_LR(player, Turn::Right);
+//         }
+//         turn_LR(player, Turn::Left);
+
+//         response = player.scan();
+//         player.end();
+
+//         if response.get_enemy_detected() {
+//             stalk(player);
+//         };
+
+//         turn_LR_n(player, Turn::Right, 2);
+//         response = player.scan();
+//         player.end();
+
+//         if response.get_enemy_detected() {
+//             stalk(player);
+//         };
+
+//         turn_LR(player, Turn::Left);
+//         player.drive();
+//         player.end();
+//         drive_count += 1;
+
+//         // if player rounds played >= exploration rounds, break
+
+//         if player.get_rounds() >= player.get_exploration_rounds() {
+//             break;
+//         }
+//     }
+// }
+
+// /// Helper function. Assumes enemy is directly in front of robot.
+// /// Goes left and around the enemy, expects no obstacles adjacent to enemy
+// fn flank(player: &mut Player) -> bool {
+//     turn_LR(player, Turn::Left);
+//     player.drive();
+//     player.end();
+//     turn_LR(player, Turn::Right);
+//     player.drive();
+//     player.end();
+//     turn_LR_n(player, Turn::Right, 2);
+//     return true;
+// }
+
+// /// phase 3. Stalks an enemy player. Closes the distance and attempts to circle around.
+// /// ONLY FOR EXPLORATIOIN ROUNDS!
+// /// Does not attack, only observes.
+// /// returns false if target is lost, returns true if target is in vision and exploration rounds are over
+// fn stalk(player: &mut Player) -> bool {
+//     let mut player_lost_for_rounds = 0;
+//     let mut response: ScanResponse;
+
+//     loop {
+//         // if exploration rounds are over, go to the tank phase
+//         if player.get_rounds() >= player.get_exploration_rounds() {
+//             return true;
+//         }
+
+//         response = player.scan();
+//         player.end();
+
+//         if response.get_enemy_detected() {
+//             if response.get_enemies().contains(&(1 as usize)) {
+//                 // upper: Driving twice
+//                 if response.get_walls().contains(&0) && // check if we can get around player
+//                     response.get_walls().contains(&4)
+//                 {
+//                     player.skip();
+//                 } else {
+//                     flank(player); // drives twice
+//                 }
+//             }
+//             // Face towards enemy, then drive.
+//             else if !response.get_enemies().contains(&8)
+//                 && !response.get_enemies().contains(&9)
+//                 && !response.get_enemies().contains(&10)
+//             {
+//                 let mut move_dir = construct_shoot_move(
+//                     player.get_facing_direction(),
+//                     response.get_enemies()[0] as usize,
+//                 );
+//                 move_dir = get_args(move_dir).get(1).unwrap().to_string();
+//                 player.turn(&*move_dir);
+//                 player.drive();
+//                 player.end();
+//             } else {
+//                 // just drive forward hopefully in enemy direction
+//                 player.drive();
+//                 player.end();
+//             }
+//         } else {
+//             player_lost_for_rounds += 1;
+//             player.drive();
+//             player.end();
+//         }
+
+//         if player_lost_for_rounds > 2 {
+//             return false;
+//         }
+//     }
+// }
+
+// /// phase 4. Chosen for its exceptional survivability and adaptability.
+// /// A very general set of moves that work after exploration rounds are over.
+// /// Directly influenced by the professor's tank algorithm.
+// /// Main difference is that it scans first instead of randomly turns first.
+// /// This is to take advantage of previous functions that set up robot for the kill
+// fn tank_movement(player: &mut Player) {
+//     // eprintln!("Reached tank!");
+//     let mut response: ScanResponse;
+
+//     loop {
+//         // start with scan
+//         // We scan in a random direction and shoot at the closest thing to us,
+//         response = player.scan();
+//         player.end();
+
+//         // We saw an enemy and they're in range, so we shoot!
+//         if response.get_enemy_detected() && response.get_enemies()[0] <= 7 {
+//             let shoot_command =
+//                 construct_shoot_move(player.get_facing_direction(), response.get_enemies()[0]);
+//             player.shoot(&*shoot_command);
+//         }
+
+//         let mut valid_moves = construct_drive_moves(player.get_facing_direction(), &mut response);
+
+//         if valid_moves.len() > 0 {
+//             let num_moves = valid_moves.len() as usize;
+//             player.turn(&valid_moves[rand::random::<usize>() % num_moves][6..]);
+//             player.drive();
+//             player.end();
+//         } else {
+//             turn_opposite(player);
+//             response = player.scan();
+//             player.end();
+
+//             valid_moves = construct_drive_moves(player.get_facing_direction(), &mut response);
+
+//             let num_moves = valid_moves.len() as usize;
+//             player.turn(&valid_moves[rand::random::<usize>() % num_moves][6..]);
+//             player.drive();
+//             player.end();
+//         }
+
+//         // in the end turn to a random direction
+//         turn_rand(player);
+//     }
+// }
+
+// /// The public facing function that starts executing the robot strategies
+// pub fn execute_robot_strategies(player: &mut Player) {
+//     orient_to_near_corner(player);
+//     looker(player);
+//     tank_movement(player);
+// }
+
+#[cfg(test)]
+mod unit_test {
+    use crate::robot_strategies::*;
+    use crate::game::EnemyTracker;
+    use crate::CoordSystem::{board, OWN_SCAN_CONFIDENCE};
+
+    #[test]
+    fn test_find_corner_calibrates_on_wall() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        let game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut strategy = FindCornerStrategy::new();
+
+        player.add_scan_action();
+        player.add_step();
+        scan.scan_entry(get_args("aWcdefghijk".to_string()));
+        strategy.on_result(&Command::Scan, &CommandResult::Scanned(scan.clone()), &mut player);
+
+        assert!(player.get_corner_status(), "A wall right in front of the tank means it reached its corner.");
+        assert_eq!((4, -4, 0), player.get_position(), "R should be calibrated on its corner.");
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 2, target: None };
+        assert_eq!(Command::Skip, strategy.next_action(&view), "Once in the corner the tank should skip the rest of the round.");
+    }
+
+    #[test]
+    fn test_patrol_trees_follow_the_profile() {
+        let heavy = CombatProfile::for_tank_type(tank_type::heavy).patrol_tree();
+        let scout = CombatProfile::for_tank_type(tank_type::scout).patrol_tree();
+        let tank = CombatProfile::for_tank_type(tank_type::tank).patrol_tree();
+
+        for tree in [&heavy, &scout, &tank] {
+            assert!(BehaviourTree::parse(tree).is_ok(), "Every patrol tree should be valid:{}", tree);
+        }
+        assert!(!heavy.contains("turn"), "The heavy should never turn while patrolling.");
+        assert!(scout.contains("    last_action_scan\n    enemy_in_scan"), "The scout should only shoot right after a scan.");
+        assert!(scout.ends_with("  limit 2\n    turn_left\n  end\n"), "The scout should turn left twice when blocked.");
+        assert!(tank.contains("    scanned_this_round\n    enemy_in_scan"), "The tank should shoot an enemy of any scan of the round.");
+        assert!(tank.ends_with("  sequence\n    last_action_scan\n    limit 1\n      turn_right\n  end\n"), "The tank should turn right once, after a scan.");
+    }
+
+    #[test]
+    fn test_explorer_scans_then_drives_to_frontier() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut strategy = ExplorerStrategy::new();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(Command::Scan, strategy.next_action(&view), "With nothing known every direction is as good, the tank should scan where it faces.");
+
+        player.add_scan_action();
+        player.add_step();
+        scan.scan_entry(get_args("aWcdefghijk".to_string()));
+        game_board.update_board(&mut scan, player.get_position(), "N", 1, 1);
+        strategy.on_result(&Command::Scan, &CommandResult::Scanned(scan.clone()), &mut player);
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        let command = strategy.next_action(&view);
+        assert!(matches!(command, Command::Turn(_)), "The wall blocks the way north, the tank should turn towards the frontier.");
+        assert_ne!(Command::Turn(NORTH.to_string()), command, "The tank should not turn into the wall.");
+        let path = strategy.planned_path(&view);
+        assert!(!path.is_empty() && hex_distance((0, 0, 0), path[0]) == 1, "The planned path should start next to the tank.");
+        assert!(!path.contains(&(0, -1, 1)), "The planned path should go around the wall.");
+
+        player.start_round(10);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        strategy.on_turn_start(&view);
+        assert_eq!(Command::End, strategy.next_action(&view), "The explorer stops once the exploration rounds are over.");
+    }
+
+    #[test]
+    fn test_traverse_hunts_engages_and_retreats() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 1 as u32, 5, tank_type::tank);
+        player.start_round(2);
+        player.calibrate(0, 0, 0);
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(get_args("abcdefghiBk".to_string()));
+        game_board.update_board(&mut scan, (0, 0, 0), "N", 2, 1);
+        let mut strategy = TraverseStrategy::new();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        strategy.on_turn_start(&view);
+        assert_eq!(Command::Drive, strategy.next_action(&view), "B is 3 hexes ahead, the tank should close in.");
+
+        player.drive_forward();
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::DRIVE, action_counter: 1, target: None };
+        assert_eq!(Command::Shoot("SHOOT N-N".to_string()), strategy.next_action(&view), "B was seen this round 2 hexes ahead, the tank should shoot.");
+
+        player.take_damage();
+        player.start_round(3);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        strategy.on_turn_start(&view);
+        assert_eq!(Command::Scan, strategy.next_action(&view), "B was not seen this round, the tank should confirm it before shooting.");
+
+        strategy.on_result(&Command::Scan, &CommandResult::Scanned(scan.clone()), &mut player);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        let away = match strategy.next_action(&view) {
+            Command::Turn(direction) => {
+                let (dq, dr, ds) = direction_vector(direction.as_str());
+                hex_distance((dq, -1 + dr, 1 + ds), (0, -3, 3)) > 2
+            },
+            _ => false,
+        };
+        assert!(away, "B was not confirmed and the tank was hit, it should turn away from B.");
+    }
+
+    #[test]
+    fn test_turret_holds_a_covered_hex_and_shoots_twice() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::heavy);
+        player.start_round(2);
+        player.calibrate(0, 0, 0);
+        let mut game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        // (1, -1, 0) has walls on its north, north east and south east sides.
+        for tile in [(0, 0, 0), (1, -1, 0)] {
+            game_board.merge_tile(tile, 'a', 1, 1, None, OWN_SCAN_CONFIDENCE);
+        }
+        for wall in [(1, -2, 1), (2, -2, 0), (2, -1, -1)] {
+            game_board.merge_tile(wall, 'W', 1, 1, None, OWN_SCAN_CONFIDENCE);
+        }
+        let mut strategy = TurretStrategy::new();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        strategy.on_turn_start(&view);
+        assert_eq!(Command::Turn(NORTH_EAST.to_string()), strategy.next_action(&view), "The tank should head to the covered hex.");
+        assert_eq!(Some((1, -1, 0)), strategy.get_post(), "The hex with walls on three sides should be the post.");
+
+        player.update_facing_direction(NORTH_EAST);
+        player.drive_forward();
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::DRIVE, action_counter: 1, target: None };
+        assert!(matches!(strategy.next_action(&view), Command::Turn(_) | Command::Scan), "At its post the tank should watch an approach.");
+
+        game_board.merge_tile((0, 1, -1), 'B', 2, 2, None, OWN_SCAN_CONFIDENCE);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 2, target: None };
+        assert_eq!(Command::Shoot("SHOOT S-SW".to_string()), strategy.next_action(&view), "B is within reach, the tank should shoot.");
+
+        player.add_shoot_action();
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SHOOT, action_counter: 3, target: None };
+        assert_eq!(Command::Shoot("SHOOT S-SW".to_string()), strategy.next_action(&view), "The heavy has a second shot for the same enemy.");
+    }
+
+    #[test]
+    fn test_spotter_scans_and_keeps_its_distance() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::scout);
+        player.start_round(2);
+        player.calibrate(0, 0, 0);
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut strategy = SpotterStrategy::new();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        strategy.on_turn_start(&view);
+        assert_eq!(Command::Scan, strategy.next_action(&view), "With no enemy around the scout should scan first.");
+
+        player.add_scan_action();
+        player.add_step();
+        scan.scan_entry(get_args("abcdefgBijklmnop".to_string()));
+        game_board.update_board(&mut scan, (0, 0, 0), "N", 2, 1);
+        strategy.on_result(&Command::Scan, &CommandResult::Scanned(scan.clone()), &mut player);
+        assert!(game_board.get_tile(2, -2, 0).unwrap().is_tank(), "B should be on the board from the scout scan.");
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        let next = match strategy.next_action(&view) {
+            Command::Turn(direction) => direction,
+            command => panic!("The scout should turn away from B, got {:?}", command),
+        };
+        let (dq, dr, ds) = direction_vector(next.as_str());
+        assert!(hex_distance((dq, dr, ds), (2, -2, 0)) > 2, "The scout should back off from B, which is 2 hexes away.");
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::clock::Clock;
+use crate::comms::{TeamBroadcaster, TeamListener};
+use crate::player::tank_type;
+use crate::team_coordination::colour_rank;
+use crate::team_protocol::{decode_message, TeamMessage};
+
+/// Roles the team wants on the field, in the order they are handed out: one scout, one heavy and one tank.
+/// Tanks beyond the planned composition play as normal tanks.
+pub const TEAM_COMPOSITION: [char; 3] = ['S', 'H', 'T'];
+
+/// How long the robots exchange role proposals before sending IAM, in milliseconds.
+pub const NEGOTIATION_TIME_MS: u64 = 300;
+
+/// How often the team channel is checked for proposals during the negotiation, in milliseconds.
+const NEGOTIATION_POLL_MS: u64 = 10;
+
+/**
+ * Agree on the role of each robot of the team before sending IAM.
+ *
+ * Every robot publishes the role it was started with, then listens to the proposals of its teammates for
+ * NEGOTIATION_TIME_MS. The proposal is published again each time a new teammate is heard, so robots that started a bit
+ * later still get it. Every robot then runs assign_roles on the same proposals and gets the same answer.
+ *
+ *  - own_colour: Our colour, given by START.
+ *  - preferred: The role given on the command line, T, S or H.
+ *  - listener, broadcaster: The team channel.
+ *  - clock: Time source of the negotiation, SystemClock in a game.
+ *
+ *  - Returns: The role to send with IAM. Without a team channel, or when no teammate answers, the preferred role is kept.
+ *
+ * The roles are only a plan, there is no confirmation step: a robot whose IAM is refused by the server falls back to
+ * another role without telling the team, so the final roles of the team are not guaranteed to match the assignment.
+ */
+pub fn negotiate_role(own_colour: char, preferred: char, listener: Option<&TeamListener>, broadcaster: &mut TeamBroadcaster, clock: &dyn Clock) -> char {
+    let listener = match listener {
+        Some(listener) if broadcaster.is_connected() => listener,
+        _ => {
+            eprintln!("No team channel, playing the role given on the command line: {}", preferred);
+            return preferred;
+        }
+    };
+
+    let mut proposals: HashMap<char, char> = HashMap::new();
+    proposals.insert(own_colour, preferred);
+    broadcaster.publish(TeamMessage::RoleProposal { role: preferred });
+
+    let deadline = clock.now_ms() + NEGOTIATION_TIME_MS;
+    while clock.now_ms() < deadline {
+        let heard_before = proposals.len();
+
+        for line in listener.drain() {
+            match decode_message(&line) {
+                Ok(envelope) => {
+                    if let TeamMessage::RoleProposal { role } = envelope.message {
+                        if envelope.sender != own_colour {
+                            proposals.insert(envelope.sender, role);
+                        }
+                    }
+                },
+                Err(error) => eprintln!("Ignoring message from teammate: {}", error),
+            }
+        }
+
+        if proposals.len() > heard_before {
+            broadcaster.publish(TeamMessage::RoleProposal { role: preferred });
+        }
+        clock.sleep_ms(NEGOTIATION_POLL_MS);
+    }
+
+    if proposals.len() == 1 {
+        eprintln!("No teammate answered, playing the role given on the command line: {}", preferred);
+        return preferred;
+    }
+
+    let roles = assign_roles(&proposals);
+    let role = roles.get(&own_colour).cloned().unwrap_or(preferred);
+    eprintln!("Team roles agreed: {:?}, playing {}", roles, role);
+    return role;
+}
+
+/**
+ * Give a role to every robot of the team. The result only depends on the proposals, so all the robots get the same one.
+ *   - Robots are handled in colour order (R, O, Y, G, B, V).
+ *   - First, each robot gets the role it proposed if the composition still has room for it.
+ *   - Then the others get the first role still missing from TEAM_COMPOSITION, or T once the composition is complete.
+ *
+ *  - proposals: The role proposed by each robot, by colour.
+ *
+ *  - Returns: The role of each robot, by colour.
+ */
+pub fn assign_roles(proposals: &HashMap<char, char>) -> HashMap<char, char> {
+    let mut colours: Vec<char> = proposals.keys().cloned().collect();
+    colours.sort_by_key(|colour| (colour_rank(*colour), *colour));
+
+    let mut open_roles: Vec<char> = TEAM_COMPOSITION.to_vec();
+    let mut roles: HashMap<char, char> = HashMap::new();
+
+    for colour in colours.iter() {
+        let proposed = proposals[colour];
+        if let Some(index) = open_roles.iter().position(|role| *role == proposed) {
+            open_roles.remove(index);
+            roles.insert(*colour, proposed);
+        }
+    }
+
+    for colour in colours.iter() {
+        if !roles.contains_key(colour) {
+            let role = if open_roles.is_empty() { 'T' } else { open_roles.remove(0) };
+            roles.insert(*colour, role);
+        }
+    }
+    return roles;
+}
+
+/**
+ * Return the tank type of a role letter, as given with IAM or on the command line.
+ */
+pub fn role_tank_type(role: char) -> tank_type {
+    return match role.to_ascii_uppercase() {
+        'T' => tank_type::tank,
+        'H' => tank_type::heavy,
+        'S' => tank_type::scout,
+        _ => tank_type::error,
+    };
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::role_negotiation::*;
+    use crate::clock::SystemClock;
+    use crate::team_bus::{TeamBus, TeamBusConfig};
+    use crate::team_protocol::{encode_message, TeamEnvelope};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_assign_roles_keeps_the_composition() {
+        let proposals: HashMap<char, char> = [('G', 'T'), ('R', 'T'), ('B', 'T')].iter().cloned().collect();
+        let roles = assign_roles(&proposals);
+
+        assert_eq!('T', roles[&'R'], "R comes first in colour order and keeps its proposal.");
+        assert_eq!('S', roles[&'G'], "G should take the first missing role of the composition.");
+        assert_eq!('H', roles[&'B'], "B should take the next missing role.");
+    }
+
+    #[test]
+    fn test_assign_roles_honours_distinct_proposals() {
+        let proposals: HashMap<char, char> = [('O', 'H'), ('Y', 'S'), ('V', 'T'), ('R', 'H')].iter().cloned().collect();
+        let roles = assign_roles(&proposals);
+
+        assert_eq!('H', roles[&'R'], "R comes before O and gets the heavy it asked for.");
+        assert_eq!('S', roles[&'Y'], "Nobody else asked for the scout.");
+        assert_eq!('T', roles[&'V'], "Nobody else asked for the tank.");
+        assert_eq!('T', roles[&'O'], "The composition is complete, O should play a normal tank.");
+    }
+
+    #[derive(Clone)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    #[test]
+    fn test_negotiate_role_with_teammate() {
+        let bus = TeamBus::new(TeamBusConfig { latency_ms: 20, ..TeamBusConfig::default() });
+        let (_, mut red_writer) = bus.connect();
+        let (green_reader, _) = bus.connect();
+        let listener = TeamListener::start(green_reader);
+        let output = SharedOutput(Arc::new(Mutex::new(Vec::new())));
+        let mut broadcaster = TeamBroadcaster::new('G', Some(Box::new(output.clone())));
+
+        let teammate = encode_message(&TeamEnvelope::new('R', 0, 0, TeamMessage::RoleProposal { role: 'S' })) + "\n";
+        red_writer.write_all(teammate.as_bytes()).unwrap();
+        assert_eq!('H', negotiate_role('G', 'S', Some(&listener), &mut broadcaster, &bus.clock()), "R asked for the scout first, G should take the heavy.");
+
+        let sent = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(2, sent.lines().count(), "The proposal should be sent again once R is heard.");
+        bus.close();
+        listener.shutdown();
+    }
+
+    #[test]
+    fn test_negotiate_role_without_team_channel() {
+        let mut broadcaster = TeamBroadcaster::new('G', None);
+        assert_eq!('H', negotiate_role('G', 'H', None, &mut broadcaster, &SystemClock::new()), "Without a team channel the command line role should be kept.");
+    }
+}
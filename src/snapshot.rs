@@ -0,0 +1,158 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::CoordSystem::board;
+use crate::game::EnemyTracker;
+use crate::player::Player;
+
+/// Version written on every snapshot. Increase it when the format changes so old fixtures are rejected with a clear error.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/**
+ * Snapshot of what the robot knows at a given moment. Used by tests to start from a known state such as
+ * "round 12, tank at (2,-3,1) facing SE, this map known" instead of building it by hand through scans.
+ *
+ *  - version: The snapshot format version, see SNAPSHOT_VERSION.
+ *  - player: The player model, it holds the round number, position and facing direction.
+ *  - board: The board, only observed tiles are written.
+ *  - tracker: The enemy sightings, the moves per round of the enemies and the hits we took.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub version: u32,
+    pub player: Player,
+    pub board: board,
+    pub tracker: EnemyTracker,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::Io(error) => write!(f, "could not access the snapshot file: {}", error),
+            SnapshotError::Json(error) => write!(f, "the snapshot is not valid: {}", error),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "snapshot version {} is not supported, expected version {}", version, SNAPSHOT_VERSION),
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(error: io::Error) -> Self {
+        SnapshotError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(error: serde_json::Error) -> Self {
+        SnapshotError::Json(error)
+    }
+}
+
+impl GameSnapshot {
+
+    /**
+     * Take a snapshot of the player, the board and the enemy tracker.
+     */
+    pub fn new(player: Player, game_board: board, tracker: EnemyTracker) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            player: player,
+            board: game_board,
+            tracker: tracker,
+        }
+    }
+
+    /**
+     * Serialize the snapshot to pretty printed json, so fixtures can be read and edited by hand.
+     */
+    pub fn to_json(&self) -> Result<String, SnapshotError> {
+        return Ok(serde_json::to_string_pretty(self)?);
+    }
+
+    /**
+     * Load a snapshot from json. Snapshots written with another format version are rejected.
+     */
+    pub fn from_json(json: &str) -> Result<Self, SnapshotError> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|version| version.as_u64()).unwrap_or(0) as u32;
+
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        return Ok(serde_json::from_value(value)?);
+    }
+
+    /**
+     * Write the snapshot to a file.
+     */
+    pub fn save(&self, path: &str) -> Result<(), SnapshotError> {
+        fs::write(path, self.to_json()?)?;
+        return Ok(());
+    }
+
+    /**
+     * Load a snapshot from a file.
+     */
+    pub fn load(path: &str) -> Result<Self, SnapshotError> {
+        return Self::from_json(&fs::read_to_string(path)?);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::snapshot::*;
+    use crate::CoordSystem::board_operations;
+    use crate::damage::DamageReport;
+    use crate::game::{EnemiesPos, TeamColor};
+    use crate::parser;
+    use crate::player::{PlayerState, tank_type};
+    use crate::scan_parser::{GetScanReturn, ScanResponse};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut player: Player = Player::initialize_player("G".to_string(), 10 as u32, 5, tank_type::scout);
+        player.calibrate(2, -3, 1);
+        player.update_facing_direction("SE");
+
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        scan.scan_entry(parser::get_args("aWcdeRghijk".to_string()));
+        game_board.update_board(&mut scan, (0, 0, 0), "N", 4, 2);
+
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, -2, 2, TeamColor::R, 4, None));
+        tracker.record(EnemiesPos::new(1, -2, 1, TeamColor::B, 3, Some('Y')));
+        tracker.set_max_moves(TeamColor::R, 4);
+        tracker.record_damage(DamageReport::new((2, -3, 1), vec!["S".to_string()], 4, &game_board), &game_board);
+
+        let json = GameSnapshot::new(player, game_board, tracker).to_json().unwrap();
+        assert!(!json.contains("facing_directon"), "The field names of Player should not be part of the format.");
+        let loaded = GameSnapshot::from_json(&json).unwrap();
+
+        assert_eq!((2, -3, 1), loaded.player.get_position(), "The player position should survive the snapshot.");
+        assert_eq!("SE", loaded.player.get_facing_direction(), "The facing direction should survive the snapshot.");
+        assert_eq!(Some((4, 2)), loaded.board.last_seen(0, -1, 1), "The observed tiles should survive the snapshot.");
+        assert_eq!(None, loaded.board.last_seen(0, 1, -1), "Tiles never observed should stay unknown.");
+        assert!(loaded.board.get_tile(0, -2, 2).unwrap().is_tank(), "The enemy sighting should survive the snapshot.");
+        assert!(matches!(loaded.player.get_tank_type(), tank_type::scout), "The tank type should survive the snapshot.");
+        assert_eq!(Some((0, -2, 2)), loaded.tracker.last_sighting(TeamColor::R).map(|sighting| sighting.get_position()), "The enemy sightings should survive the snapshot.");
+        assert_eq!(Some('Y'), loaded.tracker.last_sighting(TeamColor::B).unwrap().get_reported_by(), "Who reported a sighting should survive the snapshot.");
+        assert_eq!(4, loaded.tracker.get_max_moves(TeamColor::R), "The moves per round of the enemies should survive the snapshot.");
+        assert_eq!(Some(4), loaded.tracker.last_damage().map(|report| report.get_round()), "The hits we took should survive the snapshot.");
+    }
+
+    #[test]
+    fn test_snapshot_rejects_other_versions() {
+        let json = "{\"version\": 999}";
+        assert!(matches!(GameSnapshot::from_json(json), Err(SnapshotError::UnsupportedVersion(999))), "A snapshot from another version should be rejected.");
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::CoordSystem::board;
+use crate::game::EnemyTracker;
+use crate::action_manager::playerOutput;
+use crate::mcts::{MctsConfig, MctsStrategy};
+use crate::parser::RunOptions;
+use crate::player::Player;
+use crate::scan_parser::ScanResponse;
+use crate::team_coordination::EnemyTarget;
+
+/// Strategy used when none is given on the command line.
+pub const DEFAULT_STRATEGY: &str = "fsm";
+
+/**
+ * Action a strategy wants to send to the server, it is sent by action_manager::execute.
+ *  - Turn: The direction to face, for example "NE".
+ *  - Shoot: The full shoot command, for example "SHOOT N-NE".
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Scan,
+    Drive,
+    Turn(String),
+    Shoot(String),
+    Skip,
+    End,
+}
+
+impl fmt::Display for Command {
+
+    /// The command as it is sent to the server.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Scan => write!(f, "SCAN"),
+            Command::Drive => write!(f, "DRIVE"),
+            Command::Turn(direction) => write!(f, "TURN {}", direction),
+            Command::Shoot(shot) => write!(f, "{}", shot),
+            Command::Skip => write!(f, "SKIP"),
+            Command::End => write!(f, "END"),
+        }
+    }
+}
+
+/**
+ * What the server answered to the last command.
+ *  - Accepted: The server answered OK.
+ *  - Scanned: The server answered a scan, already parsed.
+ *  - Refused: The server answered HUH?, or the command was never sent (for example a shot vetoed by the friendly fire guard).
+ */
+#[derive(Clone)]
+pub enum CommandResult {
+    Accepted,
+    Scanned(ScanResponse),
+    Refused,
+}
+
+/**
+ * Read only view of the game given to the strategies to decide their next action.
+ *  - tracker: Every enemy sighting, with the predictions of where the enemies can be.
+ *  - last_action: The last action sent to the server.
+ *  - action_counter: Number of actions sent this round, turns included.
+ *  - target: The enemy the team agreed to focus fire on, if any.
+ */
+pub struct GameView<'a> {
+    pub player: &'a Player,
+    pub board: &'a board,
+    pub scan: &'a ScanResponse,
+    pub tracker: &'a EnemyTracker,
+    pub last_action: playerOutput,
+    pub action_counter: u32,
+    pub target: Option<EnemyTarget>,
+}
+
+/**
+ * A way of playing. The strategy controller calls:
+ *   - on_turn_start once at the start of every round.
+ *   - next_action every time the robot has to act. The command is sent by action_manager.
+ *   - on_result with the server answer to the command. This is where the strategy updates the player model, for example
+ *     when a scan shows the tank reached its corner.
+ *   - planned_path for the debug renderers, the hexes the strategy would drive through next. Empty by default.
+ */
+pub trait Strategy {
+    fn name(&self) -> &str;
+    fn on_turn_start(&mut self, view: &GameView);
+    fn next_action(&mut self, view: &GameView) -> Command;
+    fn on_result(&mut self, command: &Command, result: &CommandResult, player: &mut Player);
+
+    fn planned_path(&self, _view: &GameView) -> Vec<(i32, i32, i32)> {
+        return Vec::new();
+    }
+}
+
+/**
+ * Strategies by name, so they can be chosen on the command line with --strategy <name> and used by other strategies.
+ * New strategies are added with register, handle_server_output doesn't need to know about them.
+ */
+pub struct StrategyRegistry {
+    factories: HashMap<String, Box<dyn Fn() -> Box<dyn Strategy>>>,
+}
+
+impl StrategyRegistry {
+
+    /**
+     * Create an empty registry.
+     */
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    /**
+     * Create a registry with the strategies of robot_strategies, the default strategy machine, the MCTS planner
+     * and the utility scorer.
+     */
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        crate::robot_strategies::register_strategies(&mut registry);
+        registry.register("fsm", || Box::new(crate::strategy_machine::StrategyMachine::default_machine()));
+        registry.register("mcts", || Box::new(MctsStrategy::new(MctsConfig::default())));
+        registry.register("utility", || Box::new(crate::utility_ai::UtilityStrategy::new(crate::utility_ai::UtilityWeights::default())));
+        return registry;
+    }
+
+    /**
+     * Create the registry of with_defaults, with the strategies set up by the command line options. The MCTS planner
+     * searches for --mcts-budget milliseconds.
+     */
+    pub fn with_options(options: &RunOptions) -> Self {
+        let mut registry = Self::with_defaults();
+        if let Some(budget) = options.mcts_budget_ms {
+            let config = MctsConfig { time_budget_ms: budget, ..MctsConfig::default() };
+            registry.register("mcts", move || Box::new(MctsStrategy::new(config)));
+        }
+        return registry;
+    }
+
+    /**
+     * Add a strategy, replacing the one with the same name if any.
+     */
+    pub fn register<F: Fn() -> Box<dyn Strategy> + 'static>(&mut self, name: &str, factory: F) {
+        self.factories.insert(name.to_string(), Box::new(factory));
+    }
+
+    /**
+     * Create a new instance of the strategy with the given name.
+     */
+    pub fn create(&self, name: &str) -> Option<Box<dyn Strategy>> {
+        return self.factories.get(name).map(|factory| factory());
+    }
+
+    /**
+     * Return the names of the registered strategies, sorted.
+     */
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.factories.keys().cloned().collect();
+        names.sort();
+        return names;
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::strategy::*;
+
+    struct AlwaysScan;
+
+    impl Strategy for AlwaysScan {
+        fn name(&self) -> &str { "always_scan" }
+        fn on_turn_start(&mut self, _view: &GameView) {}
+        fn next_action(&mut self, _view: &GameView) -> Command { Command::Scan }
+        fn on_result(&mut self, _command: &Command, _result: &CommandResult, _player: &mut Player) {}
+    }
+
+    #[test]
+    fn test_registry_creates_by_name() {
+        let mut registry = StrategyRegistry::with_defaults();
+        registry.register("always_scan", || Box::new(AlwaysScan));
+
+        assert!(registry.names().contains(&DEFAULT_STRATEGY.to_string()), "The default strategy should be registered.");
+        assert_eq!("utility", registry.create("utility").unwrap().name(), "The utility scorer should be playable with --strategy utility.");
+        assert_eq!("always_scan", registry.create("always_scan").unwrap().name(), "A registered strategy should be created by its name.");
+        assert!(registry.create("no_such_strategy").is_none(), "An unknown name should not create a strategy.");
+    }
+}
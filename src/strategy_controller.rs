@@ -1,94 +1,249 @@
-use crate::{player::{Player, PlayerState}, action_manager::{playerOutput, action_manager, manage_player_action}};
-
-pub enum strategiesType{
-    find_corner,
-    exlporer,
-    traverse,
-    none
-}
-
-pub struct startegies{
-    find_corner_status: bool,
-    explorer_status: bool,
-    traverse_status: bool
-}
-
-pub trait strategy_controller{
-    fn initialize_initial_startegy() -> Self;
-    fn set_active_strategy(&mut self, strategy :strategiesType);
-    fn get_active_strategy(&mut self) -> strategiesType;
-    fn avaliate_startegy(&mut self, player :&mut Player, action_manager :&mut action_manager);
-}
-
-impl strategy_controller for startegies{
-
-    /**
-     * Set initial strategy to find corner
-     */
-    fn initialize_initial_startegy() -> Self{
-        return Self{
-            find_corner_status: true,
-            explorer_status: false,
-            traverse_status: false
-        };
-    }
-
-    /**
-     * Set active strategy. By setting a new one, the old strategy should be turned off.
-     */
-    fn set_active_strategy(&mut self, strategy :strategiesType){
-        match strategy {
-            strategiesType::find_corner =>{
-                self.find_corner_status = true;
-                self.explorer_status = false;
-                self.traverse_status = false;
-            },
-            strategiesType::exlporer => {
-                self.explorer_status = true;
-                self.find_corner_status = false;
-                self.traverse_status = false;
-            }
-            strategiesType::traverse => {
-                self.traverse_status = true;
-                self.explorer_status = false;
-                self.find_corner_status = false;
-            }
-            _ => ()
-        }
-    }
-
-    /**
-     * Return the current active strategy
-     */
-    fn get_active_strategy(&mut self) -> strategiesType{
-
-        if self.traverse_status{
-            return strategiesType::traverse;
-        } else if self.explorer_status{
-            return strategiesType::exlporer;
-        }else if self.find_corner_status {
-            return strategiesType::find_corner;
-        }else{
-            return strategiesType::none;
-        }
-    }
-
-    /**
-     * Check if the robot should change to another strategy.
-     */
-    fn avaliate_startegy(&mut self, player :&mut Player, action_manager :&mut action_manager){
-        let round_num = player.get_rounds();
-        let max_exploration_round = player.get_exploration_rounds();
-
-        if !player.get_corner_status(){
-            self.set_active_strategy(strategiesType::find_corner);
-
-        }else if round_num < max_exploration_round{
-            self.set_active_strategy(strategiesType::exlporer);
-
-        }else {
-            self.set_active_strategy(strategiesType::traverse);
-        }
-    }
-
-}
+use crate::{player::{Player, PlayerState}, action_manager::{action_manager, manage_player_action}};
+use crate::CoordSystem::board;
+use crate::scan_parser::ScanResponse;
+use crate::game::EnemyTracker;
+use crate::damage::evasive_command;
+use crate::strategy::{Command, CommandResult, GameView, Strategy, StrategyRegistry, DEFAULT_STRATEGY};
+use crate::team_coordination::{EnemyTarget, TargetCoordinator, target_coordination};
+
+/// Commands asked to the strategy in one call to play before giving up on the action and ending the turn. A command is
+/// asked again when the previous one could not be sent, for example a shot vetoed by the friendly fire guard.
+pub const MAX_PLAY_ATTEMPTS: usize = 3;
+
+/**
+ * Holds the active strategy and the last command it sent, so the server answer can be passed back to it.
+ *
+ * The round after we took a hit, the controller plays an evasive manoeuvre (see damage::evasive_command) before giving
+ * the round back to the strategy. The strategy is not told about the commands of the manoeuvre.
+ */
+pub struct startegies{
+    active: Box<dyn Strategy>,
+    last_command: Option<Command>,
+    target: Option<EnemyTarget>,
+    evading: bool,
+    last_command_evasive: bool
+}
+
+pub trait strategy_controller{
+    fn initialize_initial_startegy() -> Self;
+    fn from_name(name :&str, registry :&StrategyRegistry) -> Option<Self> where Self: Sized;
+    fn from_strategy(strategy :Box<dyn Strategy>) -> Self where Self: Sized;
+    fn get_active_strategy(&self) -> &str;
+    fn avaliate_startegy(&mut self, player :&mut Player, coordinator :&mut TargetCoordinator);
+    fn start_turn(&mut self, player :&Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager);
+    fn play(&mut self, player :&mut Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager);
+    fn on_result(&mut self, result :CommandResult, player :&mut Player);
+    fn get_target(&self) -> Option<EnemyTarget>;
+    fn get_planned_path(&self, player :&Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager) -> Vec<(i32, i32, i32)>;
+}
+
+impl startegies {
+
+    /**
+     * Build the view of the game given to the strategy.
+     */
+    fn view<'a>(&self, player :&'a Player, board :&'a board, scan :&'a ScanResponse, tracker :&'a EnemyTracker, action_manager :&mut action_manager) -> GameView<'a>{
+        return GameView{
+            player: player,
+            board: board,
+            scan: scan,
+            tracker: tracker,
+            last_action: action_manager.get_last_action(),
+            action_counter: action_manager.get_action_counter(),
+            target: self.target
+        };
+    }
+}
+
+impl strategy_controller for startegies{
+
+    /**
+     * Set initial strategy to the default one, which starts by finding the corner.
+     */
+    fn initialize_initial_startegy() -> Self{
+        return Self::from_name(DEFAULT_STRATEGY, &StrategyRegistry::with_defaults()).expect("the default strategy is always registered");
+    }
+
+    /**
+     * Use the strategy with the given name, None if it is not registered.
+     */
+    fn from_name(name :&str, registry :&StrategyRegistry) -> Option<Self>{
+        return registry.create(name).map(Self::from_strategy);
+    }
+
+    /**
+     * Use the given strategy, for example a strategy machine loaded from a file.
+     */
+    fn from_strategy(strategy :Box<dyn Strategy>) -> Self{
+        return Self{
+            active: strategy,
+            last_command: None,
+            target: None,
+            evading: false,
+            last_command_evasive: false
+        };
+    }
+
+    /**
+     * Return the name of the active strategy
+     */
+    fn get_active_strategy(&self) -> &str{
+        return self.active.name();
+    }
+
+    /**
+     * Update what the strategy should know before acting.
+     * After the exploration rounds, the team coordinator chooses the enemy to focus fire on.
+     */
+    fn avaliate_startegy(&mut self, player :&mut Player, coordinator :&mut TargetCoordinator){
+        let round_num = player.get_rounds();
+        let max_exploration_round = player.get_exploration_rounds();
+
+        if player.get_corner_status() && round_num >= max_exploration_round{
+            self.target = coordinator.choose_target(player.get_position(), round_num);
+        }
+    }
+
+    /**
+     * Tell the strategy a new round started. If we took a hit last round, the round starts with an evasive manoeuvre.
+     */
+    fn start_turn(&mut self, player :&Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager){
+        self.evading = tracker.last_damage().map_or(false, |report| report.get_round() + 1 == player.get_rounds());
+        let view = self.view(player, board, scan, tracker, action_manager);
+        self.active.on_turn_start(&view);
+    }
+
+    /**
+     * Ask the strategy for its next command and send it to the server. Every call sends exactly one command.
+     * A command that could not be sent is reported back to the strategy as refused and the strategy is asked again, up
+     * to MAX_PLAY_ATTEMPTS times. When the strategy keeps choosing commands that can't be sent, or chooses a refused one
+     * again, the turn is ended.
+     */
+    fn play(&mut self, player :&mut Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager){
+        let mut refused: Vec<Command> = Vec::new();
+
+        for _ in 0..MAX_PLAY_ATTEMPTS {
+            let command = {
+                let view = self.view(player, board, scan, tracker, action_manager);
+                let evasive = if self.evading { tracker.last_damage().and_then(|report| evasive_command(&view, report)) } else { None };
+                self.evading = evasive.is_some();
+                self.last_command_evasive = evasive.is_some();
+                evasive.unwrap_or_else(|| self.active.next_action(&view))
+            };
+            if refused.contains(&command) {
+                break;
+            }
+
+            let sent = action_manager.execute(&command, player);
+            self.last_command = Some(command.clone());
+            if sent {
+                return;
+            }
+            self.evading = false;
+            if !self.last_command_evasive {
+                self.active.on_result(&command, &CommandResult::Refused, player);
+            }
+            refused.push(command);
+        }
+
+        eprintln!("No command of the strategy could be sent, ending the turn.");
+        self.last_command = Some(Command::End);
+        self.last_command_evasive = false;
+        action_manager.end();
+    }
+
+    /**
+     * Give the server answer to the last command to the strategy.
+     */
+    fn on_result(&mut self, result :CommandResult, player :&mut Player){
+        if self.last_command_evasive {
+            if matches!(result, CommandResult::Refused) {
+                self.evading = false;
+            }
+            return;
+        }
+        if let Some(command) = self.last_command.as_ref() {
+            self.active.on_result(command, &result, player);
+        }
+    }
+
+    /**
+     * Return the enemy the team agreed to focus fire on, if any.
+     */
+    fn get_target(&self) -> Option<EnemyTarget>{
+        return self.target;
+    }
+
+    /**
+     * Return the hexes the active strategy would drive through next, drawn by the debug renderers.
+     */
+    fn get_planned_path(&self, player :&Player, board :&board, scan :&ScanResponse, tracker :&EnemyTracker, action_manager :&mut action_manager) -> Vec<(i32, i32, i32)>{
+        let view = self.view(player, board, scan, tracker, action_manager);
+        return self.active.planned_path(&view);
+    }
+
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::strategy_controller::*;
+    use crate::action_manager::playerOutput;
+    use crate::CoordSystem::board_operations;
+    use crate::player::tank_type;
+    use crate::scan_parser::GetScanReturn;
+
+    /// Shoots north through the teammate, and scans instead once told the shot was refused if 'learns' is set.
+    struct ShootNorth {
+        learns: bool,
+        refused: u32,
+    }
+
+    impl Strategy for ShootNorth {
+        fn name(&self) -> &str { "shoot_north" }
+        fn on_turn_start(&mut self, _view: &GameView) {}
+        fn next_action(&mut self, _view: &GameView) -> Command {
+            if self.learns && self.refused > 0 {
+                return Command::Scan;
+            }
+            return Command::Shoot("SHOOT N-N".to_string());
+        }
+        fn on_result(&mut self, _command: &Command, result: &CommandResult, _player: &mut Player) {
+            if matches!(result, CommandResult::Refused) {
+                self.refused += 1;
+            }
+        }
+    }
+
+    fn play_with_teammate_north(strategy: ShootNorth) -> (startegies, action_manager) {
+        let mut player: Player = Player::initialize_player("R".to_string(), 1 as u32, 5, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        player.start_round(2);
+        let mut game_board: board = board::initialize(5);
+        game_board.set_teammate('B', (0, -1, 1), 2);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let tracker = EnemyTracker::new();
+
+        let mut action_manager: action_manager = action_manager::initialize();
+        action_manager.update_fire_guard(&game_board, 2);
+        let mut controller = startegies::from_strategy(Box::new(strategy));
+        controller.play(&mut player, &game_board, &scan, &tracker, &mut action_manager);
+        return (controller, action_manager);
+    }
+
+    #[test]
+    fn test_play_ends_the_turn_when_every_shot_is_vetoed() {
+        let (_, mut action_manager) = play_with_teammate_north(ShootNorth { learns: false, refused: 0 });
+
+        assert!(matches!(action_manager.get_last_action(), playerOutput::END), "Every shot goes through the teammate, the turn should be ended.");
+        assert_eq!(1, action_manager.get_action_counter(), "Exactly one command should be sent.");
+        assert!(action_manager.get_round_shots().is_empty(), "No shot should be sent.");
+    }
+
+    #[test]
+    fn test_play_asks_again_after_a_veto() {
+        let (_, mut action_manager) = play_with_teammate_north(ShootNorth { learns: true, refused: 0 });
+
+        assert!(matches!(action_manager.get_last_action(), playerOutput::SCAN), "The strategy should be asked again after the veto.");
+        assert_eq!(1, action_manager.get_action_counter(), "Exactly one command should be sent.");
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use serde::Deserialize;
+
+use crate::CoordSystem::board_operations;
+use crate::player::{Player, PlayerState, tank_type};
+use crate::strategy::{Command, CommandResult, GameView, Strategy, StrategyRegistry};
+
+/// Enemies seen within this many rounds count for the enemies_seen conditions.
+pub const ENEMY_SEEN_ROUNDS: u32 = 3;
+
+/// State name that matches every state in the 'from' of a transition.
+pub const ANY_STATE: &str = "*";
+
+/**
+ * Default machine: find the corner, explore until the exploration rounds are over, then traverse the map.
+ * The heavy tank holds a chokepoint instead of exploring, and the scout spots for the team for the whole game.
+ */
+pub const DEFAULT_MACHINE_CONFIG: &str = r#"{
+    "initial": "find_corner",
+    "transitions": [
+        { "from": "find_corner", "to": "turret", "when": { "calibrated": true, "exploration_over": false, "tank_type": "heavy" } },
+        { "from": "turret", "to": "traverse", "when": { "exploration_over": true } },
+        { "from": "find_corner", "to": "spotter", "when": { "calibrated": true, "tank_type": "scout" } },
+        { "from": "find_corner", "to": "explorer", "when": { "calibrated": true, "exploration_over": false } },
+        { "from": "find_corner", "to": "traverse", "when": { "calibrated": true, "exploration_over": true } },
+        { "from": "explorer", "to": "traverse", "when": { "exploration_over": true } }
+    ]
+}"#;
+
+/**
+ * Predicate over the game state. Every field that is set must hold, a condition with no field always holds.
+ *  - calibrated: The tank found its corner and knows its position.
+ *  - exploration_over: The exploration rounds given by START are over.
+ *  - min_round, max_round: Bounds on the round number.
+ *  - min_health, max_health: Bounds on our health.
+ *  - min_enemies_seen, max_enemies_seen: Bounds on the number of enemies seen in the last ENEMY_SEEN_ROUNDS rounds.
+ *  - min_points_left, max_points_left: Bounds on the points left this round.
+ *  - tank_type: The type of our tank, for example "heavy".
+ */
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Condition {
+    pub calibrated: Option<bool>,
+    pub exploration_over: Option<bool>,
+    pub min_round: Option<u32>,
+    pub max_round: Option<u32>,
+    pub min_health: Option<u8>,
+    pub max_health: Option<u8>,
+    pub min_enemies_seen: Option<usize>,
+    pub max_enemies_seen: Option<usize>,
+    pub min_points_left: Option<i32>,
+    pub max_points_left: Option<i32>,
+    pub tank_type: Option<tank_type>,
+}
+
+/**
+ * Switch from one strategy to another when the condition holds. 'from' can be ANY_STATE.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub when: Condition,
+}
+
+/**
+ * Configuration of a machine, see DEFAULT_MACHINE_CONFIG. The states are names of the strategy registry.
+ */
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MachineConfig {
+    pub initial: String,
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Debug)]
+pub enum MachineConfigError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnknownStrategy(String),
+}
+
+impl fmt::Display for MachineConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MachineConfigError::Io(error) => write!(f, "could not read the strategy machine file: {}", error),
+            MachineConfigError::Json(error) => write!(f, "the strategy machine is not valid: {}", error),
+            MachineConfigError::UnknownStrategy(name) => write!(f, "there is no strategy named {}", name),
+        }
+    }
+}
+
+impl From<io::Error> for MachineConfigError {
+    fn from(error: io::Error) -> Self {
+        MachineConfigError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for MachineConfigError {
+    fn from(error: serde_json::Error) -> Self {
+        MachineConfigError::Json(error)
+    }
+}
+
+/**
+ * Finite state machine where the states are strategies. Before each action the transitions leaving the current state
+ * are checked in the order of the configuration, and the first one whose condition holds is taken. Every transition is
+ * logged to stderr, so the configuration can be tuned from the logs of a game.
+ */
+pub struct StrategyMachine {
+    states: HashMap<String, Box<dyn Strategy>>,
+    transitions: Vec<Transition>,
+    current: String,
+}
+
+impl StrategyMachine {
+
+    /**
+     * Build the machine of a configuration, creating its states from the registry.
+     */
+    pub fn new(config: MachineConfig, registry: &StrategyRegistry) -> Result<Self, MachineConfigError> {
+        let mut names: Vec<&String> = vec![&config.initial];
+        for transition in config.transitions.iter() {
+            names.push(&transition.to);
+            if transition.from != ANY_STATE {
+                names.push(&transition.from);
+            }
+        }
+
+        let mut states: HashMap<String, Box<dyn Strategy>> = HashMap::new();
+        for name in names {
+            if !states.contains_key(name) {
+                let strategy = registry.create(name).ok_or_else(|| MachineConfigError::UnknownStrategy(name.clone()))?;
+                states.insert(name.clone(), strategy);
+            }
+        }
+
+        return Ok(Self {
+            states: states,
+            current: config.initial,
+            transitions: config.transitions,
+        });
+    }
+
+    /**
+     * Build the machine of a json configuration.
+     */
+    pub fn from_json(json: &str, registry: &StrategyRegistry) -> Result<Self, MachineConfigError> {
+        return Self::new(serde_json::from_str(json)?, registry);
+    }
+
+    /**
+     * Build the machine of a json configuration file.
+     */
+    pub fn load(path: &str, registry: &StrategyRegistry) -> Result<Self, MachineConfigError> {
+        return Self::from_json(&fs::read_to_string(path)?, registry);
+    }
+
+    /**
+     * Build the default machine, see DEFAULT_MACHINE_CONFIG.
+     */
+    pub fn default_machine() -> Self {
+        return Self::from_json(DEFAULT_MACHINE_CONFIG, &StrategyRegistry::with_defaults()).expect("the default strategy machine is valid");
+    }
+
+    /**
+     * Return the name of the current state.
+     */
+    pub fn get_state(&self) -> &str {
+        return &self.current;
+    }
+
+    /**
+     * Take the first transition leaving the current state whose condition holds, if any.
+     */
+    fn update_state(&mut self, view: &GameView) {
+        let next = self.transitions.iter()
+            .filter(|transition| transition.from == self.current || (transition.from == ANY_STATE && transition.to != self.current))
+            .find(|transition| condition_holds(&transition.when, view))
+            .map(|transition| transition.to.clone());
+
+        if let Some(next) = next {
+            eprintln!("Strategy: {} -> {} (round {}, step {}, health {}, points left {})",
+                self.current, next, view.player.get_rounds(), view.player.get_step_count(), view.player.get_health(), view.player.get_points_left());
+            self.current = next;
+        }
+    }
+
+    fn current_strategy(&mut self) -> &mut Box<dyn Strategy> {
+        return self.states.get_mut(&self.current).expect("every state of the machine has a strategy");
+    }
+}
+
+impl Strategy for StrategyMachine {
+
+    fn name(&self) -> &str {
+        return "fsm";
+    }
+
+    fn on_turn_start(&mut self, view: &GameView) {
+        self.update_state(view);
+        self.current_strategy().on_turn_start(view);
+    }
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        self.update_state(view);
+        return self.current_strategy().next_action(view);
+    }
+
+    fn on_result(&mut self, command: &Command, result: &CommandResult, player: &mut Player) {
+        self.current_strategy().on_result(command, result, player);
+    }
+
+    fn planned_path(&self, view: &GameView) -> Vec<(i32, i32, i32)> {
+        return self.states.get(&self.current).map_or(Vec::new(), |strategy| strategy.planned_path(view));
+    }
+}
+
+/**
+ * Check a condition against the game state.
+ */
+pub fn condition_holds(condition: &Condition, view: &GameView) -> bool {
+    let player = view.player;
+    let round = player.get_rounds();
+
+    let in_bounds = |value: i64, min: Option<i64>, max: Option<i64>| -> bool {
+        min.map_or(true, |min| value >= min) && max.map_or(true, |max| value <= max)
+    };
+
+    return condition.calibrated.map_or(true, |calibrated| player.is_calibrated() == calibrated)
+        && condition.exploration_over.map_or(true, |over| (round >= player.get_exploration_rounds()) == over)
+        && condition.tank_type.map_or(true, |tank_type| player.get_tank_type() == tank_type)
+        && in_bounds(round as i64, condition.min_round.map(i64::from), condition.max_round.map(i64::from))
+        && in_bounds(player.get_health() as i64, condition.min_health.map(i64::from), condition.max_health.map(i64::from))
+        && in_bounds(player.get_points_left() as i64, condition.min_points_left.map(i64::from), condition.max_points_left.map(i64::from))
+        && (condition.min_enemies_seen.is_none() && condition.max_enemies_seen.is_none()
+            || in_bounds(enemies_seen(view) as i64, condition.min_enemies_seen.map(|count| count as i64), condition.max_enemies_seen.map(|count| count as i64)));
+}
+
+/**
+ * Number of enemy sightings on the board from the last ENEMY_SEEN_ROUNDS rounds. Our own tank and our teammates are
+ * tanks on the board too, they are not counted.
+ */
+fn enemies_seen(view: &GameView) -> usize {
+    return view.board.recent_enemies(view.player.get_colour().as_str(), view.player.get_rounds(), ENEMY_SEEN_ROUNDS).len();
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::strategy_machine::*;
+    use crate::game::EnemyTracker;
+    use crate::CoordSystem::board;
+    use crate::action_manager::playerOutput;
+    use crate::scan_parser::{GetScanReturn, ScanResponse};
+
+    #[test]
+    fn test_default_machine_follows_the_phases() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut machine = StrategyMachine::default_machine();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.next_action(&view);
+        assert_eq!("find_corner", machine.get_state(), "The machine should stay in find_corner until the tank is calibrated.");
+
+        player.calibrate(4, -4, 0);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.next_action(&view);
+        assert_eq!("explorer", machine.get_state(), "A calibrated tank should explore during the exploration rounds.");
+
+        player.start_round(10);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.on_turn_start(&view);
+        assert_eq!("traverse", machine.get_state(), "The tank should traverse once the exploration rounds are over.");
+    }
+
+    #[test]
+    fn test_default_machine_by_tank_type() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::heavy);
+        player.calibrate(4, -4, 0);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut machine = StrategyMachine::default_machine();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.next_action(&view);
+        assert_eq!("turret", machine.get_state(), "A calibrated heavy should hold a chokepoint during the exploration rounds.");
+
+        let mut scout: Player = Player::initialize_player("G".to_string(), 10 as u32, 5, tank_type::scout);
+        scout.calibrate(-4, 4, 0);
+        let mut machine = StrategyMachine::default_machine();
+        let view = GameView { player: &scout, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.next_action(&view);
+        assert_eq!("spotter", machine.get_state(), "A calibrated scout should spot for the team.");
+    }
+
+    #[test]
+    fn test_config_with_any_state_and_health() {
+        let config = r#"{
+            "initial": "explorer",
+            "transitions": [ { "from": "*", "to": "find_corner", "when": { "max_health": 1 } } ]
+        }"#;
+        let mut machine = StrategyMachine::from_json(config, &StrategyRegistry::with_defaults()).unwrap();
+
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        let game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.on_turn_start(&view);
+        assert_eq!("explorer", machine.get_state(), "Full health should not trigger the transition.");
+
+        player.take_damage();
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        machine.on_turn_start(&view);
+        assert_eq!("find_corner", machine.get_state(), "With one health left the machine should switch from any state.");
+    }
+
+    #[test]
+    fn test_enemies_seen_ignores_the_team() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.start_round(3);
+        let mut game_board: board = board::initialize(5);
+        let scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        game_board.set_teammate('G', (1, 0, -1), 3);
+        game_board.merge_tile((1, 0, -1), 'G', 3, 0, None, 1.0);
+        game_board.merge_tile((0, 0, 0), 'R', 3, 0, None, 1.0);
+        game_board.merge_tile((2, -1, -1), 'Y', 3, 0, None, 1.0);
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(1, enemies_seen(&view), "Only Y is an enemy, R is our tank and G a teammate.");
+
+        let condition = Condition { min_enemies_seen: Some(2), ..Condition::default() };
+        assert!(!condition_holds(&condition, &view), "The team should not count towards the enemies seen.");
+    }
+
+    #[test]
+    fn test_config_errors() {
+        let registry = StrategyRegistry::with_defaults();
+        let unknown_state = r#"{ "initial": "dance", "transitions": [] }"#;
+        let unknown_condition = r#"{ "initial": "explorer", "transitions": [ { "from": "explorer", "to": "traverse", "when": { "moon_phase": 3 } } ] }"#;
+
+        assert!(matches!(StrategyMachine::from_json(unknown_state, &registry), Err(MachineConfigError::UnknownStrategy(_))), "A state must be a registered strategy.");
+        assert!(matches!(StrategyMachine::from_json(unknown_condition, &registry), Err(MachineConfigError::Json(_))), "An unknown condition should be rejected.");
+    }
+}
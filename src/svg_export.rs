@@ -0,0 +1,291 @@
+use std::fs;
+use std::io;
+
+use crate::CoordSystem::{board, board_operations, direction_vector};
+use crate::parser;
+use crate::player::{Player, PlayerState};
+use crate::threat_map::ThreatMap;
+
+/// Radius of one hex in the picture, in pixels.
+const HEX_SIZE: f64 = 20.0;
+
+/// Margin around the board, in pixels.
+const MARGIN: f64 = 30.0;
+
+/**
+ * Shot recorded for the picture.
+ *  - round: The round the shot was fired on.
+ *  - origin: The position the shot was fired from.
+ *  - path: The directions of the shot, for example ["N", "NE"] for SHOOT N-NE.
+ */
+#[derive(Debug, Clone)]
+struct ShotRecord {
+    round: u32,
+    origin: (i32, i32, i32),
+    path: Vec<String>,
+}
+
+/**
+ * Exporter that writes one SVG picture per round with what the robot knows about the arena, the path the tank
+ * took so far and the shots it fired. It is meant to review lost games, the pictures are written to 'output_dir'
+ * as round_001.svg, round_002.svg...
+ */
+pub struct SvgExporter {
+    output_dir: String,
+    trail: Vec<(i32, i32, i32)>,
+    shots: Vec<ShotRecord>,
+    threat: Option<ThreatMap>,
+}
+
+pub trait SvgExport {
+    fn initialize(output_dir: &str) -> Self;
+    fn record_position(&mut self, position: (i32, i32, i32));
+    fn record_shots(&mut self, round: u32, shots: Vec<(String, (i32, i32, i32))>);
+    fn record_threat(&mut self, threat: ThreatMap);
+    fn render_round(&self, game_board: &board, player: &Player) -> String;
+    fn write_round(&self, game_board: &board, player: &Player) -> io::Result<()>;
+}
+
+impl SvgExport for SvgExporter {
+
+    /**
+     * Initialize the exporter, the output directory is created when the first picture is written.
+     */
+    fn initialize(output_dir: &str) -> Self {
+        Self {
+            output_dir: output_dir.to_string(),
+            trail: Vec::new(),
+            shots: Vec::new(),
+            threat: None,
+        }
+    }
+
+    /**
+     * Add a position to the path the tank took. Repeated positions are only recorded once.
+     */
+    fn record_position(&mut self, position: (i32, i32, i32)) {
+        if self.trail.last() != Some(&position) {
+            self.trail.push(position);
+        }
+    }
+
+    /**
+     * Record the shots fired on a round.
+     *  - shots: The shoot commands with the position they were fired from, as returned by action_manager.
+     */
+    fn record_shots(&mut self, round: u32, shots: Vec<(String, (i32, i32, i32))>) {
+        for (command, origin) in shots {
+            let path: Vec<String> = parser::get_args(command).into_iter().skip(1).filter(|direction| !direction.is_empty()).collect();
+            self.shots.push(ShotRecord { round, origin, path });
+        }
+    }
+
+    /**
+     * Set the threat map drawn on the next pictures.
+     */
+    fn record_threat(&mut self, threat: ThreatMap) {
+        self.threat = Some(threat);
+    }
+
+    /**
+     * Render the picture of the current round:
+     *   - The hex grid, grey when unknown, white when open and black for walls.
+     *   - The threat map, if recorded, as a red heat-map: the more enemies can shoot a hex, the redder it is.
+     *   - Enemy sightings in red, fading with the probability they are still there, labeled with their age in rounds.
+     *   - Teammates in blue.
+     *   - The path the tank took so far in green and the tank itself with a line towards its facing direction.
+     *   - Shots in orange, shots from previous rounds are faded.
+     */
+    fn render_round(&self, game_board: &board, player: &Player) -> String {
+        let max_coord = game_board.get_side_len() - 1;
+        let round = player.get_rounds();
+        let (width, height) = picture_size(max_coord);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" viewBox=\"0 0 {:.0} {:.0}\">\n",
+            width, height, width, height
+        );
+        svg.push_str(&format!("<text x=\"5\" y=\"15\" font-family=\"monospace\" font-size=\"12\">Round {} - {} {}</text>\n", round, player.get_colour(), tank_type_name(player)));
+
+        // Hex grid and terrain.
+        for q in -max_coord..=max_coord {
+            for r in -max_coord..=max_coord {
+                if let Some(tile) = game_board.get_tile(q, r, -q - r) {
+                    let fill = if !tile.is_known() {
+                        "#cccccc"
+                    } else if tile.get_occupied_by() == 'W' {
+                        "#333333"
+                    } else {
+                        "#ffffff"
+                    };
+                    svg.push_str(&format!("<polygon points=\"{}\" fill=\"{}\" stroke=\"#999999\"/>\n", hex_corners(max_coord, (q, r)), fill));
+                }
+            }
+        }
+
+        // Threat heat-map.
+        if let Some(threat) = self.threat.as_ref() {
+            let max_threat = threat.get_max_threat().max(1) as f64;
+            for q in -max_coord..=max_coord {
+                for r in -max_coord..=max_coord {
+                    let count = threat.get_threat((q, r, -q - r));
+                    if count > 0 {
+                        let opacity = 0.1 + 0.4 * count as f64 / max_threat;
+                        svg.push_str(&format!("<polygon points=\"{}\" fill=\"#d62728\" fill-opacity=\"{:.2}\"/>\n", hex_corners(max_coord, (q, r)), opacity));
+                    }
+                }
+            }
+        }
+
+        // Enemy sightings with their age.
+        for q in -max_coord..=max_coord {
+            for r in -max_coord..=max_coord {
+                let s = -q - r;
+                match game_board.get_tile(q, r, s) {
+                    Some(tile) if tile.is_tank() => {
+                        let (x, y) = hex_center(max_coord, (q, r));
+                        let probability = game_board.tank_probability(q, r, s, round).max(0.15);
+                        let age = round.saturating_sub(tile.get_seen_round());
+                        svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"#d62728\" fill-opacity=\"{:.2}\"/>\n", x, y, HEX_SIZE * 0.6, probability));
+                        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"monospace\" font-size=\"10\" text-anchor=\"middle\">{} {}</text>\n", x, y + 4.0, tile.get_occupied_by(), age));
+                    },
+                    _ => ()
+                }
+            }
+        }
+
+        // Teammates.
+        for teammate in game_board.get_teammates() {
+            let (q, r, _) = teammate.get_coords();
+            let (x, y) = hex_center(max_coord, (q, r));
+            svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"#1f77b4\"/>\n", x, y, HEX_SIZE * 0.5));
+        }
+
+        // Path the tank took so far.
+        if self.trail.len() > 1 {
+            let points: Vec<String> = self.trail.iter().map(|position| {
+                let (x, y) = hex_center(max_coord, (position.0, position.1));
+                format!("{:.1},{:.1}", x, y)
+            }).collect();
+            svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"#2ca02c\" stroke-width=\"3\" stroke-opacity=\"0.6\"/>\n", points.join(" ")));
+        }
+
+        // Shots and their trajectories.
+        for shot in self.shots.iter().filter(|shot| shot.round <= round) {
+            let mut position = shot.origin;
+            let mut points: Vec<String> = Vec::new();
+            let (x, y) = hex_center(max_coord, (position.0, position.1));
+            points.push(format!("{:.1},{:.1}", x, y));
+
+            for direction in shot.path.iter() {
+                let (dq, dr, ds) = direction_vector(direction.as_str());
+                position = (position.0 + dq, position.1 + dr, position.2 + ds);
+                let (x, y) = hex_center(max_coord, (position.0, position.1));
+                points.push(format!("{:.1},{:.1}", x, y));
+            }
+
+            let opacity = if shot.round == round { 1.0 } else { 0.3 };
+            svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"#ff7f0e\" stroke-width=\"2\" stroke-dasharray=\"4 2\" stroke-opacity=\"{:.1}\"/>\n", points.join(" "), opacity));
+        }
+
+        // Our tank and its facing direction.
+        if player.is_calibrated() {
+            let (q, r, _) = player.get_position();
+            let (x, y) = hex_center(max_coord, (q, r));
+            let (dq, dr, _) = direction_vector(player.get_facing_direction().as_str());
+            let (facing_x, facing_y) = hex_center(max_coord, (q + dq, r + dr));
+            svg.push_str(&format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"#2ca02c\"/>\n", x, y, HEX_SIZE * 0.5));
+            svg.push_str(&format!("<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#2ca02c\" stroke-width=\"3\"/>\n", x, y, (x + facing_x) / 2.0, (y + facing_y) / 2.0));
+        }
+
+        svg.push_str("</svg>\n");
+        return svg;
+    }
+
+    /**
+     * Write the picture of the current round to the output directory.
+     */
+    fn write_round(&self, game_board: &board, player: &Player) -> io::Result<()> {
+        fs::create_dir_all(&self.output_dir)?;
+        let file_name = format!("{}/round_{:03}.svg", self.output_dir, player.get_rounds());
+        return fs::write(file_name, self.render_round(game_board, player));
+    }
+}
+
+/**
+ * Return the pixel position of the centre of a hex, flat-top with north pointing up.
+ * https://www.redblobgames.com/grids/hexagons/#hex-to-pixel
+ */
+fn hex_center(max_coord: i32, (q, r): (i32, i32)) -> (f64, f64) {
+    let x = HEX_SIZE * 1.5 * (q + max_coord) as f64;
+    let y = HEX_SIZE * 3f64.sqrt() * (r as f64 + q as f64 / 2.0 + max_coord as f64);
+    return (x + MARGIN, y + MARGIN);
+}
+
+/**
+ * Return the "x,y x,y ..." corners of a hex for an svg polygon.
+ */
+fn hex_corners(max_coord: i32, coords: (i32, i32)) -> String {
+    let (x, y) = hex_center(max_coord, coords);
+    let corners: Vec<String> = (0..6).map(|corner| {
+        let angle = (60.0 * corner as f64).to_radians();
+        format!("{:.1},{:.1}", x + HEX_SIZE * angle.cos(), y + HEX_SIZE * angle.sin())
+    }).collect();
+    return corners.join(" ");
+}
+
+fn picture_size(max_coord: i32) -> (f64, f64) {
+    let width = HEX_SIZE * 1.5 * (2 * max_coord) as f64 + 2.0 * MARGIN;
+    let height = HEX_SIZE * 3f64.sqrt() * (2 * max_coord) as f64 + 2.0 * MARGIN;
+    return (width, height);
+}
+
+fn tank_type_name(player: &Player) -> &'static str {
+    return match player.get_tank_type() {
+        crate::player::tank_type::heavy => "heavy",
+        crate::player::tank_type::scout => "scout",
+        crate::player::tank_type::tank => "tank",
+        crate::player::tank_type::error => "unknown",
+    };
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::svg_export::*;
+    use crate::player::tank_type;
+    use crate::game::{EnemiesPos, EnemyTracker, TeamColor};
+
+    #[test]
+    fn test_render_round_with_shot() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 100 as u32, 3, tank_type::tank);
+        player.calibrate(0, 0, 0);
+        let game_board: board = board::initialize(3);
+
+        let mut exporter: SvgExporter = SvgExporter::initialize("unused");
+        exporter.record_position((0, 1, -1));
+        exporter.record_position((0, 0, 0));
+        exporter.record_shots(1, vec![("SHOOT N-NE".to_string(), (0, 0, 0))]);
+
+        let svg = exporter.render_round(&game_board, &player);
+
+        assert!(svg.starts_with("<svg"), "The picture should be an svg document.");
+        assert_eq!(19, svg.matches("<polygon").count(), "A board with side 3 has 19 hexes.");
+        assert_eq!(2, svg.matches("<polyline").count(), "The picture should have the tank path and one shot.");
+        assert!(svg.contains("stroke=\"#ff7f0e\" stroke-width=\"2\" stroke-dasharray=\"4 2\" stroke-opacity=\"1.0\""), "The shot of the current round should be opaque.");
+    }
+
+    #[test]
+    fn test_render_threat_heat_map() {
+        let player: Player = Player::initialize_player("R".to_string(), 100 as u32, 3, tank_type::tank);
+        let game_board: board = board::initialize(3);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, 0, 0, TeamColor::B, 1, None));
+        tracker.set_max_moves(TeamColor::B, 0);
+
+        let mut exporter: SvgExporter = SvgExporter::initialize("unused");
+        exporter.record_threat(ThreatMap::build(&tracker, &game_board, 1));
+        let svg = exporter.render_round(&game_board, &player);
+
+        assert_eq!(19 + 18, svg.matches("<polygon").count(), "Every hex but B's own is within its reach and should be tinted.");
+    }
+}
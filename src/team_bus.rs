@@ -0,0 +1,446 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::os::fd::IntoRawFd;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::clock::Clock;
+
+/**
+ * Behaviour of a simulated team channel.
+ *  - latency_ms: Time between a message being written and it being readable by the teammates.
+ *  - drop_rate: Probability, between 0 and 1, that a message is lost for a teammate.
+ *  - reorder_rate: Probability, between 0 and 1, that a message is held back so later messages overtake it.
+ *  - seed: Seed of the random generator, the same seed drops and reorders the same messages.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct TeamBusConfig {
+    pub latency_ms: u64,
+    pub drop_rate: f64,
+    pub reorder_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for TeamBusConfig {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            drop_rate: 0.0,
+            reorder_rate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+/// Message written on the bus, waiting to be delivered to one robot.
+struct PendingMessage {
+    deliver_at: u64,
+    to: usize,
+    line: Vec<u8>,
+}
+
+struct BusState {
+    rng: StdRng,
+    now_ms: u64,
+    pending: Vec<PendingMessage>,
+    inboxes: Vec<VecDeque<u8>>,
+    connected: Vec<bool>,
+    busy: Vec<bool>,              // The reader returned lines that its robot may not have handled yet.
+    clocks: HashMap<usize, Option<u64>>, // Clock id -> time its robot sleeps until, None while the robot runs.
+    next_clock: usize,
+    closed: bool,
+}
+
+/**
+ * Simulated side channel of one team, used to test team features offline. In a real game each robot of the team reads
+ * its teammates on fd 3 and writes to them on fd 4. Here each robot connects to the bus instead, either with a reader
+ * and a writer that go in place of those descriptors (connect), or with real descriptors given to the robot like
+ * --team-in and --team-out (connect_fds).
+ *
+ * Every line written by a robot is delivered to all the other robots of the bus, after the configured latency and with
+ * the configured drops and reordering. Each team should have its own bus, teams don't hear each other.
+ *
+ * Time on the bus is virtual, it only moves with advance or with the clocks of the robots (see BusClock), so tests don't
+ * depend on how fast the machine is.
+ */
+#[derive(Clone)]
+pub struct TeamBus {
+    config: TeamBusConfig,
+    state: Arc<(Mutex<BusState>, Condvar)>,
+}
+
+/// Reading end of a robot, gives the lines written by its teammates.
+pub struct BusReader {
+    id: usize,
+    state: Arc<(Mutex<BusState>, Condvar)>,
+}
+
+/// Writing end of a robot, sends each complete line to its teammates.
+pub struct BusWriter {
+    id: usize,
+    config: TeamBusConfig,
+    state: Arc<(Mutex<BusState>, Condvar)>,
+    buffer: Vec<u8>,
+}
+
+/**
+ * Virtual clock of one robot of the bus. The robots of a bus run in lockstep: sleeping only returns once every robot
+ * with a clock is sleeping and every reader handled the messages delivered so far, then the time of the bus moves to
+ * the next delivery or the first robot to wake up. Every robot therefore sees the same messages at the same virtual
+ * time, whatever the scheduling of the threads.
+ *
+ * Readers must be read (by a TeamListener for instance) or dropped, a reader left with messages stops the clock.
+ * Readers behind connect_fds are not waited for, use advance with them. Give the bus some latency: a message delivered
+ * as soon as it is written reaches the robots that wake up at the same time or not depending on who runs first.
+ */
+pub struct BusClock {
+    id: usize,
+    state: Arc<(Mutex<BusState>, Condvar)>,
+}
+
+impl TeamBus {
+
+    /**
+     * Create a team bus with no robot connected, its time starts at 0.
+     */
+    pub fn new(config: TeamBusConfig) -> Self {
+        let state = BusState {
+            rng: StdRng::seed_from_u64(config.seed),
+            now_ms: 0,
+            pending: Vec::new(),
+            inboxes: Vec::new(),
+            connected: Vec::new(),
+            busy: Vec::new(),
+            clocks: HashMap::new(),
+            next_clock: 0,
+            closed: false,
+        };
+        Self {
+            config: config,
+            state: Arc::new((Mutex::new(state), Condvar::new())),
+        }
+    }
+
+    /**
+     * Connect a robot to the bus. Robots only receive the lines written after they connected.
+     */
+    pub fn connect(&self) -> (BusReader, BusWriter) {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.inboxes.push(VecDeque::new());
+        state.connected.push(true);
+        state.busy.push(false);
+        let id = state.inboxes.len() - 1;
+
+        return (
+            BusReader { id: id, state: Arc::clone(&self.state) },
+            BusWriter { id: id, config: self.config, state: Arc::clone(&self.state), buffer: Vec::new() },
+        );
+    }
+
+    /**
+     * Connect a robot to the bus through file descriptors, as the robot gets them from the process that starts it.
+     *
+     *  - Returns: The descriptor the robot reads its teammates from and the one it writes to them on, to be given with
+     *    --team-in and --team-out. The robot owns them, they are closed when its listener and broadcaster are dropped.
+     */
+    pub fn connect_fds(&self) -> io::Result<(i32, i32)> {
+        let (mut reader, mut writer) = self.connect();
+        let (team_in, mut to_robot) = io::pipe()?;
+        let (mut from_robot, team_out) = io::pipe()?;
+
+        // The descriptors are pumped from and to the bus, the pumps stop once the bus or the robot closes its side.
+        thread::spawn(move || { let _ = io::copy(&mut reader, &mut to_robot); });
+        thread::spawn(move || { let _ = io::copy(&mut from_robot, &mut writer); });
+
+        return Ok((team_in.into_raw_fd(), team_out.into_raw_fd()));
+    }
+
+    /**
+     * Give a virtual clock to a robot of the bus. Every clock should be created before the robots start, so none of
+     * them sleeps before the others joined.
+     */
+    pub fn clock(&self) -> BusClock {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let id = state.next_clock;
+        state.next_clock += 1;
+        state.clocks.insert(id, None);
+        return BusClock { id: id, state: Arc::clone(&self.state) };
+    }
+
+    /**
+     * Move the time of the bus forward by 'ms' milliseconds and deliver the messages that are due.
+     */
+    pub fn advance(&self, ms: u64) {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.now_ms += ms;
+        let now = state.now_ms;
+        state.deliver_due(now);
+        signal.notify_all();
+    }
+
+    /**
+     * Number of messages written that did not reach their robot yet.
+     */
+    pub fn in_flight(&self) -> usize {
+        let (lock, _) = &*self.state;
+        return lock.lock().unwrap().pending.len();
+    }
+
+    /**
+     * Close the bus. Nothing can be written anymore, the messages already written are delivered in order and readers
+     * then get the end of the input, so listener threads stop on their own.
+     */
+    pub fn close(&self) {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.closed = true;
+        state.deliver_due(u64::MAX);
+        signal.notify_all();
+    }
+}
+
+impl BusState {
+
+    /// Move the messages that are due to the inboxes of their robots, in delivery order.
+    fn deliver_due(&mut self, now: u64) {
+        self.pending.sort_by_key(|message| message.deliver_at);
+        while !self.pending.is_empty() && self.pending[0].deliver_at <= now {
+            let message = self.pending.remove(0);
+            if self.connected[message.to] {
+                self.inboxes[message.to].extend(message.line);
+            }
+        }
+    }
+
+    /// True when every reader handled the messages delivered to it.
+    fn is_quiet(&self) -> bool {
+        return (0..self.inboxes.len()).all(|id| !self.connected[id] || (self.inboxes[id].is_empty() && !self.busy[id]));
+    }
+
+    /// Move the time to the next delivery or wake up when every robot with a clock sleeps and every reader handled
+    /// its messages. Returns true if the time moved.
+    fn step_clock(&mut self) -> bool {
+        if self.clocks.is_empty() || self.clocks.values().any(|wake| wake.is_none()) || !self.is_quiet() {
+            return false;
+        }
+
+        let next_wake = self.clocks.values().filter_map(|wake| *wake).min().unwrap_or(u64::MAX);
+        let next_delivery = self.pending.iter().map(|message| message.deliver_at).min().unwrap_or(u64::MAX);
+        let now = next_wake.min(next_delivery);
+        if now <= self.now_ms {
+            return false; // A robot is waking up, it runs before the time moves again.
+        }
+        self.now_ms = now;
+        self.deliver_due(now);
+        return true;
+    }
+}
+
+impl Clock for BusClock {
+
+    fn now_ms(&self) -> u64 {
+        let (lock, _) = &*self.state;
+        return lock.lock().unwrap().now_ms;
+    }
+
+    /**
+     * Sleep until the time of the bus reached now + 'ms', see BusClock for when the time moves.
+     */
+    fn sleep_ms(&self, ms: u64) {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let wake = state.now_ms + ms;
+        state.clocks.insert(self.id, Some(wake));
+
+        while state.now_ms < wake || !state.is_quiet() {
+            if state.step_clock() {
+                signal.notify_all();
+            } else {
+                state = signal.wait(state).unwrap();
+            }
+        }
+        state.clocks.insert(self.id, None);
+    }
+}
+
+impl Drop for BusClock {
+
+    /// The robot stopped, the others don't wait for it anymore.
+    fn drop(&mut self) {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.clocks.remove(&self.id);
+        state.step_clock();
+        signal.notify_all();
+    }
+}
+
+impl Read for BusReader {
+
+    /**
+     * Block until teammates' messages are available, or return 0 once the bus is closed and nothing is left to deliver.
+     */
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        // This reader handled its messages, it may be the last one the clocks were waiting for.
+        state.busy[self.id] = false;
+        state.step_clock();
+        signal.notify_all();
+
+        loop {
+            let inbox = &mut state.inboxes[self.id];
+            if !inbox.is_empty() {
+                let count = buf.len().min(inbox.len());
+                for (index, byte) in inbox.drain(..count).enumerate() {
+                    buf[index] = byte;
+                }
+                state.busy[self.id] = true;
+                return Ok(count);
+            }
+
+            if state.closed {
+                return Ok(0);
+            }
+
+            if state.step_clock() {
+                signal.notify_all();
+            } else {
+                state = signal.wait(state).unwrap();
+            }
+        }
+    }
+}
+
+impl Drop for BusReader {
+
+    /// Nothing is delivered to a robot that stopped reading.
+    fn drop(&mut self) {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.connected[self.id] = false;
+        state.busy[self.id] = false;
+        state.inboxes[self.id].clear();
+        state.step_clock();
+        signal.notify_all();
+    }
+}
+
+impl Write for BusWriter {
+
+    /**
+     * Buffer the bytes and send every complete line to the other robots of the bus.
+     */
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (lock, signal) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        if state.closed {
+            return Err(io::Error::from(io::ErrorKind::BrokenPipe));
+        }
+
+        self.buffer.extend_from_slice(buf);
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let now = state.now_ms;
+
+            for to in 0..state.inboxes.len() {
+                if to == self.id || !state.connected[to] || state.rng.gen_bool(self.config.drop_rate.clamp(0.0, 1.0)) {
+                    continue;
+                }
+
+                // A held back message waits for a few more latencies, messages written right after it arrive first.
+                let mut delay = self.config.latency_ms;
+                if state.rng.gen_bool(self.config.reorder_rate.clamp(0.0, 1.0)) {
+                    delay += 3 * self.config.latency_ms.max(1);
+                }
+
+                state.pending.push(PendingMessage { deliver_at: now + delay, to: to, line: line.clone() });
+            }
+            state.deliver_due(now);
+        }
+
+        signal.notify_all();
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::team_bus::*;
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn test_lines_reach_every_other_robot() {
+        let bus = TeamBus::new(TeamBusConfig::default());
+        let (_red_reader, mut red_writer) = bus.connect();
+        let (green_reader, _green_writer) = bus.connect();
+        let (blue_reader, _blue_writer) = bus.connect();
+
+        red_writer.write_all(b"hello\n").unwrap();
+        bus.close();
+
+        let green_lines: Vec<String> = BufReader::new(green_reader).lines().map(|line| line.unwrap()).collect();
+        let blue_lines: Vec<String> = BufReader::new(blue_reader).lines().map(|line| line.unwrap()).collect();
+        assert_eq!(vec!["hello".to_string()], green_lines, "G should get the line written by R.");
+        assert_eq!(vec!["hello".to_string()], blue_lines, "B should get the line written by R.");
+    }
+
+    #[test]
+    fn test_reordering_is_seeded() {
+        let config = TeamBusConfig { latency_ms: 1, drop_rate: 0.0, reorder_rate: 0.5, seed: 7 };
+        let order = |config: TeamBusConfig| -> Vec<String> {
+            let bus = TeamBus::new(config);
+            let (_reader, mut writer) = bus.connect();
+            let (teammate_reader, _teammate_writer) = bus.connect();
+            for index in 0..10 {
+                writer.write_all(format!("{}\n", index).as_bytes()).unwrap();
+            }
+            bus.close();
+            return BufReader::new(teammate_reader).lines().map(|line| line.unwrap()).collect();
+        };
+
+        let first = order(config);
+        assert_eq!(10, first.len(), "Reordering should not lose messages.");
+        assert_eq!(first, order(config), "The same seed should reorder the same messages.");
+    }
+
+    #[test]
+    fn test_messages_wait_for_the_latency() {
+        let bus = TeamBus::new(TeamBusConfig { latency_ms: 10, drop_rate: 0.0, reorder_rate: 0.0, seed: 0 });
+        let (_red_reader, mut red_writer) = bus.connect();
+        let (_green_reader, _green_writer) = bus.connect();
+
+        red_writer.write_all(b"hello\n").unwrap();
+        bus.advance(9);
+        assert_eq!(1, bus.in_flight(), "The line should still be on its way before the latency.");
+        bus.advance(1);
+        assert_eq!(0, bus.in_flight(), "The line should be delivered once the latency passed.");
+    }
+
+    #[test]
+    fn test_clocks_run_in_lockstep() {
+        let bus = TeamBus::new(TeamBusConfig::default());
+        let fast = bus.clock();
+        let slow = bus.clock();
+
+        let sleeper = thread::spawn(move || {
+            slow.sleep_ms(30);
+            return slow.now_ms();
+        });
+        fast.sleep_ms(10);
+        assert_eq!(10, fast.now_ms(), "The first robot to wake up should wake up on time.");
+        drop(fast);
+
+        assert_eq!(30, sleeper.join().unwrap(), "The other robot should wake up at its own time.");
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use crate::CoordSystem::{hex_distance, TANK_LETTERS};
+
+/// Health every tank starts with.
+pub const ENEMY_START_HEALTH: u8 = 2;
+
+/// Enemies not seen for more than this many rounds are not considered as targets.
+pub const TARGET_MEMORY_ROUNDS: u32 = 3;
+
+/// Claims older than this many rounds are forgotten, the teammate probably lost the target.
+pub const CLAIM_MEMORY_ROUNDS: u32 = 1;
+
+/**
+ * What the team knows about one enemy.
+ *  - position, seen_round: Last known position of the enemy and when it was seen there.
+ *  - estimated_health: Start health minus the shots of the team that ended on its last known position. The enemy may have
+ *    driven away before the shot, so it is an optimistic estimate: the enemy may have more health left.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct EnemyTarget {
+    pub colour: char,
+    pub position: (i32, i32, i32),
+    pub seen_round: u32,
+    pub estimated_health: u8,
+}
+
+/**
+ * Coordinates targets between the tanks of the team without negotiation round-trips:
+ *   - Every tank picks the weakest enemy, then the closest one, then the first one in colour order (R, O, Y, G, B, V).
+ *   - The pick is published as a target claim. Tanks focus fire on the same enemy, unless the enemy is down to its last
+ *     health point and another teammate already claimed it, then one shot is enough and the others pick another target.
+ *   - When two tanks claim the same enemy on the same round, the tank first in colour order keeps the claim.
+ */
+pub struct TargetCoordinator {
+    own_colour: char,
+    enemies: HashMap<char, EnemyTarget>,
+    claims: HashMap<char, (char, u32)>, // enemy -> (tank that claimed it, round of the claim)
+    current_target: Option<char>,
+}
+
+pub trait target_coordination {
+    fn initialize(own_colour: char) -> Self;
+    fn record_sighting(&mut self, enemy: char, position: (i32, i32, i32), round: u32);
+    fn record_claim(&mut self, claimer: char, enemy: char, round: u32);
+    fn record_shot(&mut self, enemy: char, position: (i32, i32, i32)) -> bool;
+    fn choose_target(&mut self, own_position: (i32, i32, i32), round: u32) -> Option<EnemyTarget>;
+    fn get_current_target(&self) -> Option<EnemyTarget>;
+    fn get_enemy(&self, enemy: char) -> Option<EnemyTarget>;
+}
+
+impl target_coordination for TargetCoordinator {
+
+    /**
+     * Initialize the coordinator for the tank with the given colour.
+     */
+    fn initialize(own_colour: char) -> Self {
+        Self {
+            own_colour: own_colour,
+            enemies: HashMap::new(),
+            claims: HashMap::new(),
+            current_target: None,
+        }
+    }
+
+    /**
+     * Record where an enemy was seen, by us or by a teammate. Older sightings than the one we know are ignored.
+     */
+    fn record_sighting(&mut self, enemy: char, position: (i32, i32, i32), round: u32) {
+        if !TANK_LETTERS.contains(&enemy) || enemy == self.own_colour {
+            return;
+        }
+
+        let known = self.enemies.entry(enemy).or_insert(EnemyTarget {
+            colour: enemy,
+            position: position,
+            seen_round: round,
+            estimated_health: ENEMY_START_HEALTH,
+        });
+
+        if round >= known.seen_round {
+            known.position = position;
+            known.seen_round = round;
+        }
+    }
+
+    /**
+     * Record that a tank of the team claimed an enemy. On the same round, the tank first in colour order keeps the claim.
+     */
+    fn record_claim(&mut self, claimer: char, enemy: char, round: u32) {
+        let replace = match self.claims.get(&enemy) {
+            Some((current_claimer, current_round)) => {
+                round > *current_round || (round == *current_round && colour_rank(claimer) < colour_rank(*current_claimer))
+            },
+            None => true
+        };
+
+        if replace {
+            self.claims.insert(enemy, (claimer, round));
+        }
+    }
+
+    /**
+     * Record a shot of the team, by us or by a teammate, that ended on the given position. The estimated health of the
+     * enemy goes down by one if that is where it was last seen, a shot anywhere else missed it.
+     *
+     *  - Returns: true if the shot was counted.
+     */
+    fn record_shot(&mut self, enemy: char, position: (i32, i32, i32)) -> bool {
+        return match self.enemies.get_mut(&enemy) {
+            Some(known) if known.position == position => {
+                known.estimated_health = known.estimated_health.saturating_sub(1);
+                true
+            },
+            _ => false
+        };
+    }
+
+    /**
+     * Choose the enemy to shoot at and claim it. See TargetCoordinator for the rules.
+     *
+     *  - own_position: Our position, used to find the closest enemy.
+     *  - round: The current round, enemies and claims that are too old are ignored.
+     *
+     *  - Returns: The chosen enemy, or None if no enemy is worth shooting at.
+     */
+    fn choose_target(&mut self, own_position: (i32, i32, i32), round: u32) -> Option<EnemyTarget> {
+        let mut candidates: Vec<EnemyTarget> = self.enemies.values()
+            .filter(|enemy| enemy.estimated_health > 0)
+            .filter(|enemy| round.saturating_sub(enemy.seen_round) <= TARGET_MEMORY_ROUNDS)
+            .filter(|enemy| !self.finished_by_teammate(enemy, round))
+            .cloned()
+            .collect();
+
+        candidates.sort_by_key(|enemy| (enemy.estimated_health, hex_distance(own_position, enemy.position), colour_rank(enemy.colour)));
+
+        let target = candidates.first().cloned();
+        self.current_target = target.map(|enemy| enemy.colour);
+
+        if let Some(enemy) = target {
+            self.record_claim(self.own_colour, enemy.colour, round);
+        }
+        return target;
+    }
+
+    /**
+     * Return the target chosen on the last call to choose_target.
+     */
+    fn get_current_target(&self) -> Option<EnemyTarget> {
+        return self.current_target.and_then(|enemy| self.get_enemy(enemy));
+    }
+
+    /**
+     * Return what we know about an enemy.
+     */
+    fn get_enemy(&self, enemy: char) -> Option<EnemyTarget> {
+        return self.enemies.get(&enemy).cloned();
+    }
+}
+
+impl TargetCoordinator {
+
+    /**
+     * True if the enemy is down to its last health point and a teammate that keeps the claim over us is already on it.
+     */
+    fn finished_by_teammate(&self, enemy: &EnemyTarget, round: u32) -> bool {
+        if enemy.estimated_health > 1 {
+            return false;
+        }
+
+        return match self.claims.get(&enemy.colour) {
+            Some((claimer, claim_round)) => {
+                *claimer != self.own_colour
+                    && round.saturating_sub(*claim_round) <= CLAIM_MEMORY_ROUNDS
+                    && !(*claim_round == round && colour_rank(self.own_colour) < colour_rank(*claimer))
+            },
+            None => false
+        };
+    }
+}
+
+/**
+ * Position of a colour in the deterministic tie-break order R, O, Y, G, B, V.
+ */
+pub fn colour_rank(colour: char) -> usize {
+    return TANK_LETTERS.iter().position(|letter| *letter == colour).unwrap_or(TANK_LETTERS.len());
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::team_coordination::*;
+
+    #[test]
+    fn test_choose_weakest_then_closest() {
+        let mut coordinator: TargetCoordinator = TargetCoordinator::initialize('G');
+        coordinator.record_sighting('R', (3, -3, 0), 5);
+        coordinator.record_sighting('O', (1, 0, -1), 5);
+        coordinator.record_sighting('Y', (4, -1, -3), 5);
+
+        assert_eq!('O', coordinator.choose_target((0, 0, 0), 5).unwrap().colour, "With the same health, the closest enemy should be chosen.");
+
+        assert!(!coordinator.record_shot('Y', (4, -2, -2)), "A shot next to Y missed it.");
+        assert_eq!('O', coordinator.choose_target((0, 0, 0), 5).unwrap().colour, "A missed shot should not change the health estimate.");
+
+        assert!(coordinator.record_shot('Y', (4, -1, -3)), "A shot on the hex Y was seen on should be counted.");
+        assert_eq!('Y', coordinator.choose_target((0, 0, 0), 5).unwrap().colour, "The weakest enemy should be chosen first.");
+    }
+
+    #[test]
+    fn test_skip_enemy_finished_by_teammate() {
+        let mut coordinator: TargetCoordinator = TargetCoordinator::initialize('G');
+        coordinator.record_sighting('R', (1, -1, 0), 5);
+        coordinator.record_sighting('O', (3, 0, -3), 5);
+        coordinator.record_shot('R', (1, -1, 0));
+
+        coordinator.record_claim('B', 'R', 5);
+        assert_eq!('R', coordinator.choose_target((0, 0, 0), 5).unwrap().colour, "G comes before B in colour order, so G keeps R.");
+
+        coordinator.record_claim('O', 'R', 5);
+        assert_eq!('O', coordinator.choose_target((0, 0, 0), 5).unwrap().colour, "O claimed R, which has one health left, G should not waste a shot on it.");
+    }
+
+    #[test]
+    fn test_ignore_old_sightings() {
+        let mut coordinator: TargetCoordinator = TargetCoordinator::initialize('G');
+        coordinator.record_sighting('R', (1, -1, 0), 1);
+
+        assert!(coordinator.choose_target((0, 0, 0), 1 + TARGET_MEMORY_ROUNDS + 1).is_none(), "An enemy not seen for a long time should not be a target.");
+    }
+}
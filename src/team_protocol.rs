@@ -0,0 +1,147 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Version of the team protocol. Messages from robots running another version are rejected instead of being misread.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/**
+ * Envelope of every message sent between teammates. Each message is one line of json on the comms channel.
+ *
+ *  - version: The protocol version, see PROTOCOL_VERSION.
+ *  - sender: The colour of the robot that sent the message.
+ *  - round: The round the message was sent on.
+ *  - seq: Sequence number of the message, increased by one by the sender for each message it sends.
+ *  - message: The content of the message.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamEnvelope {
+    pub version: u32,
+    pub sender: char,
+    pub round: u32,
+    pub seq: u64,
+    pub message: TeamMessage,
+}
+
+/**
+ * Messages teammates can exchange. Positions are absolute (q, r, s) board coordinates.
+ *
+ *  - Scan: A scan with the pose it was made from, the entries are relative to that pose.
+ *  - Pose: Where the sender is and where it is facing.
+ *  - EnemySighting: An enemy the sender saw.
+ *  - TargetClaim: The sender is going to shoot at this enemy.
+ *  - Shot: The sender fired a shot that ended on the last position it knew of this enemy.
+ *  - Intention: The tiles the sender plans to drive through.
+ *  - Health: The health the sender has left.
+ *  - RoleProposal: Before the game starts, the role the sender would like to play (T, S or H).
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TeamMessage {
+    Scan { step: u32, position: (i32, i32, i32), facing: String, scan: Vec<String> },
+    Pose { position: (i32, i32, i32), facing: String },
+    EnemySighting { enemy: char, position: (i32, i32, i32), seen_round: u32 },
+    TargetClaim { enemy: char, position: (i32, i32, i32) },
+    Shot { enemy: char, position: (i32, i32, i32) },
+    Intention { path: Vec<(i32, i32, i32)> },
+    Health { health: u8 },
+    RoleProposal { role: char },
+}
+
+#[derive(Debug)]
+pub enum CommsError {
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    Empty,
+}
+
+impl fmt::Display for CommsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommsError::Json(error) => write!(f, "message is not a valid team message: {}", error),
+            CommsError::UnsupportedVersion(version) => write!(f, "protocol version {} is not supported, expected version {}", version, PROTOCOL_VERSION),
+            CommsError::Empty => write!(f, "message is empty"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CommsError {
+    fn from(error: serde_json::Error) -> Self {
+        CommsError::Json(error)
+    }
+}
+
+impl TeamEnvelope {
+
+    /**
+     * Wrap a message in an envelope with the current protocol version.
+     */
+    pub fn new(sender: char, round: u32, seq: u64, message: TeamMessage) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            sender: sender,
+            round: round,
+            seq: seq,
+            message: message,
+        }
+    }
+}
+
+/**
+ * Encode a message as one line of json, without the trailing new line.
+ */
+pub fn encode_message(envelope: &TeamEnvelope) -> String {
+    // Serializing plain data to a string can't fail.
+    return serde_json::to_string(envelope).expect("team messages are always serializable");
+}
+
+/**
+ * Decode one line received on the comms channel.
+ *   - The version is checked first, so a message from another version is reported as such and not as a bad message.
+ *   - Unknown message types and unknown or missing fields are rejected.
+ */
+pub fn decode_message(line: &str) -> Result<TeamEnvelope, CommsError> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Err(CommsError::Empty);
+    }
+
+    let value: serde_json::Value = serde_json::from_str(line)?;
+    if let Some(version) = value.get("version").and_then(|version| version.as_u64()) {
+        if version as u32 != PROTOCOL_VERSION {
+            return Err(CommsError::UnsupportedVersion(version as u32));
+        }
+    }
+    return Ok(serde_json::from_value(value)?);
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::team_protocol::*;
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let envelope = TeamEnvelope::new('G', 4, 7, TeamMessage::EnemySighting { enemy: 'R', position: (1, -2, 1), seen_round: 4 });
+        let line = encode_message(&envelope);
+
+        assert!(!line.contains('\n'), "A message should fit in a single line.");
+        assert_eq!(envelope, decode_message(&line).unwrap(), "Decoding an encoded message should give the same message.");
+    }
+
+    #[test]
+    fn test_decode_rejects_other_versions() {
+        let line = "{\"version\":2,\"sender\":\"G\",\"round\":1,\"seq\":0,\"message\":{\"type\":\"health\",\"health\":1}}";
+        assert!(matches!(decode_message(line), Err(CommsError::UnsupportedVersion(2))), "A message from another protocol version should be rejected.");
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_messages() {
+        let unknown_type = "{\"version\":1,\"sender\":\"G\",\"round\":1,\"seq\":0,\"message\":{\"type\":\"dance\"}}";
+        let missing_field = "{\"version\":1,\"sender\":\"G\",\"round\":1,\"seq\":0,\"message\":{\"type\":\"health\"}}";
+
+        assert!(matches!(decode_message(unknown_type), Err(CommsError::Json(_))), "An unknown message type should be rejected.");
+        assert!(matches!(decode_message(missing_field), Err(CommsError::Json(_))), "A message with a missing field should be rejected.");
+        assert!(matches!(decode_message("SCAN {}"), Err(CommsError::Json(_))), "The old SCAN prefix format should be rejected.");
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::CoordSystem::{board, board_operations, hex_distance};
+use crate::move_manager::clear_shot;
+use crate::game::{EnemyTracker, TeamColor, ENEMY_SHOT_RANGE};
+
+/// Enemies not seen for more than this many rounds are too old to tell where they are, they are not a threat.
+pub const THREAT_MAX_AGE: u32 = 3;
+
+/**
+ * Threat of every hex of the board: the enemies that could shoot it next round. An enemy can shoot a hex if, from one
+ * of the hexes it can drive to by the end of next round (see EnemyTracker::reachable_this_round), the hex is within
+ * shot range and the shot doesn't go through a wall. A hit we took that could not be put on an enemy counts as one more
+ * enemy around its shooter cone (see EnemyTracker::unknown_shooters).
+ *
+ * The strategies use it to choose safe positions (safest) and as an extra cost in pathfinding (path_cost), the
+ * debug renderers draw it as a heat-map.
+ */
+pub struct ThreatMap {
+    threats: HashMap<(i32, i32, i32), Vec<TeamColor>>,
+    unknown: HashMap<(i32, i32, i32), u32>,
+}
+
+impl ThreatMap {
+
+    /**
+     * Build the threat map for the round after 'round', from the enemies seen in the last THREAT_MAX_AGE rounds.
+     */
+    pub fn build(tracker: &EnemyTracker, game_board: &board, round: u32) -> Self {
+        let mut threats: HashMap<(i32, i32, i32), Vec<TeamColor>> = HashMap::new();
+        let mut unknown: HashMap<(i32, i32, i32), u32> = HashMap::new();
+
+        for colour in tracker.get_colours() {
+            let recent = tracker.last_sighting(colour).map_or(false, |sighting| round.saturating_sub(sighting.get_seen_at_round()) <= THREAT_MAX_AGE);
+            if !recent {
+                continue;
+            }
+            for hex in shootable_hexes(game_board, &tracker.reachable_this_round(colour, round + 1, game_board)) {
+                threats.entry(hex).or_insert_with(Vec::new).push(colour);
+            }
+        }
+
+        for report in tracker.unknown_shooters(round, THREAT_MAX_AGE) {
+            for hex in shootable_hexes(game_board, &tracker.shooter_reachable_this_round(report, round + 1, game_board)) {
+                *unknown.entry(hex).or_insert(0) += 1;
+            }
+        }
+
+        return Self { threats, unknown };
+    }
+
+    /**
+     * Return the number of enemies that could shoot the hex next round, unknown shooters included.
+     */
+    pub fn get_threat(&self, position: (i32, i32, i32)) -> u32 {
+        let known = self.threats.get(&position).map_or(0, |enemies| enemies.len() as u32);
+        return known + self.unknown.get(&position).copied().unwrap_or(0);
+    }
+
+    /**
+     * Return the enemies of known colour that could shoot the hex next round.
+     */
+    pub fn get_enemies(&self, position: (i32, i32, i32)) -> &[TeamColor] {
+        return self.threats.get(&position).map_or(&[], |enemies| enemies.as_slice());
+    }
+
+    /**
+     * Return the highest threat of the board.
+     */
+    pub fn get_max_threat(&self) -> u32 {
+        return self.threats.keys().chain(self.unknown.keys()).map(|hex| self.get_threat(*hex)).max().unwrap_or(0);
+    }
+
+    /**
+     * Extra cost of driving onto a hex for board::find_weighted_path, 'weight' drives per enemy that could shoot it.
+     */
+    pub fn path_cost(&self, weight: u32) -> impl Fn((i32, i32, i32)) -> u32 + '_ {
+        return move |position| weight * self.get_threat(position);
+    }
+
+    /**
+     * Return the candidate with the lowest threat, the first one on a tie.
+     */
+    pub fn safest(&self, candidates: &[(i32, i32, i32)]) -> Option<(i32, i32, i32)> {
+        return candidates.iter().copied().min_by_key(|position| self.get_threat(*position));
+    }
+}
+
+/**
+ * Hexes within shot range of at least one of the origins, that a shot from there reaches without going through a wall.
+ */
+fn shootable_hexes(game_board: &board, origins: &[(i32, i32, i32)]) -> HashSet<(i32, i32, i32)> {
+    let max_coord = game_board.get_side_len() - 1;
+    let range = ENEMY_SHOT_RANGE as i32;
+    let mut hexes: HashSet<(i32, i32, i32)> = HashSet::new();
+
+    for origin in origins {
+        for dq in -range..=range {
+            for dr in -range..=range {
+                let hex = (origin.0 + dq, origin.1 + dr, origin.2 - dq - dr);
+                if hex.0.abs() > max_coord || hex.1.abs() > max_coord || hex.2.abs() > max_coord {
+                    continue;
+                }
+                if hex == *origin || hex_distance(*origin, hex) > ENEMY_SHOT_RANGE || hexes.contains(&hex) {
+                    continue;
+                }
+                if clear_shot(game_board, *origin, hex).is_some() {
+                    hexes.insert(hex);
+                }
+            }
+        }
+    }
+    return hexes;
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::threat_map::*;
+    use crate::CoordSystem::OWN_SCAN_CONFIDENCE;
+    use crate::game::EnemiesPos;
+
+    #[test]
+    fn test_threat_of_a_still_enemy() {
+        let mut game_board: board = board::initialize(5);
+        game_board.merge_tile((0, -1, 1), 'W', 1, 1, None, OWN_SCAN_CONFIDENCE);
+        let mut tracker = EnemyTracker::new();
+        tracker.record(EnemiesPos::new(0, 0, 0, TeamColor::B, 3, None));
+        tracker.set_max_moves(TeamColor::B, 0);
+
+        let threat = ThreatMap::build(&tracker, &game_board, 3);
+        assert_eq!(1, threat.get_threat((0, 1, -1)), "The hex next to B can be shot.");
+        assert_eq!(0, threat.get_threat((0, -2, 2)), "The wall blocks the straight shot two hexes north.");
+        assert_eq!(0, threat.get_threat((0, 3, -3)), "Three hexes away is out of range.");
+        assert_eq!(Some((0, 3, -3)), threat.safest(&[(0, 1, -1), (0, 3, -3)]), "The hex out of range is the safest.");
+
+        tracker.record(EnemiesPos::new(1, 0, -1, TeamColor::R, 3, None));
+        tracker.set_max_moves(TeamColor::R, 0);
+        let threat = ThreatMap::build(&tracker, &game_board, 3);
+        assert_eq!(2, threat.get_threat((0, 1, -1)), "Both enemies can shoot the hex between them.");
+        assert_eq!(2, threat.get_max_threat(), "The highest threat is two enemies.");
+        assert!(ThreatMap::build(&tracker, &game_board, 3 + THREAT_MAX_AGE + 1).get_max_threat() == 0, "Old sightings are not a threat.");
+    }
+}
@@ -0,0 +1,357 @@
+use serde::Deserialize;
+
+use crate::CoordSystem::{board_operations, direction_vector, hex_distance, scan_area};
+use crate::action_manager::playerOutput;
+use crate::fire_guard::shot_path;
+use crate::move_manager::{clear_shot, construct_shoot_move, NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST, SOUTH_WEST};
+use crate::player::{Player, PlayerState, tank_type};
+use crate::scan_parser::GetScanReturn;
+use crate::strategy::{Command, CommandResult, GameView, Strategy};
+
+/// Enemies further than this many tiles are not a threat.
+const THREAT_RANGE: u32 = 3;
+
+/**
+ * Weights of the considerations, the score of a command is the weighted sum of its considerations.
+ *  - information: How much unknown or old map the command would reveal.
+ *  - threat: How exposed to enemies the tank would be after the command, it is subtracted.
+ *  - hit: Chance that a shot hits an enemy, higher for the enemy the team focuses on.
+ *  - position: How much better the tile the tank ends on is, closer to the centre is better.
+ */
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UtilityWeights {
+    pub information: f32,
+    pub threat: f32,
+    pub hit: f32,
+    pub position: f32,
+}
+
+impl Default for UtilityWeights {
+    fn default() -> Self {
+        Self {
+            information: 1.0,
+            threat: 1.0,
+            hit: 2.0,
+            position: 0.5,
+        }
+    }
+}
+
+/**
+ * A legal command with its score and the value of each consideration, between 0 and 1.
+ */
+#[derive(Debug, Clone)]
+pub struct ScoredCommand {
+    pub command: Command,
+    pub score: f32,
+    pub information: f32,
+    pub threat: f32,
+    pub hit: f32,
+    pub position: f32,
+}
+
+/**
+ * Utility AI: every step, every legal command is scored and the best one is played. The scores are written to stderr
+ * so the weights can be tuned from the logs of a game.
+ */
+pub struct UtilityStrategy {
+    weights: UtilityWeights,
+    last_scores: Vec<ScoredCommand>,
+}
+
+impl UtilityStrategy {
+    pub fn new(weights: UtilityWeights) -> Self {
+        Self {
+            weights: weights,
+            last_scores: Vec::new(),
+        }
+    }
+
+    /**
+     * Return the scores of the last step, best first.
+     */
+    pub fn get_last_scores(&self) -> &[ScoredCommand] {
+        return &self.last_scores;
+    }
+
+    /**
+     * Score every legal command, best first.
+     */
+    pub fn score_commands(&self, view: &GameView) -> Vec<ScoredCommand> {
+        let mut scored: Vec<ScoredCommand> = legal_commands(view).into_iter().map(|command| {
+            let information = information_gain(&command, view);
+            let threat = threat(&command, view);
+            let hit = hit_chance(&command, view);
+            let position = position_value(&command, view);
+            let base = match command {
+                Command::Skip => 0.05,
+                _ => 0.0,
+            };
+            let score = base + self.weights.information * information - self.weights.threat * threat
+                + self.weights.hit * hit + self.weights.position * position;
+
+            ScoredCommand { command, score, information, threat, hit, position }
+        }).collect();
+
+        // Stable sort, on a tie the first legal command wins.
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        return scored;
+    }
+}
+
+impl Strategy for UtilityStrategy {
+
+    fn name(&self) -> &str {
+        return "utility";
+    }
+
+    fn on_turn_start(&mut self, _view: &GameView) {}
+
+    fn next_action(&mut self, view: &GameView) -> Command {
+        self.last_scores = self.score_commands(view);
+
+        let scores: Vec<String> = self.last_scores.iter().map(|scored| format!(
+            "{}={:.2} [info {:.2} threat {:.2} hit {:.2} pos {:.2}]",
+            scored.command, scored.score, scored.information, scored.threat, scored.hit, scored.position
+        )).collect();
+        eprintln!("Utility scores (round {}, step {}): {}", view.player.get_rounds(), view.player.get_step_count(), scores.join(", "));
+
+        return self.last_scores.first().map(|scored| scored.command.clone()).unwrap_or(Command::End);
+    }
+
+    fn on_result(&mut self, _command: &Command, _result: &CommandResult, _player: &mut Player) {}
+}
+
+/**
+ * Enumerate the commands the player can send this step, given what is left of its budgets.
+ * Turns don't use a step, so they are only offered if the last action was not a turn.
+ * Shots are aimed from the scan only right after it, the scan is relative to the pose it was made from. Once the tank
+ * turned or drove, the enemies seen this round are aimed at from their position on the board.
+ */
+pub fn legal_commands(view: &GameView) -> Vec<Command> {
+    let player = view.player;
+    let mut commands: Vec<Command> = Vec::new();
+
+    let mut shots: Vec<String> = Vec::new();
+    if player.get_shoot_action_check() && matches!(view.last_action, playerOutput::SCAN) && player.get_scan_action_count() > 0 {
+        for enemy in view.scan.get_enemies().into_iter().filter(|enemy| *enemy <= 7) {
+            shots.push(construct_shoot_move(player.get_facing_direction(), enemy));
+        }
+    } else if player.get_shoot_action_check() && player.is_calibrated() {
+        for enemy in view.board.recent_enemies(player.get_colour().as_str(), player.get_rounds(), 0) {
+            shots.extend(clear_shot(view.board, player.get_position(), enemy.get_coords()));
+        }
+    }
+    for shot in shots {
+        let shot = Command::Shoot(shot);
+        if !commands.contains(&shot) {
+            commands.push(shot);
+        }
+    }
+    if player.get_scan_actions_check() {
+        commands.push(Command::Scan);
+    }
+    if player.get_drive_actions_check() && can_drive_forward(view) {
+        commands.push(Command::Drive);
+    }
+    if !matches!(view.last_action, playerOutput::TURN) {
+        let facing = player.get_facing_direction();
+        for direction in [NORTH, NORTH_EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, NORTH_WEST] {
+            if direction != facing.as_str() {
+                commands.push(Command::Turn(direction.to_string()));
+            }
+        }
+    }
+    commands.push(Command::Skip);
+    commands.push(Command::End);
+    return commands;
+}
+
+/**
+ * Before calibration the tank only drives right after a scan showed the tile in front is free. After calibration the
+ * board tells if the tile in front is inside the arena, not a wall and not likely to hold a tank.
+ */
+fn can_drive_forward(view: &GameView) -> bool {
+    let player = view.player;
+    if !player.is_calibrated() {
+        return matches!(view.last_action, playerOutput::SCAN) && !view.scan.get_walls().contains(&1) && !view.scan.get_enemies().contains(&1);
+    }
+
+    let (q, r, s) = ahead(player.get_position(), player.get_facing_direction().as_str());
+    return match view.board.get_tile(q, r, s) {
+        Some(tile) => tile.get_occupied_by() != 'W' && view.board.tank_probability(q, r, s, player.get_rounds()) < 0.5,
+        None => false,
+    };
+}
+
+fn ahead(position: (i32, i32, i32), facing: &str) -> (i32, i32, i32) {
+    let (dq, dr, ds) = direction_vector(facing);
+    return (position.0 + dq, position.1 + dr, position.2 + ds);
+}
+
+/// Pose of the tank after the command.
+fn pose_after(command: &Command, player: &Player) -> ((i32, i32, i32), String) {
+    let facing = player.get_facing_direction();
+    return match command {
+        Command::Drive => (ahead(player.get_position(), facing.as_str()), facing),
+        Command::Turn(direction) => (player.get_position(), direction.clone()),
+        _ => (player.get_position(), facing),
+    };
+}
+
+/**
+ * Share of the scan area that is unknown or was not seen this round. A scan reveals it now, a drive or a turn only
+ * prepares the next scan, so they get half of it.
+ */
+fn information_gain(command: &Command, view: &GameView) -> f32 {
+    let player = view.player;
+    let fresh_scan = matches!(view.last_action, playerOutput::SCAN);
+
+    if !player.is_calibrated() {
+        return match command {
+            Command::Scan if !fresh_scan => 1.0,
+            Command::Drive | Command::Turn(_) => 0.25,
+            _ => 0.0,
+        };
+    }
+
+    let (position, facing) = pose_after(command, player);
+    let area = scan_area(position, facing.as_str(), player.get_tank_type() == tank_type::scout);
+    let stale = area.iter().filter(|(q, r, s)| match view.board.get_tile(*q, *r, *s) {
+        Some(tile) => !tile.is_known() || tile.get_seen_round() < player.get_rounds(),
+        None => false,
+    }).count() as f32 / area.len() as f32;
+
+    return match command {
+        Command::Scan if !fresh_scan => stale,
+        Command::Drive | Command::Turn(_) => stale * 0.5,
+        _ => 0.0,
+    };
+}
+
+/**
+ * Exposure to the enemies seen on the board, from the tile the tank ends on. Closer and more recent sightings weigh more.
+ */
+fn threat(command: &Command, view: &GameView) -> f32 {
+    let player = view.player;
+    if !player.is_calibrated() {
+        return 0.0;
+    }
+
+    let (position, _) = pose_after(command, player);
+    let max_coord = view.board.get_side_len() - 1;
+    let mut threat = 0.0;
+
+    for q in -max_coord..=max_coord {
+        for r in -max_coord..=max_coord {
+            let distance = hex_distance(position, (q, r, -q - r));
+            if distance <= THREAT_RANGE {
+                let probability = view.board.tank_probability(q, r, -q - r, player.get_rounds());
+                threat += probability * (THREAT_RANGE + 1 - distance) as f32 / (THREAT_RANGE + 1) as f32;
+            }
+        }
+    }
+    return threat.min(1.0);
+}
+
+/**
+ * Chance a shot hits: the enemy was seen by a scan this step, or earlier this round. A shot at the enemy the team
+ * focuses on gets a bonus.
+ */
+fn hit_chance(command: &Command, view: &GameView) -> f32 {
+    let shot = match command {
+        Command::Shoot(shot) => shot,
+        _ => return 0.0,
+    };
+
+    let last_action_scan = matches!(view.last_action, playerOutput::SCAN);
+    let enemy = if last_action_scan {
+        let facing = view.player.get_facing_direction();
+        let positions = view.scan.get_scanned_positions();
+        view.scan.get_enemies().into_iter()
+            .find(|enemy| construct_shoot_move(facing.clone(), *enemy) == *shot)
+            .and_then(|enemy| positions.get(enemy).map(|point| point.get_occupied_by()))
+    } else {
+        let directions: Vec<String> = shot[6..].split('-').map(|direction| direction.to_string()).collect();
+        shot_path(view.player.get_position(), &directions).last()
+            .and_then(|(q, r, s)| view.board.get_tile(*q, *r, *s))
+            .map(|tile| tile.get_occupied_by())
+    };
+
+    let mut chance = if last_action_scan { 0.9 } else { 0.6 };
+    if let (Some(enemy), Some(target)) = (enemy, view.target) {
+        if enemy == target.colour {
+            chance += 0.1;
+        }
+    }
+    return chance;
+}
+
+/**
+ * How much closer to the centre of the arena a drive takes the tank, where it can reach the most enemies.
+ */
+fn position_value(command: &Command, view: &GameView) -> f32 {
+    let player = view.player;
+    if !matches!(command, Command::Drive) {
+        return 0.0;
+    }
+    if !player.is_calibrated() {
+        return 0.5;
+    }
+
+    let (position, _) = pose_after(command, player);
+    let before = hex_distance(player.get_position(), (0, 0, 0)) as f32;
+    let after = hex_distance(position, (0, 0, 0)) as f32;
+    return ((before - after + 1.0) / 2.0).clamp(0.0, 1.0);
+}
+
+#[cfg(test)]
+mod unit_test {
+    use crate::utility_ai::*;
+    use crate::game::EnemyTracker;
+    use crate::CoordSystem::board;
+    use crate::parser;
+    use crate::scan_parser::ScanResponse;
+
+    #[test]
+    fn test_scan_then_shoot() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 2, -2);
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+        let mut strategy = UtilityStrategy::new(UtilityWeights::default());
+
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::NONE, action_counter: 0, target: None };
+        assert_eq!(Command::Scan, strategy.next_action(&view), "With the whole map unknown the tank should scan first.");
+
+        player.add_scan_action();
+        player.add_step();
+        scan.scan_entry(parser::get_args("abcdeRghijk".to_string()));
+        game_board.update_board(&mut scan, player.get_position(), "N", 1, 1);
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::SCAN, action_counter: 1, target: None };
+        assert_eq!(Command::Shoot("SHOOT N-N".to_string()), strategy.next_action(&view), "An enemy just scanned should be shot.");
+        assert!(strategy.get_last_scores().iter().any(|scored| scored.command == Command::Drive && scored.threat > 0.0), "Driving towards the enemy should be a threat.");
+
+        let mut no_shooting = UtilityStrategy::new(UtilityWeights { hit: 0.0, ..UtilityWeights::default() });
+        assert_ne!(Command::Shoot("SHOOT N-N".to_string()), no_shooting.next_action(&view), "Without weight on hits the shot is not worth it.");
+    }
+
+    #[test]
+    fn test_no_stale_shot_after_a_turn() {
+        let mut player: Player = Player::initialize_player("R".to_string(), 10 as u32, 5, tank_type::tank);
+        player.calibrate(0, 2, -2);
+        let mut game_board: board = board::initialize(5);
+        let mut scan: ScanResponse = ScanResponse::initialize_scan_response();
+
+        player.add_scan_action();
+        player.add_step();
+        scan.scan_entry(parser::get_args("abcdeYghijk".to_string()));
+        game_board.update_board(&mut scan, player.get_position(), "N", 1, 1);
+        player.update_facing_direction("NE");
+        let view = GameView { player: &player, board: &game_board, scan: &scan, tracker: &EnemyTracker::new(), last_action: playerOutput::TURN, action_counter: 2, target: None };
+
+        let shots: Vec<Command> = legal_commands(&view).into_iter().filter(|command| matches!(command, Command::Shoot(_))).collect();
+        assert_eq!(vec![Command::Shoot("SHOOT N-N".to_string())], shots, "After the turn the enemy should be aimed at from the board, not from the scan made facing N.");
+    }
+}
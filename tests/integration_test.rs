@@ -1,38 +1,49 @@
-use lib::{self, player::{Player, PlayerState, tank_type}, scan_parser::{ScanResponse, GetScanReturn}, move_manager, parser};
-
-#[test]
-fn player_basic_commands(){
-    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 7, tank_type::tank);
-
-    let mut scan :ScanResponse = ScanResponse::initialize_scan_response();
-    let mut scan_string: Vec<String> = parser::get_args("abcdefghijk".to_string());
-    
-
-    player.update_facing_direction("NE");
-    scan.scan_entry(scan_string);
-    let mut possible_moves :Vec<String> = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
-
-    assert_eq!(possible_moves.get(0).unwrap(), "DRIVE N", "With player facing NE the first first possible index on position 'a' should be N.");
-
-    player.update_facing_direction("S");
-    scan_string = parser::get_args("abWdefWWijW".to_string());
-
-    scan.scan_entry(scan_string);
-
-    possible_moves = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
-
-    assert_eq!(possible_moves.len(), 2, "The scanned entry had only two possible moves but {} moves was returned.", possible_moves.len());
-    assert_eq!(possible_moves.get(1).unwrap(), "DRIVE S", "The second possible move on position 'b' should be DRIVE S but found {}.", possible_moves.get(1).unwrap());
-}
-
-#[test]
-fn player_scanning_walls(){
-    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 7, tank_type::tank);
-
-    let mut scan :ScanResponse = ScanResponse::initialize_scan_response();
-    let scan_string: Vec<String> = parser::get_args("WWWWWWWWWWW".to_string());
-
-    scan.scan_entry(scan_string);
-    let possible_moves :Vec<String> = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
-    assert_eq!(possible_moves.len(), 0, "All the scan entries are walls and therefore the possible moves should be empty but it has length of {}",possible_moves.len());
-}
\ No newline at end of file
+use lib::{self, player::{Player, PlayerState, tank_type}, scan_parser::{ScanResponse, GetScanReturn}, move_manager, parser, snapshot::GameSnapshot, game::TeamColor, CoordSystem::board_operations};
+
+#[test]
+fn player_basic_commands(){
+    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 7, tank_type::tank);
+
+    let mut scan :ScanResponse = ScanResponse::initialize_scan_response();
+    let mut scan_string: Vec<String> = parser::get_args("abcdefghijk".to_string());
+    
+
+    player.update_facing_direction("NE");
+    scan.scan_entry(scan_string);
+    let mut possible_moves :Vec<String> = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
+
+    assert_eq!(possible_moves.get(0).unwrap(), "DRIVE N", "With player facing NE the first first possible index on position 'a' should be N.");
+
+    player.update_facing_direction("S");
+    scan_string = parser::get_args("abWdefWWijW".to_string());
+
+    scan.scan_entry(scan_string);
+
+    possible_moves = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
+
+    assert_eq!(possible_moves.len(), 2, "The scanned entry had only two possible moves but {} moves was returned.", possible_moves.len());
+    assert_eq!(possible_moves.get(1).unwrap(), "DRIVE S", "The second possible move on position 'b' should be DRIVE S but found {}.", possible_moves.get(1).unwrap());
+}
+
+#[test]
+fn player_scanning_walls(){
+    let mut player :Player = Player::initialize_player("R".to_string(), 100 as u32, 7, tank_type::tank);
+
+    let mut scan :ScanResponse = ScanResponse::initialize_scan_response();
+    let scan_string: Vec<String> = parser::get_args("WWWWWWWWWWW".to_string());
+
+    scan.scan_entry(scan_string);
+    let possible_moves :Vec<String> = move_manager::construct_drive_moves(player.get_facing_direction(), &mut scan);
+    assert_eq!(possible_moves.len(), 0, "All the scan entries are walls and therefore the possible moves should be empty but it has length of {}",possible_moves.len());
+}
+#[test]
+fn player_starts_from_snapshot(){
+    let snapshot: GameSnapshot = GameSnapshot::load("tests/fixtures/round12_snapshot.json").unwrap();
+
+    assert_eq!(12, snapshot.player.get_rounds(), "The fixture starts on round 12.");
+    assert_eq!((2, -3, 1), snapshot.player.get_position(), "The fixture tank is on (2, -3, 1).");
+    assert_eq!("SE", snapshot.player.get_facing_direction(), "The fixture tank is facing SE.");
+    assert_eq!('W', snapshot.board.get_tile(2, -2, 0).unwrap().get_occupied_by(), "The fixture has a wall south of the tank.");
+    assert_eq!(0.25, snapshot.board.tank_probability(4, -2, -2, 12), "The enemy R was seen 2 rounds before the fixture round.");
+    assert_eq!(Some(10), snapshot.tracker.last_sighting(TeamColor::R).map(|sighting| sighting.get_seen_at_round()), "The tracker of the fixture saw R on round 10.");
+}
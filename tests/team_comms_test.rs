@@ -0,0 +1,128 @@
+use std::thread;
+use std::time::Duration;
+
+use lib::clock::Clock;
+use lib::comms::{self, TeamBroadcaster, TeamListener};
+use lib::parser::get_run_options;
+use lib::player::{Player, PlayerState, tank_type};
+use lib::role_negotiation;
+use lib::team_bus::{TeamBus, TeamBusConfig};
+use lib::team_coordination::{TargetCoordinator, target_coordination};
+use lib::team_protocol::{decode_message, TeamMessage};
+use lib::CoordSystem::{board, board_operations};
+
+/**
+ * Connect one robot of the team to the bus, as the robot does with the team descriptors.
+ */
+fn connect_robot(bus: &TeamBus, colour: char) -> (TeamListener, TeamBroadcaster) {
+    let (reader, writer) = bus.connect();
+    return (TeamListener::start(reader), TeamBroadcaster::new(colour, Some(Box::new(writer))));
+}
+
+/// Only bounds how long a test waits for a message that is already delivered, the bus itself runs on virtual time.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn team_negotiates_roles_over_the_bus(){
+    let bus = TeamBus::new(TeamBusConfig { latency_ms: 5, drop_rate: 0.0, reorder_rate: 0.3, seed: 1 });
+    let robots: Vec<(char, TeamListener, TeamBroadcaster)> = ['R', 'G', 'B'].iter().map(|colour| {
+        let (listener, broadcaster) = connect_robot(&bus, *colour);
+        (*colour, listener, broadcaster)
+    }).collect();
+
+    // Every clock joins the bus before the first robot starts, the negotiation then runs in lockstep on virtual time.
+    let clocks: Vec<_> = robots.iter().map(|_| bus.clock()).collect();
+    let handles: Vec<thread::JoinHandle<(char, char, u64)>> = robots.into_iter().zip(clocks).map(|((colour, listener, mut broadcaster), clock)| {
+        thread::spawn(move || {
+            let role = role_negotiation::negotiate_role(colour, 'T', Some(&listener), &mut broadcaster, &clock);
+            (colour, role, clock.now_ms())
+        })
+    }).collect();
+
+    let mut results: Vec<(char, char, u64)> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+    results.sort();
+    bus.close();
+
+    let roles: Vec<(char, char)> = results.iter().map(|(colour, role, _)| (*colour, *role)).collect();
+    assert_eq!(vec![('B', 'H'), ('G', 'S'), ('R', 'T')], roles, "Every robot asked for a tank, the team should still field a scout, a heavy and a tank.");
+    assert!(results.iter().all(|(_, _, time)| *time == role_negotiation::NEGOTIATION_TIME_MS), "Every robot should negotiate for exactly NEGOTIATION_TIME_MS of virtual time.");
+}
+
+#[test]
+fn teammates_focus_fire_on_a_shared_sighting(){
+    let bus = TeamBus::new(TeamBusConfig { latency_ms: 10, drop_rate: 0.0, reorder_rate: 0.0, seed: 2 });
+    let (_red_listener, mut red_broadcaster) = connect_robot(&bus, 'R');
+    let (green_listener, _green_broadcaster) = connect_robot(&bus, 'G');
+    let (blue_listener, _blue_broadcaster) = connect_robot(&bus, 'B');
+
+    red_broadcaster.start_round(6);
+    red_broadcaster.publish(TeamMessage::EnemySighting { enemy: 'Y', position: (2, -1, -1), seen_round: 6 });
+    red_broadcaster.publish(TeamMessage::EnemySighting { enemy: 'O', position: (-3, 0, 3), seen_round: 6 });
+
+    bus.advance(9);
+    assert_eq!(4, bus.in_flight(), "Nothing should arrive before the latency of the bus.");
+    bus.advance(1);
+    assert_eq!(0, bus.in_flight(), "Both sightings should reach both teammates once the latency passed.");
+
+    for (colour, listener, position) in [('G', &green_listener, (1, 0, -1)), ('B', &blue_listener, (0, -1, 1))] {
+        let mut player: Player = Player::initialize_player(colour.to_string(), 100 as u32, 5, tank_type::tank);
+        let mut game_board: board = board::initialize(5);
+        let mut coordinator: TargetCoordinator = TargetCoordinator::initialize(colour);
+
+        let messages: Vec<String> = (0..2).filter_map(|_| listener.next_message(RECEIVE_TIMEOUT)).collect();
+        assert_eq!(2, messages.len(), "{} should receive both sightings.", colour);
+        for message in messages {
+            let envelope = comms::parse_thread_comms(message, &mut player, &mut game_board).unwrap();
+            if let TeamMessage::EnemySighting { enemy, position, seen_round } = envelope.message {
+                coordinator.record_sighting(enemy, position, seen_round);
+            }
+        }
+
+        assert!(game_board.get_tile(2, -1, -1).unwrap().is_tank(), "{} should have the sighting of R on its board.", colour);
+        assert_eq!('Y', coordinator.choose_target(position, 6).unwrap().colour, "{} should focus fire on the closest enemy.", colour);
+    }
+    bus.close();
+}
+
+#[test]
+fn dropped_messages_never_arrive(){
+    let bus = TeamBus::new(TeamBusConfig { latency_ms: 0, drop_rate: 1.0, reorder_rate: 0.0, seed: 3 });
+    let (_red_listener, mut red_broadcaster) = connect_robot(&bus, 'R');
+    let (green_listener, _green_broadcaster) = connect_robot(&bus, 'G');
+
+    assert!(red_broadcaster.publish(TeamMessage::Health { health: 1 }), "Writing to the bus should succeed even if the message is lost.");
+    assert_eq!(0, bus.in_flight(), "A dropped message should not be on its way to the teammate.");
+
+    bus.close();
+    assert_eq!(None, green_listener.next_message(RECEIVE_TIMEOUT), "A dropped message should not reach the teammate.");
+}
+
+#[test]
+fn robots_talk_through_their_team_descriptors(){
+    let bus = TeamBus::new(TeamBusConfig::default());
+    let mut robots: Vec<(TeamListener, TeamBroadcaster)> = Vec::new();
+    for colour in ['R', 'G'] {
+        // The descriptors are given on the command line and opened the way the robot opens them.
+        let (team_in, team_out) = bus.connect_fds().unwrap();
+        let args: Vec<String> = ["team-delta", "T", "--team-in", &team_in.to_string(), "--team-out", &team_out.to_string()].iter().map(|arg| arg.to_string()).collect();
+        let (listener, broadcaster) = comms::open_team_channel(colour, &get_run_options(&args));
+
+        assert!(broadcaster.is_connected(), "{} should be able to write to its team descriptor.", colour);
+        robots.push((listener.expect("The team descriptor given with --team-in should be open."), broadcaster));
+    }
+
+    robots[0].1.publish(TeamMessage::Health { health: 2 });
+    robots[1].1.publish(TeamMessage::Pose { position: (1, -1, 0), facing: "S".to_string() });
+
+    let to_green = decode_message(&robots[1].0.next_message(RECEIVE_TIMEOUT).unwrap()).unwrap();
+    assert_eq!(('R', TeamMessage::Health { health: 2 }), (to_green.sender, to_green.message), "G should read what R wrote on its descriptor.");
+    let to_red = decode_message(&robots[0].0.next_message(RECEIVE_TIMEOUT).unwrap()).unwrap();
+    assert_eq!('G', to_red.sender, "R should read what G wrote on its descriptor.");
+
+    // Closing the bus ends the input of the robots, their listeners stop.
+    bus.close();
+    for (listener, _) in robots {
+        assert_eq!(None, listener.next_message(RECEIVE_TIMEOUT), "Nothing should arrive once the bus is closed.");
+        listener.shutdown();
+    }
+}